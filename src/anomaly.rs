@@ -0,0 +1,378 @@
+//! 支出の異常検知
+//!
+//! ジャンルごとの中央値と中央絶対偏差（MAD）を使って、金額が大きく外れた支出データと、
+//! 直近の月に比べて合計金額が急に増えた月を検出します。検出結果には理由の説明が付きます。
+//!
+//! ```
+//! use shishutsukan::anomaly::{detect, AnomalyOptions};
+//! use shishutsukan::ExpenseWithId;
+//!
+//! let mut expenses: Vec<ExpenseWithId> = (1..=8)
+//!     .map(|day| ExpenseWithId {
+//!         id: day,
+//!         date: format!("2025-01-{:02}", day),
+//!         genre: "食費".to_string(),
+//!         amount: 1000 + day * 10,
+//!     })
+//!     .collect();
+//! expenses.push(ExpenseWithId {
+//!     id: 9,
+//!     date: "2025-01-09".to_string(),
+//!     genre: "食費".to_string(),
+//!     amount: 25000,
+//! });
+//!
+//! let report = detect(&expenses, &[], &AnomalyOptions::default());
+//! assert_eq!(report.expenses[0].expense.id, 9);
+//! println!("{}", report.expenses[0].explanation());
+//! ```
+
+use crate::models::{ExpenseWithId, GenreWithId};
+use crate::report::{format_yen, Report};
+use chrono::Datelike;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// 正規分布での中央絶対偏差と標準偏差の比
+const MAD_SCALE: f64 = 0.6745;
+
+/// 正規分布での標準偏差と平均絶対偏差の比
+const MEAN_AD_SCALE: f64 = 1.253314; // sqrt(pi/2)
+
+/// 異常検知の条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyOptions {
+    /// 金額の異常とみなす修正zスコアの絶対値（既定値: 3.5）
+    pub score_threshold: f64,
+    /// 金額の異常を判定するのに必要なジャンルごとの件数（既定値: 5）
+    pub min_samples: usize,
+    /// 月の急増とみなす、直近の月の中央値に対する倍率（既定値: 2.0）
+    pub spike_ratio: f64,
+    /// 月の急増の判定で比べる直近の月数（既定値: 6）
+    pub baseline_months: usize,
+    /// 月の急増を判定するのに必要な直近の月数（既定値: 3）
+    pub min_baseline_months: usize,
+}
+
+impl Default for AnomalyOptions {
+    fn default() -> Self {
+        Self {
+            score_threshold: 3.5,
+            min_samples: 5,
+            spike_ratio: 2.0,
+            baseline_months: 6,
+            min_baseline_months: 3,
+        }
+    }
+}
+
+impl AnomalyOptions {
+    /// 金額の異常とみなす修正zスコアの絶対値を指定
+    pub fn score_threshold(mut self, threshold: f64) -> Self {
+        self.score_threshold = threshold;
+        self
+    }
+
+    /// 金額の異常を判定するのに必要なジャンルごとの件数を指定
+    pub fn min_samples(mut self, count: usize) -> Self {
+        self.min_samples = count;
+        self
+    }
+
+    /// 月の急増とみなす倍率を指定
+    pub fn spike_ratio(mut self, ratio: f64) -> Self {
+        self.spike_ratio = ratio;
+        self
+    }
+
+    /// 月の急増の判定で比べる直近の月数を指定
+    pub fn baseline_months(mut self, months: usize) -> Self {
+        self.baseline_months = months;
+        self
+    }
+
+    /// 月の急増を判定するのに必要な直近の月数を指定
+    pub fn min_baseline_months(mut self, months: usize) -> Self {
+        self.min_baseline_months = months;
+        self
+    }
+}
+
+/// 金額が大きく外れた支出データ
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpenseAnomaly {
+    /// 支出データ
+    pub expense: ExpenseWithId,
+    /// ジャンルの金額の中央値
+    pub median: f64,
+    /// ジャンルの金額の中央絶対偏差
+    pub mad: f64,
+    /// 修正zスコア（中央値より大きい場合は正）
+    pub score: f64,
+}
+
+impl ExpenseAnomaly {
+    /// 検出した理由の説明
+    pub fn explanation(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for ExpenseAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} の{} {} は{}の中央値 {} ",
+            self.expense.date,
+            self.expense.genre,
+            format_yen(i64::from(self.expense.amount)),
+            self.expense.genre,
+            format_yen(self.median.round() as i64),
+        )?;
+        // 中央値が0以下の場合は倍率に意味がないため省く
+        if self.median > 0.0 {
+            write!(
+                f,
+                "の{:.1}倍です",
+                f64::from(self.expense.amount) / self.median
+            )?;
+        } else {
+            write!(f, "から外れています")?;
+        }
+        write!(f, "（スコア {:.1}）", self.score)
+    }
+}
+
+/// 合計金額が急に増えた月
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthAnomaly {
+    /// ジャンル
+    pub genre: String,
+    /// 月（`2025-01` の形式）
+    pub month: String,
+    /// 月の合計金額
+    pub total: i64,
+    /// 比べた直近の月数
+    pub baseline_months: usize,
+    /// 直近の月の合計金額の中央値
+    pub baseline_median: f64,
+    /// 中央値に対する倍率
+    pub ratio: f64,
+}
+
+impl MonthAnomaly {
+    /// 検出した理由の説明
+    pub fn explanation(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for MonthAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} の{}の合計 {} は直近{}か月の中央値 {} の{:.1}倍です",
+            self.month,
+            self.genre,
+            format_yen(self.total),
+            self.baseline_months,
+            format_yen(self.baseline_median.round() as i64),
+            self.ratio
+        )
+    }
+}
+
+/// 異常検知の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnomalyReport {
+    /// 金額が大きく外れた支出データ（スコアの絶対値の大きい順）
+    pub expenses: Vec<ExpenseAnomaly>,
+    /// 合計金額が急に増えた月（倍率の大きい順）
+    pub months: Vec<MonthAnomaly>,
+}
+
+impl AnomalyReport {
+    /// 異常が見つからなかったかどうか
+    pub fn is_empty(&self) -> bool {
+        self.expenses.is_empty() && self.months.is_empty()
+    }
+
+    /// すべての検出結果の説明
+    pub fn explanations(&self) -> Vec<String> {
+        self.expenses
+            .iter()
+            .map(ExpenseAnomaly::explanation)
+            .chain(self.months.iter().map(MonthAnomaly::explanation))
+            .collect()
+    }
+}
+
+/// 支出データの異常を検出する
+///
+/// 金額の異常は、ジャンルごとに中央値からの隔たりを中央絶対偏差で割った修正zスコアで
+/// 判定します。月の急増は、ジャンルの最初の支出以降の直近の月（支出がない月は0円）の
+/// 合計金額の中央値と比べて判定します。日付を解釈できない支出データは月の急増の判定に
+/// 含めません。
+///
+/// # Arguments
+///
+/// * `expenses` - 支出データの一覧
+/// * `genres` - 対象のジャンルの一覧（空の場合はすべてのジャンルが対象）
+/// * `options` - 異常検知の条件
+pub fn detect(
+    expenses: &[ExpenseWithId],
+    genres: &[GenreWithId],
+    options: &AnomalyOptions,
+) -> AnomalyReport {
+    let expenses: Vec<ExpenseWithId> = expenses
+        .iter()
+        .filter(|expense| genres.is_empty() || genres.iter().any(|g| g.name == expense.genre))
+        .cloned()
+        .collect();
+    let mut report = AnomalyReport {
+        expenses: expense_anomalies(&expenses, options),
+        months: month_anomalies(&expenses, options),
+    };
+    report
+        .expenses
+        .sort_by(|a, b| b.score.abs().total_cmp(&a.score.abs()));
+    report.months.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+    report
+}
+
+fn expense_anomalies(expenses: &[ExpenseWithId], options: &AnomalyOptions) -> Vec<ExpenseAnomaly> {
+    let mut by_genre: BTreeMap<&str, Vec<&ExpenseWithId>> = BTreeMap::new();
+    for expense in expenses {
+        by_genre.entry(&expense.genre).or_default().push(expense);
+    }
+
+    let mut anomalies = Vec::new();
+    for rows in by_genre.values() {
+        if rows.len() < options.min_samples {
+            continue;
+        }
+        let amounts: Vec<f64> = rows.iter().map(|row| f64::from(row.amount)).collect();
+        let center = median(&amounts);
+        let deviations: Vec<f64> = amounts
+            .iter()
+            .map(|amount| (amount - center).abs())
+            .collect();
+        let mad = median(&deviations);
+        // 半数以上が同じ金額の場合は平均絶対偏差で代用する
+        let scale = if mad > 0.0 {
+            mad / MAD_SCALE
+        } else {
+            deviations.iter().sum::<f64>() / deviations.len() as f64 * MEAN_AD_SCALE
+        };
+        if scale <= 0.0 {
+            continue;
+        }
+        for row in rows {
+            let score = (f64::from(row.amount) - center) / scale;
+            if score.abs() >= options.score_threshold {
+                anomalies.push(ExpenseAnomaly {
+                    expense: (*row).clone(),
+                    median: center,
+                    mad,
+                    score,
+                });
+            }
+        }
+    }
+    anomalies
+}
+
+fn month_anomalies(expenses: &[ExpenseWithId], options: &AnomalyOptions) -> Vec<MonthAnomaly> {
+    let report = Report::new(expenses);
+    let mut anomalies = Vec::new();
+    for genre in &report.genres {
+        let (Some(first), Some(last)) = (genre.months.first(), genre.months.last()) else {
+            continue;
+        };
+        let (Some(first), Some(last)) = (month_index(&first.period), month_index(&last.period))
+        else {
+            continue;
+        };
+        let totals: Vec<(String, i64)> = (first..=last)
+            .map(|index| {
+                let month = format!("{:04}-{:02}", index / 12, index % 12 + 1);
+                let total = genre.month_total(&month);
+                (month, total)
+            })
+            .collect();
+
+        for (position, (month, total)) in totals.iter().enumerate() {
+            let baseline: Vec<f64> = totals
+                [position.saturating_sub(options.baseline_months)..position]
+                .iter()
+                .map(|(_, total)| *total as f64)
+                .collect();
+            if baseline.len() < options.min_baseline_months.max(1) {
+                continue;
+            }
+            let baseline_median = median(&baseline);
+            if baseline_median <= 0.0 {
+                continue;
+            }
+            let ratio = *total as f64 / baseline_median;
+            if ratio >= options.spike_ratio {
+                anomalies.push(MonthAnomaly {
+                    genre: genre.genre.clone(),
+                    month: month.clone(),
+                    total: *total,
+                    baseline_months: baseline.len(),
+                    baseline_median,
+                    ratio,
+                });
+            }
+        }
+    }
+    anomalies
+}
+
+/// `2025-01` 形式の月を年×12+月-1 にする
+fn month_index(month: &str) -> Option<i32> {
+    let date = chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    Some(date.year() * 12 + date.month0() as i32)
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    match sorted.len() {
+        0 => 0.0,
+        // 奇数個の場合は2つの添字がどちらも中央の値を指す
+        len => (sorted[(len - 1) / 2] + sorted[len / 2]) / 2.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_index() {
+        assert_eq!(month_index("2025-01"), Some(2025 * 12));
+        assert_eq!(month_index("2024-12"), Some(2025 * 12 - 1));
+        assert_eq!(month_index("2025-13"), None);
+    }
+
+    #[test]
+    fn test_explanation_without_ratio_for_zero_median() {
+        let anomaly = |median| ExpenseAnomaly {
+            expense: ExpenseWithId {
+                id: 1,
+                date: "2025-01-15".to_string(),
+                genre: "食費".to_string(),
+                amount: 5000,
+            },
+            median,
+            mad: 0.0,
+            score: 4.0,
+        };
+        assert!(anomaly(1000.0).explanation().contains("の5.0倍です"));
+        let explanation = anomaly(0.0).explanation();
+        assert!(!explanation.contains("inf") && !explanation.contains("倍"));
+        assert!(explanation.contains("中央値 ¥0 から外れています"));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::error::{Result, ShishutsukanError};
+use crate::models::{Expense, ExpenseWithId, GenreWithId};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+
+/// バックアップ形式のバージョン
+pub const BACKUP_VERSION: u32 = 1;
+
+/// バックアップの書き出し形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackupFormat {
+    /// 1つのJSONドキュメント
+    #[default]
+    Json,
+    /// 1行に1レコードのJSON Lines（先頭行はバージョンを含むヘッダー）
+    Ndjson,
+}
+
+/// ジャンルと支出データの全件バックアップ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    /// バックアップ形式のバージョン
+    pub version: u32,
+    /// バックアップを作成した日時（RFC 3339形式）
+    pub created_at: String,
+    /// ジャンルの一覧
+    pub genres: Vec<GenreWithId>,
+    /// 支出データの一覧
+    pub expenses: Vec<ExpenseWithId>,
+}
+
+/// JSON Lines形式の1行
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum NdjsonRecord {
+    Header { version: u32, created_at: String },
+    Genre(GenreWithId),
+    Expense(ExpenseWithId),
+}
+
+impl Backup {
+    /// 現在の日時でバックアップを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `genres` - ジャンルの一覧
+    /// * `expenses` - 支出データの一覧
+    pub fn new(genres: Vec<GenreWithId>, expenses: Vec<ExpenseWithId>) -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            created_at: Utc::now().to_rfc3339(),
+            genres,
+            expenses,
+        }
+    }
+
+    /// バックアップを書き出す
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - 書き込み先
+    /// * `format` - 書き出し形式
+    pub fn write_to<W: Write>(&self, mut writer: W, format: BackupFormat) -> Result<()> {
+        match format {
+            BackupFormat::Json => {
+                serde_json::to_writer_pretty(&mut writer, self)?;
+                writeln!(writer)?;
+            }
+            BackupFormat::Ndjson => {
+                let header = NdjsonRecord::Header {
+                    version: self.version,
+                    created_at: self.created_at.clone(),
+                };
+                let records = std::iter::once(header)
+                    .chain(self.genres.iter().cloned().map(NdjsonRecord::Genre))
+                    .chain(self.expenses.iter().cloned().map(NdjsonRecord::Expense));
+                for record in records {
+                    serde_json::to_writer(&mut writer, &record)?;
+                    writeln!(writer)?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// バックアップを読み込む
+    ///
+    /// JSONとJSON Linesのどちらの形式かは内容から判別します。
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - 読み込み元
+    ///
+    /// # Errors
+    ///
+    /// 対応していないバージョンの場合や、JSON Lines形式の先頭行がヘッダーでない場合は
+    /// [`ShishutsukanError::ParseError`] を返します。
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let backup = match lines.next().map(serde_json::from_str::<NdjsonRecord>) {
+            Some(Ok(NdjsonRecord::Header {
+                version,
+                created_at,
+            })) => {
+                let mut backup = Self {
+                    version,
+                    created_at,
+                    genres: Vec::new(),
+                    expenses: Vec::new(),
+                };
+                for line in lines {
+                    match serde_json::from_str(line)? {
+                        NdjsonRecord::Genre(genre) => backup.genres.push(genre),
+                        NdjsonRecord::Expense(expense) => backup.expenses.push(expense),
+                        NdjsonRecord::Header { .. } => {
+                            return Err(ShishutsukanError::ParseError(
+                                "ヘッダー行が複数あります".to_string(),
+                            ))
+                        }
+                    }
+                }
+                backup
+            }
+            Some(Ok(_)) => {
+                return Err(ShishutsukanError::ParseError(
+                    "先頭行がヘッダーではありません".to_string(),
+                ))
+            }
+            _ => serde_json::from_str(&contents)?,
+        };
+
+        if backup.version > BACKUP_VERSION {
+            return Err(ShishutsukanError::ParseError(format!(
+                "対応していないバックアップのバージョンです: {}",
+                backup.version
+            )));
+        }
+        Ok(backup)
+    }
+}
+
+/// 復元の方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestoreMode {
+    /// 支出データのないサーバーにのみ復元する（既にある場合はエラー）
+    Empty,
+    /// 既存のデータに統合する（日付・ジャンル・金額が同じ支出データは追加しない）
+    #[default]
+    Merge,
+}
+
+/// 復元の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RestoreSummary {
+    /// 作成したジャンルの件数
+    pub genres_restored: usize,
+    /// サーバーに既にあったジャンルの件数
+    pub genres_existing: usize,
+    /// 追加した支出データの件数
+    pub expenses_restored: usize,
+    /// サーバーに同じ内容で既にあった支出データの件数
+    pub expenses_existing: usize,
+    /// バックアップ上のジャンルIDから復元先のジャンルIDへの対応
+    pub genre_ids: HashMap<i32, i32>,
+    /// バックアップ上の支出データIDから復元先の支出データIDへの対応
+    pub expense_ids: HashMap<i32, i32>,
+    /// 作成に失敗したジャンルとエラーメッセージ
+    pub failed_genres: Vec<(GenreWithId, String)>,
+    /// 追加に失敗した支出データとエラーメッセージ
+    pub failed_expenses: Vec<(ExpenseWithId, String)>,
+}
+
+/// 日付・ジャンル・金額ごとに支出データのIDを割り当てる
+///
+/// 同じ内容の支出データが複数ある場合も、1つのIDを2回割り当てないようにします。
+#[derive(Debug, Default)]
+pub(crate) struct ContentIndex {
+    ids: HashMap<(String, String, i32), VecDeque<i32>>,
+}
+
+impl ContentIndex {
+    /// 支出データからID順に割り当てる索引を作成
+    pub(crate) fn new<'a>(expenses: impl IntoIterator<Item = &'a ExpenseWithId>) -> Self {
+        let mut expenses: Vec<&ExpenseWithId> = expenses.into_iter().collect();
+        expenses.sort_by_key(|expense| expense.id);
+
+        let mut index = Self::default();
+        for expense in expenses {
+            index
+                .ids
+                .entry((expense.date.clone(), expense.genre.clone(), expense.amount))
+                .or_default()
+                .push_back(expense.id);
+        }
+        index
+    }
+
+    /// 同じ内容の支出データのうち、まだ割り当てていないもののIDを取り出す
+    pub(crate) fn claim(&mut self, expense: &Expense) -> Option<i32> {
+        self.ids
+            .get_mut(&(expense.date.clone(), expense.genre.clone(), expense.amount))?
+            .pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(id: i32, amount: i32) -> ExpenseWithId {
+        ExpenseWithId {
+            id,
+            date: "2025-01-15".to_string(),
+            genre: "食費".to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_content_index_claims_each_id_once() {
+        let expenses = vec![expense(5, 100), expense(2, 100), expense(3, 200)];
+        let mut index = ContentIndex::new(&expenses);
+
+        let content = expense(0, 100).to_expense();
+        assert_eq!(index.claim(&content), Some(2));
+        assert_eq!(index.claim(&content), Some(5));
+        assert_eq!(index.claim(&content), None);
+    }
+
+    #[test]
+    fn test_ndjson_round_trip() {
+        let backup = Backup::new(Vec::new(), vec![expense(1, 100), expense(2, 200)]);
+        let mut output = Vec::new();
+        backup.write_to(&mut output, BackupFormat::Ndjson).unwrap();
+
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert!(text.starts_with(r#"{"type":"header","version":1,"#));
+        assert_eq!(text.lines().count(), 3);
+        assert_eq!(Backup::read_from(output.as_slice()).unwrap(), backup);
+    }
+}
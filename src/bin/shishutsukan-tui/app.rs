@@ -0,0 +1,454 @@
+//! 画面の状態とキー操作
+
+use chrono::{Local, NaiveDate};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::{ListState, TableState};
+use shishutsukan::report::Report;
+use shishutsukan::{Expense, ExpenseWithId, GenreWithId};
+
+/// 操作の対象になっている領域
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    /// ジャンルの一覧
+    Genres,
+    /// 支出データの一覧
+    Expenses,
+}
+
+/// 追加フォームの項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// 日付
+    Date,
+    /// ジャンル
+    Genre,
+    /// 金額
+    Amount,
+}
+
+/// 支出データの追加フォーム
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddForm {
+    /// 日付（YYYY-MM-DD）
+    pub date: String,
+    /// ジャンルの一覧での位置
+    pub genre: usize,
+    /// 金額
+    pub amount: String,
+    /// 入力中の項目
+    pub field: Field,
+    /// 入力内容の誤り
+    pub error: Option<String>,
+}
+
+/// 入力モード
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    /// 一覧の操作
+    Normal,
+    /// 絞り込み文字列の入力
+    Filter,
+    /// 支出データの追加
+    Add(AddForm),
+    /// 削除の確認
+    ConfirmDelete(ExpenseWithId),
+}
+
+/// キー操作の結果、サーバーに対して行う処理
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// 終了
+    Quit,
+    /// 再読み込み
+    Reload,
+    /// 支出データを追加
+    Add(Expense),
+    /// 支出データを削除
+    Delete(i32),
+}
+
+/// 画面の状態
+#[derive(Debug)]
+pub struct App {
+    /// 支出データの一覧
+    pub expenses: Vec<ExpenseWithId>,
+    /// ジャンルの一覧
+    pub genres: Vec<GenreWithId>,
+    /// ジャンルの一覧の選択（0は「すべて」）
+    pub genre_state: ListState,
+    /// 支出データの一覧の選択
+    pub expense_state: TableState,
+    /// 操作の対象になっている領域
+    pub focus: Focus,
+    /// 入力モード
+    pub mode: Mode,
+    /// 絞り込み文字列
+    pub filter: String,
+    /// 状態表示欄のメッセージ
+    pub status: Option<String>,
+    /// 追加フォームで最初に選ぶジャンル（選択中のジャンルがない場合）
+    pub default_genre: Option<String>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            expenses: Vec::new(),
+            genres: Vec::new(),
+            genre_state: ListState::default().with_selected(Some(0)),
+            expense_state: TableState::default(),
+            focus: Focus::Expenses,
+            mode: Mode::Normal,
+            filter: String::new(),
+            status: None,
+            default_genre: None,
+        }
+    }
+}
+
+impl App {
+    /// サーバーから取得したデータを設定する
+    pub fn set_data(&mut self, expenses: Vec<ExpenseWithId>, genres: Vec<GenreWithId>) {
+        self.expenses = expenses;
+        self.genres = genres;
+        let genre = self
+            .genre_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.genres.len());
+        self.genre_state.select(Some(genre));
+        self.clamp_selection();
+    }
+
+    /// ジャンルの一覧で選択中のジャンル（「すべて」の場合は `None`）
+    pub fn selected_genre(&self) -> Option<&str> {
+        match self.genre_state.selected() {
+            Some(index) if index > 0 => self.genres.get(index - 1).map(|g| g.name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// ジャンルと絞り込み文字列で絞り込んだ支出データ（新しい順）
+    pub fn visible(&self) -> Vec<&ExpenseWithId> {
+        let genre = self.selected_genre();
+        let mut visible: Vec<&ExpenseWithId> = self
+            .expenses
+            .iter()
+            .filter(|expense| genre.is_none_or(|genre| expense.genre == genre))
+            .filter(|expense| {
+                self.filter.is_empty()
+                    || expense.date.contains(&self.filter)
+                    || expense.genre.contains(&self.filter)
+                    || expense.amount.to_string().contains(&self.filter)
+            })
+            .collect();
+        visible.sort_by(|a, b| b.date.cmp(&a.date).then(b.id.cmp(&a.id)));
+        visible
+    }
+
+    /// 選択中の支出データ
+    pub fn selected_expense(&self) -> Option<&ExpenseWithId> {
+        let id = self.visible().get(self.expense_state.selected()?)?.id;
+        self.expenses.iter().find(|expense| expense.id == id)
+    }
+
+    /// 絞り込んだ支出データの月ごとの合計（新しい順）
+    pub fn monthly_totals(&self) -> Vec<(String, i64)> {
+        let visible: Vec<ExpenseWithId> = self.visible().into_iter().cloned().collect();
+        Report::new(&visible)
+            .months
+            .into_iter()
+            .rev()
+            .map(|month| (month.period, month.stats.total))
+            .collect()
+    }
+
+    /// キー操作を処理する
+    ///
+    /// # Returns
+    ///
+    /// サーバーに対して行う処理がある場合はその内容
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Action::Quit);
+        }
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal(key),
+            Mode::Filter => {
+                self.handle_filter(key);
+                None
+            }
+            Mode::Add(form) => self.handle_add(form, key),
+            Mode::ConfirmDelete(expense) => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => Some(Action::Delete(expense.id)),
+                KeyCode::Char('n') | KeyCode::Esc => None,
+                _ => {
+                    self.mode = Mode::ConfirmDelete(expense);
+                    None
+                }
+            },
+        }
+    }
+
+    fn handle_normal(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('q') => return Some(Action::Quit),
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.clamp_selection();
+            }
+            KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Char('r') => return Some(Action::Reload),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Left | KeyCode::Right => {
+                self.focus = match self.focus {
+                    Focus::Genres => Focus::Expenses,
+                    Focus::Expenses => Focus::Genres,
+                };
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(isize::MAX),
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Char('a') => {
+                if self.genres.is_empty() {
+                    self.status = Some("ジャンルがありません".to_string());
+                } else {
+                    let genre = match (self.genre_state.selected(), &self.default_genre) {
+                        (Some(index), _) if index > 0 => index - 1,
+                        (_, Some(default)) => self
+                            .genres
+                            .iter()
+                            .position(|genre| genre.name == *default)
+                            .unwrap_or(0),
+                        _ => 0,
+                    };
+                    self.mode = Mode::Add(AddForm {
+                        date: Local::now().date_naive().to_string(),
+                        genre,
+                        amount: String::new(),
+                        field: Field::Amount,
+                        error: None,
+                    });
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(expense) = self.selected_expense() {
+                    self.mode = Mode::ConfirmDelete(expense.clone());
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_filter(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => return,
+            KeyCode::Esc => self.filter.clear(),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.mode = Mode::Filter;
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.mode = Mode::Filter;
+            }
+            _ => {
+                self.mode = Mode::Filter;
+                return;
+            }
+        }
+        self.expense_state.select(Some(0));
+        self.clamp_selection();
+    }
+
+    fn handle_add(&mut self, mut form: AddForm, key: KeyEvent) -> Option<Action> {
+        const FIELDS: [Field; 3] = [Field::Date, Field::Genre, Field::Amount];
+        let position = FIELDS.iter().position(|f| *f == form.field).unwrap_or(0);
+        match (key.code, form.field) {
+            (KeyCode::Esc, _) => return None,
+            (KeyCode::Enter, _) => match form.to_expense(&self.genres) {
+                Ok(expense) => return Some(Action::Add(expense)),
+                Err(message) => form.error = Some(message),
+            },
+            (KeyCode::Tab | KeyCode::Down, _) => form.field = FIELDS[(position + 1) % 3],
+            (KeyCode::BackTab | KeyCode::Up, _) => form.field = FIELDS[(position + 2) % 3],
+            (KeyCode::Left, Field::Genre) => {
+                form.genre = (form.genre + self.genres.len() - 1) % self.genres.len()
+            }
+            (KeyCode::Right | KeyCode::Char(' '), Field::Genre) => {
+                form.genre = (form.genre + 1) % self.genres.len()
+            }
+            (KeyCode::Backspace, Field::Date) => {
+                form.date.pop();
+            }
+            (KeyCode::Backspace, Field::Amount) => {
+                form.amount.pop();
+            }
+            (KeyCode::Char(c), Field::Date) if c.is_ascii_digit() || c == '-' => form.date.push(c),
+            (KeyCode::Char(c), Field::Amount) if c.is_ascii_digit() => form.amount.push(c),
+            _ => {}
+        }
+        self.mode = Mode::Add(form);
+        None
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let (len, selected) = match self.focus {
+            Focus::Genres => (self.genres.len() + 1, self.genre_state.selected()),
+            Focus::Expenses => (self.visible().len(), self.expense_state.selected()),
+        };
+        if len == 0 {
+            return;
+        }
+        let next = selected
+            .unwrap_or(0)
+            .saturating_add_signed(delta)
+            .min(len - 1);
+        match self.focus {
+            Focus::Genres => {
+                self.genre_state.select(Some(next));
+                self.expense_state.select(Some(0));
+                self.clamp_selection();
+            }
+            Focus::Expenses => self.expense_state.select(Some(next)),
+        }
+    }
+
+    /// 支出データの一覧の選択を表示中の範囲に収める
+    fn clamp_selection(&mut self) {
+        let len = self.visible().len();
+        let selected = match self.expense_state.selected() {
+            _ if len == 0 => None,
+            Some(index) => Some(index.min(len - 1)),
+            None => Some(0),
+        };
+        self.expense_state.select(selected);
+    }
+}
+
+impl AddForm {
+    /// 入力内容から支出データを作成する
+    fn to_expense(&self, genres: &[GenreWithId]) -> Result<Expense, String> {
+        let date = NaiveDate::parse_from_str(&self.date, "%Y-%m-%d")
+            .map_err(|_| "日付は YYYY-MM-DD の形式で入力してください".to_string())?;
+        let genre = genres
+            .get(self.genre)
+            .ok_or_else(|| "ジャンルを選択してください".to_string())?;
+        let amount: i32 = self
+            .amount
+            .parse()
+            .map_err(|_| "金額を入力してください".to_string())?;
+        Ok(Expense::new(date.to_string(), genre.name.clone(), amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, code: KeyCode) -> Option<Action> {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn app() -> App {
+        let expense = |id, date: &str, genre: &str, amount| ExpenseWithId {
+            id,
+            date: date.to_string(),
+            genre: genre.to_string(),
+            amount,
+        };
+        let genre = |id, name: &str| GenreWithId {
+            id,
+            name: name.to_string(),
+            created_at: "2025-01-01 00:00:00".to_string(),
+        };
+        let mut app = App::default();
+        app.set_data(
+            vec![
+                expense(1, "2025-01-10", "食費", 1200),
+                expense(2, "2025-01-15", "交通費", 500),
+                expense(3, "2025-02-01", "食費", 800),
+            ],
+            vec![genre(1, "食費"), genre(2, "交通費")],
+        );
+        app
+    }
+
+    #[test]
+    fn test_filter_by_genre_and_text() {
+        let mut app = app();
+        assert_eq!(app.visible()[0].id, 3);
+
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_genre(), Some("食費"));
+        let ids: Vec<i32> = app.visible().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert_eq!(app.monthly_totals()[0], ("2025-02".to_string(), 800));
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "01-10".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Normal);
+        let ids: Vec<i32> = app.visible().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1]);
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.filter.is_empty());
+    }
+
+    #[test]
+    fn test_delete_requires_confirmation() {
+        let mut app = app();
+        press(&mut app, KeyCode::Down);
+        assert_eq!(press(&mut app, KeyCode::Char('d')), None);
+        assert!(matches!(app.mode, Mode::ConfirmDelete(ref e) if e.id == 2));
+        assert_eq!(press(&mut app, KeyCode::Char('n')), None);
+        assert_eq!(app.mode, Mode::Normal);
+
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(press(&mut app, KeyCode::Char('y')), Some(Action::Delete(2)));
+    }
+
+    #[test]
+    fn test_add_form() {
+        let mut app = app();
+        app.default_genre = Some("交通費".to_string());
+        press(&mut app, KeyCode::Char('a'));
+        assert!(matches!(app.mode, Mode::Add(ref form) if form.genre == 1));
+        press(&mut app, KeyCode::Esc);
+
+        app.default_genre = None;
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(press(&mut app, KeyCode::Enter), None);
+        assert!(matches!(app.mode, Mode::Add(ref form) if form.error.is_some()));
+
+        for c in "1500".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Up);
+        for _ in 0..10 {
+            press(&mut app, KeyCode::Backspace);
+        }
+        for c in "2025-03-01".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(
+            press(&mut app, KeyCode::Enter),
+            Some(Action::Add(Expense::new(
+                "2025-03-01".to_string(),
+                "交通費".to_string(),
+                1500
+            )))
+        );
+        assert_eq!(app.mode, Mode::Normal);
+    }
+}
@@ -0,0 +1,126 @@
+//! `shishutsukan-tui` コマンド
+//!
+//! 支出データの一覧・ジャンル・月ごとの合計を表示し、キー操作で追加・削除・絞り込みを
+//! 行う端末用のUIです。`tui` フィーチャーを有効にするとビルドされます。
+//!
+//! ```text
+//! $ shishutsukan-tui --profile home
+//! $ shishutsukan-tui http://localhost:8000
+//! ```
+//!
+//! 接続先は [`shishutsukan::config`] の設定ファイルのプロファイルと環境変数から決まり、
+//! URLを指定した場合はそれが優先されます。
+
+mod app;
+mod ui;
+
+use app::{Action, App};
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+use shishutsukan::config::{Config, Profile};
+use shishutsukan::{Result, ShishutsukanClient, ShishutsukanError};
+use std::process::ExitCode;
+
+const USAGE: &str = "使い方: shishutsukan-tui [--profile <名前>] [URL]";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match start(&args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("エラー: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 引数で指定したプロファイルを読み込む
+fn load_profile(args: &[String]) -> Result<Profile> {
+    let mut name = None;
+    let mut url = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--profile" => name = args.next().cloned(),
+            arg if url.is_none() && !arg.starts_with('-') => url = Some(arg.to_string()),
+            _ => return Err(ShishutsukanError::ConfigError(USAGE.to_string())),
+        }
+    }
+    let mut profile = Config::load()?.profile(name.as_deref())?;
+    if url.is_some() {
+        profile.base_url = url;
+    }
+    Ok(profile)
+}
+
+async fn start(args: &[String]) -> Result<()> {
+    let profile = load_profile(args)?;
+    let client = profile.client()?;
+    let mut app = App {
+        default_genre: profile.default_genre.clone(),
+        ..App::default()
+    };
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &client, &mut app, profile.base_url()).await;
+    ratatui::restore();
+    result
+}
+
+async fn run(
+    terminal: &mut DefaultTerminal,
+    client: &ShishutsukanClient,
+    app: &mut App,
+    url: &str,
+) -> Result<()> {
+    reload(client, app).await;
+
+    loop {
+        terminal.draw(|frame| ui::draw(frame, app, url))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        app.status = None;
+
+        match app.handle_key(key) {
+            None => {}
+            Some(Action::Quit) => return Ok(()),
+            Some(Action::Reload) => reload(client, app).await,
+            Some(Action::Add(expense)) => match client.add_expense(&expense).await {
+                Ok(_) => {
+                    reload(client, app).await;
+                    app.status = Some(format!(
+                        "{} の{}を追加しました",
+                        expense.date, expense.genre
+                    ));
+                }
+                Err(e) => app.status = Some(format!("追加できませんでした: {}", e)),
+            },
+            Some(Action::Delete(id)) => match client.delete_expense(id).await {
+                Ok(_) => {
+                    reload(client, app).await;
+                    app.status = Some(format!("ID {} を削除しました", id));
+                }
+                Err(e) => app.status = Some(format!("削除できませんでした: {}", e)),
+            },
+        }
+    }
+}
+
+/// サーバーから支出データとジャンルを読み込み直す
+///
+/// 読み込めなかった場合は状態表示欄にエラーを表示し、表示中のデータはそのまま残します。
+async fn reload(client: &ShishutsukanClient, app: &mut App) {
+    let result = tokio::try_join!(client.get_expenses(), client.get_genres());
+    match result {
+        Ok((expenses, genres)) => app.set_data(expenses, genres),
+        Err(e) => app.status = Some(format!("読み込めませんでした: {}（r で再試行）", e)),
+    }
+}
@@ -0,0 +1,225 @@
+//! 画面の描画
+
+use crate::app::{AddForm, App, Field, Focus, Mode};
+use ratatui::layout::{Alignment, Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Clear, List, Paragraph, Row, Table};
+use ratatui::Frame;
+use shishutsukan::report::format_yen;
+
+/// 一覧の選択行のスタイル
+const HIGHLIGHT: Style = Style::new().add_modifier(Modifier::REVERSED);
+
+/// 画面全体を描画する
+pub fn draw(frame: &mut Frame, app: &mut App, url: &str) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [genres, expenses, months] = Layout::horizontal([
+        Constraint::Length(20),
+        Constraint::Min(40),
+        Constraint::Length(22),
+    ])
+    .areas(main);
+
+    draw_genres(frame, app, genres);
+    draw_expenses(frame, app, expenses, url);
+    draw_months(frame, app, months);
+    draw_status(frame, app, status);
+
+    match &app.mode {
+        Mode::Add(form) => draw_add_form(frame, app, form),
+        Mode::ConfirmDelete(expense) => {
+            let text = format!(
+                "{} {} {} を削除しますか？",
+                expense.date,
+                expense.genre,
+                format_yen(i64::from(expense.amount))
+            );
+            let lines = vec![
+                Line::from(text),
+                Line::from(""),
+                Line::from("y:削除  n:取消").dark_gray(),
+            ];
+            popup(frame, "削除の確認", lines, 50);
+        }
+        Mode::Normal | Mode::Filter => {}
+    }
+}
+
+fn block(title: String, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().fg(Color::Yellow))
+    } else {
+        block
+    }
+}
+
+fn draw_genres(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items = std::iter::once("すべて".to_string())
+        .chain(app.genres.iter().map(|genre| genre.name.clone()));
+    let list = List::new(items)
+        .block(block("ジャンル".to_string(), app.focus == Focus::Genres))
+        .highlight_style(HIGHLIGHT);
+    frame.render_stateful_widget(list, area, &mut app.genre_state);
+}
+
+fn draw_expenses(frame: &mut Frame, app: &mut App, area: Rect, url: &str) {
+    let visible = app.visible();
+    let total: i64 = visible.iter().map(|e| i64::from(e.amount)).sum();
+    let mut title = format!("支出 {}件 {} - {}", visible.len(), format_yen(total), url);
+    if !app.filter.is_empty() {
+        title.push_str(&format!(" [絞り込み: {}]", app.filter));
+    }
+    let rows: Vec<Row> = visible
+        .iter()
+        .map(|expense| {
+            Row::new(vec![
+                Cell::from(expense.date.clone()),
+                Cell::from(expense.genre.clone()),
+                right(format_yen(i64::from(expense.amount))),
+                right(expense.id.to_string()).dark_gray(),
+            ])
+        })
+        .collect();
+    let header = Row::new(vec![
+        Cell::from("日付"),
+        Cell::from("ジャンル"),
+        right("金額".to_string()),
+        right("ID".to_string()),
+    ])
+    .bold();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Min(8),
+            Constraint::Length(12),
+            Constraint::Length(6),
+        ],
+    )
+    .header(header)
+    .block(block(title, app.focus == Focus::Expenses))
+    .row_highlight_style(HIGHLIGHT);
+    frame.render_stateful_widget(table, area, &mut app.expense_state);
+}
+
+fn draw_months(frame: &mut Frame, app: &App, area: Rect) {
+    let rows: Vec<Row> = app
+        .monthly_totals()
+        .into_iter()
+        .map(|(month, total)| Row::new(vec![Cell::from(month), right(format_yen(total))]))
+        .collect();
+    let table = Table::new(rows, [Constraint::Length(7), Constraint::Min(10)])
+        .block(block("月ごとの合計".to_string(), false));
+    frame.render_widget(table, area);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let line = match (&app.mode, &app.status) {
+        (Mode::Filter, _) => Line::from(vec![
+            Span::raw("/"),
+            Span::raw(app.filter.as_str()),
+            Span::raw("█").slow_blink(),
+            Span::raw("  Enter:確定 Esc:解除").dark_gray(),
+        ]),
+        (_, Some(status)) => Line::from(status.as_str()),
+        (_, None) => {
+            Line::from("a:追加 d:削除 /:絞り込み Tab:ジャンル⇔一覧 r:再読み込み q:終了").dark_gray()
+        }
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_add_form(frame: &mut Frame, app: &App, form: &AddForm) {
+    let field = |label: &str, value: String, current: bool| {
+        let value = if current {
+            Span::styled(value, HIGHLIGHT)
+        } else {
+            Span::raw(value)
+        };
+        Line::from(vec![Span::raw(format!("{:　<4} ", label)), value])
+    };
+    let genre = app
+        .genres
+        .get(form.genre)
+        .map(|genre| format!("< {} >", genre.name))
+        .unwrap_or_default();
+    let mut lines = vec![
+        field("日付", form.date.clone(), form.field == Field::Date),
+        field("ジャンル", genre, form.field == Field::Genre),
+        field("金額", form.amount.clone(), form.field == Field::Amount),
+        Line::from(""),
+    ];
+    if let Some(error) = &form.error {
+        lines.push(Line::from(error.as_str()).red());
+    }
+    lines.push(Line::from("Tab:次の項目 ←→:ジャンル Enter:追加 Esc:取消").dark_gray());
+    popup(frame, "支出データの追加", lines, 50);
+}
+
+/// 画面の中央にダイアログを表示する
+fn popup(frame: &mut Frame, title: &str, lines: Vec<Line>, width: u16) {
+    let height = lines.len() as u16 + 2;
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(title.to_string())),
+        area,
+    );
+}
+
+fn right(text: String) -> Cell<'static> {
+    Cell::from(Line::from(text).alignment(Alignment::Right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+    use shishutsukan::{ExpenseWithId, GenreWithId};
+
+    #[test]
+    fn test_draw() {
+        let mut app = App::default();
+        app.set_data(
+            vec![ExpenseWithId {
+                id: 1,
+                date: "2025-01-10".to_string(),
+                genre: "食費".to_string(),
+                amount: 1200,
+            }],
+            vec![GenreWithId {
+                id: 1,
+                name: "食費".to_string(),
+                created_at: "2025-01-01 00:00:00".to_string(),
+            }],
+        );
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &mut app, "http://localhost:8000"))
+            .unwrap();
+        // 全角文字の後ろのセルは空白になるため、空白を除いて比べる
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .filter(|symbol| *symbol != " ")
+            .collect();
+        assert!(screen.contains("2025-01-10食費"));
+        assert!(screen.contains("¥1,200"));
+        assert!(screen.contains("削除の確認"));
+    }
+}
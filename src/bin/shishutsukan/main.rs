@@ -0,0 +1,358 @@
+//! `shishutsukan` コマンド
+//!
+//! 支出データとジャンルの追加・一覧・削除を行うコマンドラインツールです。
+//! `cli` フィーチャーを有効にするとビルドされます。
+//!
+//! ```text
+//! $ shishutsukan expense add 食費 1200 --date 2025-01-15
+//! $ shishutsukan expense list --from 2025-01-01 --format json
+//! $ shishutsukan genre list --profile staging
+//! $ shishutsukan rules test rules.json "SUICA チャージ" --amount 3000
+//! ```
+//!
+//! 接続先は [`shishutsukan::config`] の設定ファイルのプロファイルと環境変数から決まり、
+//! `--url` を指定した場合はそれが優先されます。
+
+mod output;
+
+use chrono::{Local, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use output::OutputFormat;
+use shishutsukan::config::Config;
+use shishutsukan::quick_entry::QuickEntryParser;
+use shishutsukan::rules::{Item, RuleSet};
+use shishutsukan::{Expense, ExpenseWithId, Genre, Result, ShishutsukanError};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "shishutsukan",
+    version,
+    about = "支出管理APIのコマンドラインクライアント"
+)]
+struct Cli {
+    /// 設定ファイルのプロファイル名
+    #[arg(short, long, global = true)]
+    profile: Option<String>,
+
+    /// サーバーのURL（プロファイルの base_url より優先）
+    #[arg(long, global = true)]
+    url: Option<String>,
+
+    /// 出力形式
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 支出データの操作
+    #[command(subcommand)]
+    Expense(ExpenseCommand),
+    /// ジャンルの操作
+    #[command(subcommand)]
+    Genre(GenreCommand),
+    /// 自動割り当ての規則の操作
+    #[command(subcommand)]
+    Rules(RulesCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum ExpenseCommand {
+    /// 支出データを追加
+    #[command(allow_missing_positional = true)]
+    Add {
+        /// ジャンル（省略時はプロファイルの default_genre）
+        genre: Option<String>,
+        /// 金額
+        amount: i32,
+        /// 日付（YYYY-MM-DD、省略時は今日）
+        #[arg(short, long)]
+        date: Option<NaiveDate>,
+    },
+    /// 支出データの一覧を表示
+    List(ListArgs),
+    /// 「昨日 ランチ 850円」のような文から支出データを追加
+    Quick {
+        /// 日付・ジャンル・金額を含む文
+        #[arg(required = true)]
+        text: Vec<String>,
+        /// 追加せずに解釈の結果だけを表示
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 支出データを削除
+    Delete {
+        /// 削除する支出データのID
+        #[arg(required = true)]
+        ids: Vec<i32>,
+    },
+}
+
+#[derive(Debug, Args)]
+struct ListArgs {
+    /// この日以降の支出データのみ表示
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /// この日以前の支出データのみ表示
+    #[arg(long)]
+    to: Option<NaiveDate>,
+    /// ジャンルで絞り込む
+    #[arg(short, long)]
+    genre: Option<String>,
+    /// 新しいものから指定した件数のみ表示
+    #[arg(short, long)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Subcommand)]
+enum GenreCommand {
+    /// ジャンルを追加
+    Add {
+        /// ジャンル名
+        name: String,
+    },
+    /// ジャンルの一覧を表示
+    List,
+    /// ジャンルを削除
+    Delete {
+        /// 削除するジャンルのID
+        #[arg(required = true)]
+        ids: Vec<i32>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RulesCommand {
+    /// 明細がどの規則に一致するかを表示
+    Test {
+        /// 規則ファイル（JSON）
+        rules: PathBuf,
+        /// 明細の説明文
+        description: String,
+        /// 支払先
+        #[arg(short, long)]
+        merchant: Option<String>,
+        /// 金額
+        #[arg(short, long, default_value_t = 0)]
+        amount: i64,
+        /// 日付（YYYY-MM-DD、省略時は今日）
+        #[arg(short, long)]
+        date: Option<NaiveDate>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("エラー: {}", e);
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let mut profile = Config::load()?.profile(cli.profile.as_deref())?;
+    if let Some(url) = cli.url {
+        profile.base_url = Some(url);
+    }
+    let client = profile.client()?;
+    let format = cli.format;
+
+    match cli.command {
+        Command::Expense(ExpenseCommand::Add {
+            genre,
+            amount,
+            date,
+        }) => {
+            let genre = genre.or(profile.default_genre).ok_or_else(|| {
+                ShishutsukanError::ConfigError(
+                    "ジャンルを指定するか、プロファイルに default_genre を設定してください"
+                        .to_string(),
+                )
+            })?;
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let expense = Expense::new(date.to_string(), genre, amount);
+            output::message(format, &client.add_expense(&expense).await?)
+        }
+        Command::Expense(ExpenseCommand::Quick { text, dry_run }) => {
+            let mut parser = QuickEntryParser::new(&client.get_genres().await?);
+            if let Some(genre) = profile.default_genre {
+                parser = parser.default_genre(genre);
+            }
+            let entry = parser.parse(&text.join(" "), Local::now().date_naive())?;
+            let Some(expense) = entry.to_expense() else {
+                let candidates: Vec<&str> =
+                    entry.candidates.iter().map(|c| c.genre.as_str()).collect();
+                return Err(ShishutsukanError::ParseError(if candidates.is_empty() {
+                    "ジャンルが分かりません（ジャンル名を含めてください）".to_string()
+                } else {
+                    format!(
+                        "ジャンルを決められません（候補: {}）",
+                        candidates.join("、")
+                    )
+                }));
+            };
+            if dry_run {
+                output::expense(format, &expense)
+            } else {
+                output::message(format, &client.add_expense(&expense).await?)
+            }
+        }
+        Command::Expense(ExpenseCommand::List(args)) => {
+            let expenses = filter_expenses(client.get_expenses().await?, &args);
+            output::expenses(format, &expenses)
+        }
+        Command::Expense(ExpenseCommand::Delete { ids }) => {
+            for id in ids {
+                output::message(format, &client.delete_expense(id).await?)?;
+            }
+            Ok(())
+        }
+        Command::Genre(GenreCommand::Add { name }) => {
+            output::message(format, &client.add_genre(&Genre::new(name)).await?)
+        }
+        Command::Genre(GenreCommand::List) => output::genres(format, &client.get_genres().await?),
+        Command::Genre(GenreCommand::Delete { ids }) => {
+            for id in ids {
+                output::message(format, &client.delete_genre(id).await?)?;
+            }
+            Ok(())
+        }
+        Command::Rules(RulesCommand::Test {
+            rules,
+            description,
+            merchant,
+            amount,
+            date,
+        }) => {
+            let rules = RuleSet::open(rules)?;
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let mut item = Item::new(date, amount, description);
+            if let Some(merchant) = merchant {
+                item = item.merchant(merchant);
+            }
+            output::explanation(format, &rules.explain(&item, &client.get_genres().await?))
+        }
+    }
+}
+
+/// 一覧の条件で絞り込み、日付順に並べる
+fn filter_expenses(mut expenses: Vec<ExpenseWithId>, args: &ListArgs) -> Vec<ExpenseWithId> {
+    expenses.retain(|expense| {
+        let date = expense.naive_date();
+        args.genre
+            .as_ref()
+            .is_none_or(|genre| expense.genre == *genre)
+            && args
+                .from
+                .is_none_or(|from| date.is_some_and(|date| date >= from))
+            && args.to.is_none_or(|to| date.is_some_and(|date| date <= to))
+    });
+    expenses.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+    if let Some(limit) = args.limit {
+        expenses.drain(..expenses.len().saturating_sub(limit));
+    }
+    expenses
+}
+
+/// エラーの種類に対応する終了コード（`sysexits.h` に準拠）
+///
+/// | 終了コード | エラー |
+/// |-----------|--------|
+/// | 65 | 入力データの誤り（`ParseError`、`ServerError`、`ServerNotEmpty`） |
+/// | 69 | サーバーに接続できない（`NetworkError`） |
+/// | 70 | その他の内部エラー |
+/// | 74 | 入出力エラー（`IoError`） |
+/// | 75 | サーバーの一時的なエラー（5xx の `HttpError`） |
+/// | 76 | 想定外の応答（その他の `HttpError`、`DecodingError`） |
+/// | 78 | 設定の誤り（`InvalidUrl`、`ConfigError`） |
+fn exit_code(error: &ShishutsukanError) -> u8 {
+    match error {
+        ShishutsukanError::ParseError(_)
+        | ShishutsukanError::ServerError(_)
+        | ShishutsukanError::ServerNotEmpty(_) => 65,
+        ShishutsukanError::NetworkError(_) => 69,
+        ShishutsukanError::IoError(_) => 74,
+        ShishutsukanError::HttpError(status) if *status >= 500 => 75,
+        ShishutsukanError::HttpError(_) | ShishutsukanError::DecodingError(_) => 76,
+        ShishutsukanError::InvalidUrl | ShishutsukanError::ConfigError(_) => 78,
+        #[allow(unreachable_patterns)]
+        _ => 70,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(&ShishutsukanError::InvalidUrl), 78);
+        assert_eq!(
+            exit_code(&ShishutsukanError::ConfigError("no profile".to_string())),
+            78
+        );
+        assert_eq!(exit_code(&ShishutsukanError::HttpError(503)), 75);
+        assert_eq!(exit_code(&ShishutsukanError::HttpError(404)), 76);
+        assert_eq!(
+            exit_code(&ShishutsukanError::ServerError("in use".to_string())),
+            65
+        );
+    }
+
+    #[test]
+    fn test_add_without_genre() {
+        let add = |args: &[&str]| {
+            let cli = Cli::parse_from(["shishutsukan", "expense", "add"].iter().chain(args));
+            match cli.command {
+                Command::Expense(ExpenseCommand::Add { genre, amount, .. }) => (genre, amount),
+                _ => panic!("unexpected command"),
+            }
+        };
+        assert_eq!(add(&["1200"]), (None, 1200));
+        assert_eq!(add(&["食費", "1200"]), (Some("食費".to_string()), 1200));
+    }
+
+    #[test]
+    fn test_filter_expenses() {
+        let expense = |id, date: &str, genre: &str| ExpenseWithId {
+            id,
+            date: date.to_string(),
+            genre: genre.to_string(),
+            amount: 100,
+        };
+        let expenses = vec![
+            expense(3, "2025-01-20", "食費"),
+            expense(1, "2025-01-10", "食費"),
+            expense(2, "2025-01-15", "交通費"),
+            expense(4, "2025-02-01", "食費"),
+        ];
+        let cli = Cli::parse_from([
+            "shishutsukan",
+            "expense",
+            "list",
+            "--genre",
+            "食費",
+            "--to",
+            "2025-01-31",
+            "--limit",
+            "1",
+        ]);
+        let Command::Expense(ExpenseCommand::List(args)) = cli.command else {
+            panic!("unexpected command");
+        };
+        let ids: Vec<i32> = filter_expenses(expenses, &args)
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec![3]);
+    }
+}
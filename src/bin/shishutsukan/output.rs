@@ -0,0 +1,129 @@
+//! 結果の出力
+
+use clap::ValueEnum;
+use shishutsukan::export::csv::{
+    write_expenses, write_genres, CsvOptions, ExpenseColumn, GenreColumn,
+};
+use shishutsukan::report::{format_yen, TextTable};
+use shishutsukan::rules::Explanation;
+use shishutsukan::{ApiMessage, Expense, ExpenseWithId, GenreWithId, Result};
+use std::io::{self, Write};
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 表
+    Table,
+    /// JSON
+    Json,
+    /// CSV
+    Csv,
+}
+
+/// 支出データの一覧を出力
+pub fn expenses(format: OutputFormat, expenses: &[ExpenseWithId]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Table => {
+            let headers = ["ID", "日付", "ジャンル", "金額"];
+            let mut table = TextTable::new(headers.iter().map(|h| h.to_string()).collect());
+            for expense in expenses {
+                table.push(vec![
+                    expense.id.to_string(),
+                    expense.date.clone(),
+                    expense.genre.clone(),
+                    format_yen(i64::from(expense.amount)),
+                ]);
+            }
+            let total: i64 = expenses.iter().map(|e| i64::from(e.amount)).sum();
+            table.push(vec![
+                "合計".to_string(),
+                String::new(),
+                format!("{}件", expenses.len()),
+                format_yen(total),
+            ]);
+            write!(stdout, "{}", table.render())?;
+        }
+        OutputFormat::Json => json(&mut stdout, expenses)?,
+        OutputFormat::Csv => write_expenses(
+            stdout,
+            expenses,
+            &ExpenseColumn::ALL,
+            &CsvOptions::default(),
+        )?,
+    }
+    Ok(())
+}
+
+/// 追加する前の支出データを出力
+pub fn expense(format: OutputFormat, expense: &Expense) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Json => json(&mut stdout, expense)?,
+        OutputFormat::Table | OutputFormat::Csv => writeln!(
+            stdout,
+            "{} {} {}",
+            expense.date,
+            expense.genre,
+            format_yen(i64::from(expense.amount))
+        )?,
+    }
+    Ok(())
+}
+
+/// ジャンルの一覧を出力
+pub fn genres(format: OutputFormat, genres: &[GenreWithId]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Table => {
+            let headers = ["ID", "ジャンル", "作成日時"];
+            let mut table = TextTable::new(headers.iter().map(|h| h.to_string()).collect());
+            for genre in genres {
+                table.push(vec![
+                    genre.id.to_string(),
+                    genre.name.clone(),
+                    genre.created_at.clone(),
+                ]);
+            }
+            write!(stdout, "{}", table.render())?;
+        }
+        OutputFormat::Json => json(&mut stdout, genres)?,
+        OutputFormat::Csv => {
+            write_genres(stdout, genres, &GenreColumn::ALL, &CsvOptions::default())?
+        }
+    }
+    Ok(())
+}
+
+/// 追加・削除の結果を出力
+pub fn message(format: OutputFormat, message: &ApiMessage) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Json => json(&mut stdout, message)?,
+        OutputFormat::Table | OutputFormat::Csv => {
+            let text = message
+                .message
+                .as_deref()
+                .or(message.error.as_deref())
+                .unwrap_or("ok");
+            writeln!(stdout, "{}", text)?;
+        }
+    }
+    Ok(())
+}
+
+/// 規則の照合の説明を出力
+pub fn explanation(format: OutputFormat, explanation: &Explanation) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Json => json(&mut stdout, explanation)?,
+        OutputFormat::Table | OutputFormat::Csv => write!(stdout, "{}", explanation)?,
+    }
+    Ok(())
+}
+
+fn json<T: serde::Serialize + ?Sized>(writer: &mut impl Write, value: &T) -> Result<()> {
+    serde_json::to_writer_pretty(&mut *writer, value)?;
+    writeln!(writer)?;
+    Ok(())
+}
@@ -0,0 +1,458 @@
+//! ジャンルごとの予算
+//!
+//! ジャンルごとに月間または年間の予算を設定し、支出データと照らし合わせて使用額・残額・
+//! 使用率を求めます。予算を超えた場合と、これまでのペースで支出が続くと期間の終わりまでに
+//! 予算を超える見込みの場合を検出します。
+//!
+//! ```
+//! use shishutsukan::budget::{evaluate, Budget};
+//! use shishutsukan::ExpenseWithId;
+//! use chrono::NaiveDate;
+//!
+//! let budgets = vec![Budget::monthly("食費", 30000)];
+//! let expenses = vec![ExpenseWithId {
+//!     id: 1,
+//!     date: "2025-01-10".to_string(),
+//!     genre: "食費".to_string(),
+//!     amount: 15000,
+//! }];
+//!
+//! let as_of = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
+//! let report = evaluate(&budgets, &expenses, &[], as_of);
+//! let status = &report.statuses[0];
+//! assert_eq!(status.remaining, 15000);
+//! assert!(!status.overspent);
+//! // 10日で半分を使ったペースでは月末までに予算を超える
+//! assert!(status.projected_overspend);
+//! ```
+
+use crate::error::Result;
+use crate::models::{ExpenseWithId, GenreWithId};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 予算の対象
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetTarget {
+    /// ジャンル名で指定
+    Genre(String),
+    /// ジャンルIDで指定（評価時にジャンルの一覧から名前を解決）
+    GenreId(i32),
+}
+
+impl From<&str> for BudgetTarget {
+    fn from(genre: &str) -> Self {
+        Self::Genre(genre.to_string())
+    }
+}
+
+impl From<String> for BudgetTarget {
+    fn from(genre: String) -> Self {
+        Self::Genre(genre)
+    }
+}
+
+impl From<i32> for BudgetTarget {
+    fn from(id: i32) -> Self {
+        Self::GenreId(id)
+    }
+}
+
+/// 予算の期間
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    /// 月ごと
+    Monthly,
+    /// 年ごと（1月1日から12月31日まで）
+    Annual,
+}
+
+impl BudgetPeriod {
+    /// 日付を含む期間の初日と最終日
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::budget::BudgetPeriod;
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
+    /// let (start, end) = BudgetPeriod::Monthly.bounds(date);
+    /// assert_eq!(start, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+    /// assert_eq!(end, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    /// ```
+    pub fn bounds(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::Monthly => {
+                let start = date.with_day(1).unwrap_or(date);
+                (start, next_start(self, start).pred_opt().unwrap_or(start))
+            }
+            Self::Annual => {
+                let start = NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date);
+                let end = NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date);
+                (start, end)
+            }
+        }
+    }
+}
+
+/// 予算
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Budget {
+    /// 対象のジャンル
+    pub target: BudgetTarget,
+    /// 期間
+    pub period: BudgetPeriod,
+    /// 1期間あたりの予算額
+    pub limit: i64,
+    /// 前の期間の残額（超過額）を次の期間に繰り越すかどうか
+    #[serde(default)]
+    pub rollover: bool,
+    /// 繰り越しを始める日（省略時はこのジャンルの最初の支出データの日付）
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub starts_on: Option<NaiveDate>,
+}
+
+impl Budget {
+    /// 月間の予算を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - ジャンル名またはジャンルID
+    /// * `limit` - 1か月あたりの予算額
+    pub fn monthly(target: impl Into<BudgetTarget>, limit: i64) -> Self {
+        Self::new(target.into(), BudgetPeriod::Monthly, limit)
+    }
+
+    /// 年間の予算を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - ジャンル名またはジャンルID
+    /// * `limit` - 1年あたりの予算額
+    pub fn annual(target: impl Into<BudgetTarget>, limit: i64) -> Self {
+        Self::new(target.into(), BudgetPeriod::Annual, limit)
+    }
+
+    fn new(target: BudgetTarget, period: BudgetPeriod, limit: i64) -> Self {
+        Self {
+            target,
+            period,
+            limit,
+            rollover: false,
+            starts_on: None,
+        }
+    }
+
+    /// 残額（超過額）を次の期間に繰り越す
+    pub fn with_rollover(mut self) -> Self {
+        self.rollover = true;
+        self
+    }
+
+    /// 繰り越しを始める日を指定
+    pub fn starts_on(mut self, date: NaiveDate) -> Self {
+        self.starts_on = Some(date);
+        self
+    }
+}
+
+// MARK: - Store
+
+/// 予算の保存先
+///
+/// ファイルを指定した場合は更新のたびにJSONとして保存されます。
+#[derive(Debug, Clone, Default)]
+pub struct BudgetStore {
+    path: Option<PathBuf>,
+    budgets: Vec<Budget>,
+}
+
+impl BudgetStore {
+    /// メモリ上だけに保持する保存先を作成
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// ファイルに保存される予算を開く
+    ///
+    /// ファイルが存在しない場合は空の状態で開き、最初の更新時にファイルを作成します。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 予算ファイルのパス
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let budgets = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            budgets,
+        })
+    }
+
+    /// 予算の一覧
+    pub fn budgets(&self) -> &[Budget] {
+        &self.budgets
+    }
+
+    /// 予算を設定する（同じ対象・期間の予算があれば置き換える）
+    pub fn set(&mut self, budget: Budget) -> Result<()> {
+        match self
+            .budgets
+            .iter_mut()
+            .find(|b| b.target == budget.target && b.period == budget.period)
+        {
+            Some(existing) => *existing = budget,
+            None => self.budgets.push(budget),
+        }
+        self.save()
+    }
+
+    /// 予算を削除する
+    ///
+    /// # Returns
+    ///
+    /// 削除した場合は `true`
+    pub fn remove(&mut self, target: &BudgetTarget, period: BudgetPeriod) -> Result<bool> {
+        let before = self.budgets.len();
+        self.budgets
+            .retain(|b| !(b.target == *target && b.period == period));
+        let removed = self.budgets.len() != before;
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// 支出データと照らし合わせて評価する（[`evaluate`] を参照）
+    pub fn evaluate(
+        &self,
+        expenses: &[ExpenseWithId],
+        genres: &[GenreWithId],
+        as_of: NaiveDate,
+    ) -> BudgetReport {
+        evaluate(&self.budgets, expenses, genres, as_of)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&self.budgets)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+// MARK: - Evaluation
+
+/// 予算の状況
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetStatus {
+    /// ジャンル名
+    pub genre: String,
+    /// 期間
+    pub period: BudgetPeriod,
+    /// 評価した期間の初日
+    pub start: NaiveDate,
+    /// 評価した期間の最終日
+    pub end: NaiveDate,
+    /// 1期間あたりの予算額
+    pub limit: i64,
+    /// 前の期間からの繰越額（超過していた場合は負）
+    pub carried_over: i64,
+    /// 使用できる額（予算額 + 繰越額）
+    pub available: i64,
+    /// 使用額
+    pub spent: i64,
+    /// 残額（超過している場合は負）
+    pub remaining: i64,
+    /// 使用率（%）。使用できる額が0以下の場合は `None`
+    pub percentage: Option<f64>,
+    /// これまでのペースで支出が続いた場合の期間の終わりの使用額
+    pub projected: i64,
+    /// 予算を超えているかどうか
+    pub overspent: bool,
+    /// まだ超えていないが、期間の終わりまでに超える見込みかどうか
+    pub projected_overspend: bool,
+}
+
+/// 予算の評価結果
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BudgetReport {
+    /// 予算ごとの状況
+    pub statuses: Vec<BudgetStatus>,
+    /// ジャンルIDを解決できなかった予算
+    pub unresolved: Vec<Budget>,
+}
+
+impl BudgetReport {
+    /// 予算を超えている状況
+    pub fn overspent(&self) -> impl Iterator<Item = &BudgetStatus> {
+        self.statuses.iter().filter(|status| status.overspent)
+    }
+
+    /// 期間の終わりまでに予算を超える見込みの状況
+    pub fn at_risk(&self) -> impl Iterator<Item = &BudgetStatus> {
+        self.statuses
+            .iter()
+            .filter(|status| status.projected_overspend)
+    }
+}
+
+/// 予算を支出データと照らし合わせて評価する
+///
+/// `as_of` を含む期間の使用額を集計します。見込みの使用額は、期間の初日から `as_of`
+/// までの1日あたりの使用額が期間の終わりまで続くものとして求めます。繰り越しが有効な
+/// 予算では、繰り越しを始める日を含む期間から前の期間までの残額（超過額）の合計を
+/// 使用できる額に加えます。日付を解釈できない支出データは無視します。
+///
+/// # Arguments
+///
+/// * `budgets` - 予算の一覧
+/// * `expenses` - 支出データの一覧
+/// * `genres` - ジャンルの一覧（ジャンルIDで指定した予算の解決に使用）
+/// * `as_of` - 評価する日
+pub fn evaluate(
+    budgets: &[Budget],
+    expenses: &[ExpenseWithId],
+    genres: &[GenreWithId],
+    as_of: NaiveDate,
+) -> BudgetReport {
+    let mut report = BudgetReport::default();
+    for budget in budgets {
+        let genre = match &budget.target {
+            BudgetTarget::Genre(name) => name.as_str(),
+            BudgetTarget::GenreId(id) => match genres.iter().find(|genre| genre.id == *id) {
+                Some(genre) => genre.name.as_str(),
+                None => {
+                    report.unresolved.push(budget.clone());
+                    continue;
+                }
+            },
+        };
+        report
+            .statuses
+            .push(evaluate_one(budget, genre, expenses, as_of));
+    }
+    report
+}
+
+fn evaluate_one(
+    budget: &Budget,
+    genre: &str,
+    expenses: &[ExpenseWithId],
+    as_of: NaiveDate,
+) -> BudgetStatus {
+    let dated: Vec<(NaiveDate, i64)> = expenses
+        .iter()
+        .filter(|expense| expense.genre == genre)
+        .filter_map(|expense| Some((expense.naive_date()?, i64::from(expense.amount))))
+        .collect();
+    let spent_between = |start: NaiveDate, end: NaiveDate| -> i64 {
+        dated
+            .iter()
+            .filter(|(date, _)| (start..=end).contains(date))
+            .map(|(_, amount)| amount)
+            .sum()
+    };
+
+    let (start, end) = budget.period.bounds(as_of);
+    let mut carried_over = 0;
+    if budget.rollover {
+        let first = budget
+            .starts_on
+            .or_else(|| dated.iter().map(|(date, _)| *date).min());
+        if let Some(first) = first {
+            let mut period_start = budget.period.bounds(first).0;
+            while period_start < start {
+                let period_end = budget.period.bounds(period_start).1;
+                carried_over += budget.limit - spent_between(period_start, period_end);
+                period_start = next_start(budget.period, period_start);
+            }
+        }
+    }
+
+    let available = budget.limit + carried_over;
+    let spent = spent_between(start, end);
+    let elapsed = (as_of.min(end) - start).num_days() + 1;
+    let length = (end - start).num_days() + 1;
+    let projected = if elapsed > 0 {
+        (spent as f64 * length as f64 / elapsed as f64).round() as i64
+    } else {
+        spent
+    };
+    let overspent = spent > available;
+
+    BudgetStatus {
+        genre: genre.to_string(),
+        period: budget.period,
+        start,
+        end,
+        limit: budget.limit,
+        carried_over,
+        available,
+        spent,
+        remaining: available - spent,
+        percentage: (available > 0).then(|| spent as f64 / available as f64 * 100.0),
+        projected,
+        overspent,
+        projected_overspend: !overspent && projected > available,
+    }
+}
+
+/// 次の期間の初日
+fn next_start(period: BudgetPeriod, start: NaiveDate) -> NaiveDate {
+    let (year, month) = match period {
+        BudgetPeriod::Monthly if start.month() == 12 => (start.year() + 1, 1),
+        BudgetPeriod::Monthly => (start.year(), start.month() + 1),
+        BudgetPeriod::Annual => (start.year() + 1, 1),
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monthly_rollover_carries_remaining_and_overspend() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        let expense = |id, date: &str, amount| ExpenseWithId {
+            id,
+            date: date.to_string(),
+            genre: "食費".to_string(),
+            amount,
+        };
+        let expenses = vec![
+            expense(1, "2025-01-05", 7000),
+            expense(2, "2025-02-05", 12000),
+            expense(3, "2025-03-01", 1000),
+        ];
+        let budget = Budget::monthly("食費", 10000).with_rollover();
+
+        // 1月: +3000、2月: -2000 を繰り越す
+        let status = evaluate_one(&budget, "食費", &expenses, date(3, 1));
+        assert_eq!(status.carried_over, 1000);
+        assert_eq!(status.available, 11000);
+        assert_eq!(status.remaining, 10000);
+        assert_eq!(status.projected, 31000);
+        assert!(status.projected_overspend);
+
+        // 支出より前から始めた月も繰り越しに含める
+        let since = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+        let status = evaluate_one(&budget.starts_on(since), "食費", &expenses, date(1, 31));
+        assert_eq!(status.carried_over, 10000);
+    }
+}
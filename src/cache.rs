@@ -0,0 +1,199 @@
+use crate::models::{ExpenseWithId, GenreWithId};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// キャッシュの統計情報
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// キャッシュから応答した回数
+    pub hits: u64,
+    /// サーバーへ問い合わせた回数
+    pub misses: u64,
+    /// キャッシュを破棄した回数
+    pub invalidations: u64,
+}
+
+/// 一覧取得APIのレスポンスキャッシュ
+///
+/// クライアントのクローン間で共有されるため、内部状態は `Mutex` で保護する。
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    ttl: Duration,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    genres: Slot<Vec<GenreWithId>>,
+    expenses: Slot<Vec<ExpenseWithId>>,
+    stats: CacheStats,
+}
+
+/// 1種類のレスポンスを保持する領域
+///
+/// `generation` は破棄のたびに進み、取得中に破棄された古いレスポンスが
+/// 書き戻されるのを防ぐ。
+#[derive(Debug)]
+struct Slot<T> {
+    entry: Option<(Instant, T)>,
+    generation: u64,
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self {
+            entry: None,
+            generation: 0,
+        }
+    }
+}
+
+impl<T: Clone> Slot<T> {
+    fn get(&self, ttl: Duration) -> Option<T> {
+        match &self.entry {
+            Some((stored_at, value)) if stored_at.elapsed() < ttl => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn put(&mut self, generation: u64, value: T) {
+        if self.generation == generation {
+            self.entry = Some((Instant::now(), value));
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.entry = None;
+        self.generation += 1;
+    }
+}
+
+/// キャッシュミス時に返す書き込み用のトークン
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Generation(u64);
+
+impl ResponseCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub(crate) fn genres(&self) -> Result<Vec<GenreWithId>, Generation> {
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        let cached = state.genres.get(self.ttl);
+        Self::lookup(&mut state.stats, cached, state.genres.generation)
+    }
+
+    pub(crate) fn put_genres(&self, generation: Generation, genres: Vec<GenreWithId>) {
+        self.lock().genres.put(generation.0, genres);
+    }
+
+    pub(crate) fn expenses(&self) -> Result<Vec<ExpenseWithId>, Generation> {
+        let mut guard = self.lock();
+        let state = &mut *guard;
+        let cached = state.expenses.get(self.ttl);
+        Self::lookup(&mut state.stats, cached, state.expenses.generation)
+    }
+
+    pub(crate) fn put_expenses(&self, generation: Generation, expenses: Vec<ExpenseWithId>) {
+        self.lock().expenses.put(generation.0, expenses);
+    }
+
+    pub(crate) fn invalidate_genres(&self) {
+        let mut state = self.lock();
+        state.genres.invalidate();
+        state.stats.invalidations += 1;
+    }
+
+    pub(crate) fn invalidate_expenses(&self) {
+        let mut state = self.lock();
+        state.expenses.invalidate();
+        state.stats.invalidations += 1;
+    }
+
+    pub(crate) fn invalidate_all(&self) {
+        let mut state = self.lock();
+        state.genres.invalidate();
+        state.expenses.invalidate();
+        state.stats.invalidations += 1;
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    fn lookup<T>(
+        stats: &mut CacheStats,
+        cached: Option<T>,
+        generation: u64,
+    ) -> Result<T, Generation> {
+        match cached {
+            Some(value) => {
+                stats.hits += 1;
+                Ok(value)
+            }
+            None => {
+                stats.misses += 1;
+                Err(Generation(generation))
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // パニックで毒化しても、キャッシュの中身は常に整合しているので継続して使う
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genre(id: i32) -> GenreWithId {
+        GenreWithId {
+            id,
+            name: format!("genre{}", id),
+            created_at: "2025-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hit_after_put() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        let generation = cache.genres().unwrap_err();
+        cache.put_genres(generation, vec![genre(1)]);
+
+        assert_eq!(cache.genres().unwrap(), vec![genre(1)]);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                invalidations: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_expired_entry_is_miss() {
+        let cache = ResponseCache::new(Duration::ZERO);
+        let generation = cache.genres().unwrap_err();
+        cache.put_genres(generation, vec![genre(1)]);
+
+        assert!(cache.genres().is_err());
+    }
+
+    #[test]
+    fn test_stale_put_after_invalidation_is_dropped() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        let generation = cache.expenses().unwrap_err();
+        cache.invalidate_expenses();
+        cache.put_expenses(generation, Vec::new());
+
+        assert!(cache.expenses().is_err());
+        assert_eq!(cache.stats().invalidations, 1);
+    }
+}
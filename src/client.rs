@@ -1,12 +1,54 @@
+use crate::backup::{Backup, BackupFormat, ContentIndex, RestoreMode, RestoreSummary};
+use crate::cache::{CacheStats, ResponseCache};
+use crate::dedupe::{DedupePlan, DedupeReport};
 use crate::error::{Result, ShishutsukanError};
+use crate::idempotency::{ExpenseLedger, IdempotentAddOutcome};
 use crate::models::{ApiMessage, Expense, ExpenseWithId, Genre, GenreWithId};
-use reqwest::Client;
+use crate::rate_limit::RateLimiter;
+use reqwest::{Client, RequestBuilder, Response};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// APIの認証情報
+#[derive(Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// Bearerトークン
+    Bearer(String),
+    /// Basic認証
+    Basic {
+        /// ユーザー名
+        username: String,
+        /// パスワード
+        password: Option<String>,
+    },
+}
+
+impl fmt::Debug for Auth {
+    // トークンやパスワードをログに出さない
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(***)"),
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+        }
+    }
+}
 
 /// 支出管理APIクライアント
+///
+/// クローンしたクライアント同士はキャッシュやレートリミッターなどの内部状態を共有します。
 #[derive(Debug, Clone)]
 pub struct ShishutsukanClient {
     base_url: String,
     client: Client,
+    cache: Option<Arc<ResponseCache>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    auth: Option<Auth>,
 }
 
 impl ShishutsukanClient {
@@ -27,6 +69,9 @@ impl ShishutsukanClient {
         Self {
             base_url: base_url.into(),
             client: Client::new(),
+            cache: None,
+            rate_limiter: None,
+            auth: None,
         }
     }
 
@@ -40,9 +85,111 @@ impl ShishutsukanClient {
         Self {
             base_url: base_url.into(),
             client,
+            cache: None,
+            rate_limiter: None,
+            auth: None,
         }
     }
 
+    /// `get_genres` と `get_expenses` のレスポンスキャッシュを有効にする
+    ///
+    /// キャッシュは `ttl` の間有効で、同じクライアント（およびそのクローン）から
+    /// `add_*` / `delete_*` を呼び出すと自動的に破棄されます。
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - キャッシュの有効期間
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::ShishutsukanClient;
+    /// use std::time::Duration;
+    ///
+    /// let client = ShishutsukanClient::new("http://localhost:8000")
+    ///     .with_cache(Duration::from_secs(60));
+    /// ```
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(ttl)));
+        self
+    }
+
+    /// クライアント側のレート制限を有効にする
+    ///
+    /// トークンバケット方式で、平均 `requests_per_second` 件/秒まで、
+    /// 瞬間的には `burst` 件までのリクエストを許可します。上限を超えた
+    /// リクエストは送信可能になるまで待機します。制限はクローンした
+    /// クライアント間で共有され、一括操作を含むすべてのAPI呼び出しに適用されます。
+    ///
+    /// # Arguments
+    ///
+    /// * `requests_per_second` - 1秒あたりの平均リクエスト数
+    /// * `burst` - 連続して送信できる最大リクエスト数
+    ///
+    /// # Panics
+    ///
+    /// `requests_per_second` が正の有限値でない場合、または `burst` が0の場合
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::ShishutsukanClient;
+    ///
+    /// let client = ShishutsukanClient::new("http://localhost:8000")
+    ///     .with_rate_limit(5.0, 10);
+    /// ```
+    pub fn with_rate_limit(mut self, requests_per_second: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(requests_per_second, burst)));
+        self
+    }
+
+    /// すべてのリクエストに認証情報を付ける
+    ///
+    /// # Arguments
+    ///
+    /// * `auth` - 認証情報
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::{Auth, ShishutsukanClient};
+    ///
+    /// let client = ShishutsukanClient::new("http://localhost:8000")
+    ///     .with_auth(Auth::Bearer("secret".to_string()));
+    /// ```
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 設定ファイルのプロファイルからクライアントを作成
+    ///
+    /// [`Config::load`](crate::config::Config::load) で読み込んだ設定ファイルから
+    /// 指定したプロファイルを選び、環境変数による上書きを適用してクライアントを作成します。
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - プロファイル名
+    ///
+    /// # Errors
+    ///
+    /// 設定ファイルを読み込めない場合や、プロファイルが存在しない場合
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use shishutsukan::ShishutsukanClient;
+    ///
+    /// # fn main() -> shishutsukan::Result<()> {
+    /// let client = ShishutsukanClient::from_profile("home")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "config")]
+    pub fn from_profile(name: &str) -> Result<Self> {
+        crate::config::Config::load()?.profile(Some(name))?.client()
+    }
+
     // MARK: - Expense APIs
 
     /// 支出データを追加
@@ -65,7 +212,9 @@ impl ShishutsukanClient {
     pub async fn add_expense(&self, expense: &Expense) -> Result<ApiMessage> {
         let url = format!("{}/expenses", self.base_url);
 
-        let response = self.client.post(&url).json(expense).send().await?;
+        let response = self.send(self.client.post(&url).json(expense)).await;
+        self.invalidate_expenses();
+        let response = response?;
 
         self.validate_response(&response)?;
 
@@ -77,6 +226,39 @@ impl ShishutsukanClient {
         Ok(message)
     }
 
+    /// 複数の支出データを順に追加
+    ///
+    /// 途中で失敗しても残りの支出データの追加を続け、入力と同じ順序で
+    /// 各支出データの結果を返します。
+    ///
+    /// # Arguments
+    ///
+    /// * `expenses` - 追加する支出データ
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use shishutsukan::{ShishutsukanClient, Expense};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ShishutsukanClient::new("http://localhost:8000").with_rate_limit(2.0, 5);
+    /// let expenses = vec![
+    ///     Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000),
+    ///     Expense::new("2025-01-16".to_string(), "交通費".to_string(), 500),
+    /// ];
+    /// for result in client.add_expenses(&expenses).await {
+    ///     println!("{:?}", result.map(|message| message.message));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_expenses(&self, expenses: &[Expense]) -> Vec<Result<ApiMessage>> {
+        let mut results = Vec::with_capacity(expenses.len());
+        for expense in expenses {
+            results.push(self.add_expense(expense).await);
+        }
+        results
+    }
+
     /// 支出データの一覧を取得
     ///
     /// # Examples
@@ -93,13 +275,22 @@ impl ShishutsukanClient {
     /// # }
     /// ```
     pub async fn get_expenses(&self) -> Result<Vec<ExpenseWithId>> {
+        let generation = match self.cache.as_ref().map(|cache| cache.expenses()) {
+            Some(Ok(expenses)) => return Ok(expenses),
+            Some(Err(generation)) => Some(generation),
+            None => None,
+        };
+
         let url = format!("{}/expenses", self.base_url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
 
         self.validate_response(&response)?;
 
         let expenses: Vec<ExpenseWithId> = response.json().await?;
+        if let (Some(cache), Some(generation)) = (self.cache.as_ref(), generation) {
+            cache.put_expenses(generation, expenses.clone());
+        }
         Ok(expenses)
     }
 
@@ -122,7 +313,9 @@ impl ShishutsukanClient {
     pub async fn delete_expense(&self, id: i32) -> Result<ApiMessage> {
         let url = format!("{}/expenses/{}", self.base_url, id);
 
-        let response = self.client.delete(&url).send().await?;
+        let response = self.send(self.client.delete(&url)).await;
+        self.invalidate_expenses();
+        let response = response?;
 
         self.validate_response(&response)?;
 
@@ -134,6 +327,150 @@ impl ShishutsukanClient {
         Ok(message)
     }
 
+    // MARK: - Idempotent Expense APIs
+
+    /// 重複を検出しながら支出データを追加
+    ///
+    /// サーバーには冪等キーの仕組みがないため、追加前に `get_expenses` の結果と
+    /// ローカル台帳を照合し、重複と判定した場合は追加せずに
+    /// [`IdempotentAddOutcome::Skipped`] を返します。
+    ///
+    /// * `key` を指定した場合は、台帳に同じキーの追加記録があれば重複とみなします。
+    ///   前回の追加がタイムアウトなどで未確認のままなら、サーバーに同じ内容の行が
+    ///   あるかどうかで判定します。
+    /// * `key` を省略した場合は、`window` 内に同じ内容（日付・ジャンル・金額）を
+    ///   追加した記録があり、かつサーバーに同じ内容の行があれば重複とみなします。
+    ///
+    /// # Arguments
+    ///
+    /// * `expense` - 追加する支出データ
+    /// * `key` - クライアントが生成した冪等キー
+    /// * `ledger` - 追加記録を保持する台帳
+    /// * `window` - キーなしの追加で重複とみなす期間
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use shishutsukan::{ShishutsukanClient, Expense, ExpenseLedger, IdempotentAddOutcome};
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ShishutsukanClient::new("http://localhost:8000");
+    /// let mut ledger = ExpenseLedger::open("ledger.json")?;
+    /// let expense = Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000);
+    ///
+    /// match client
+    ///     .add_expense_idempotent(&expense, Some("receipt-0001"), &mut ledger, Duration::from_secs(600))
+    ///     .await?
+    /// {
+    ///     IdempotentAddOutcome::Added(message) => println!("追加: {:?}", message.message),
+    ///     IdempotentAddOutcome::Skipped { reason, .. } => println!("重複: {:?}", reason),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_expense_idempotent(
+        &self,
+        expense: &Expense,
+        key: Option<&str>,
+        ledger: &mut ExpenseLedger,
+        window: Duration,
+    ) -> Result<IdempotentAddOutcome> {
+        let existing = self.get_expenses().await?;
+        let considered = ledger.entries().len();
+        self.add_expense_checked(expense, key, ledger, &existing, window, considered)
+            .await
+    }
+
+    /// 重複を検出しながら複数の支出データを順に追加
+    ///
+    /// `get_expenses` は最初に1回だけ呼び出します。判定方法は
+    /// [`add_expense_idempotent`](Self::add_expense_idempotent) と同じですが、
+    /// 同じ呼び出しの中で追加したキーなしの支出データ同士は重複とみなしません。
+    ///
+    /// # Arguments
+    ///
+    /// * `expenses` - 追加する支出データと冪等キーの組
+    /// * `ledger` - 追加記録を保持する台帳
+    /// * `window` - キーなしの追加で重複とみなす期間
+    pub async fn add_expenses_idempotent(
+        &self,
+        expenses: &[(Expense, Option<String>)],
+        ledger: &mut ExpenseLedger,
+        window: Duration,
+    ) -> Result<Vec<Result<IdempotentAddOutcome>>> {
+        let existing = self.get_expenses().await?;
+        let considered = ledger.entries().len();
+
+        let mut results = Vec::with_capacity(expenses.len());
+        for (expense, key) in expenses {
+            let result = self
+                .add_expense_checked(
+                    expense,
+                    key.as_deref(),
+                    ledger,
+                    &existing,
+                    window,
+                    considered,
+                )
+                .await;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    /// 重複削除の計画を実行
+    ///
+    /// 計画に含まれる支出データのうち、サーバー上に同じID・同じ内容で残っているものを
+    /// 削除します。`dry_run` が `true` の場合は削除せず、削除する予定の支出データを
+    /// レポートに含めて返します。
+    ///
+    /// # Arguments
+    ///
+    /// * `plan` - 重複削除の計画
+    /// * `dry_run` - 削除せずにレポートだけを作成するかどうか
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use shishutsukan::{find_duplicates, DedupePlan, DuplicateCriteria, ShishutsukanClient};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ShishutsukanClient::new("http://localhost:8000");
+    /// let expenses = client.get_expenses().await?;
+    /// let groups = find_duplicates(&expenses, &DuplicateCriteria::near(1, 0));
+    /// let plan = DedupePlan::from_groups(&groups, 0.9);
+    ///
+    /// let report = client.dedupe_expenses(&plan, true).await?;
+    /// for expense in &report.deleted {
+    ///     println!("削除予定: {} {} ¥{}", expense.date, expense.genre, expense.amount);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn dedupe_expenses(&self, plan: &DedupePlan, dry_run: bool) -> Result<DedupeReport> {
+        let existing = self.get_expenses().await?;
+        let mut report = DedupeReport {
+            dry_run,
+            ..DedupeReport::default()
+        };
+
+        for expense in &plan.deletions {
+            if !existing.contains(expense) {
+                report.missing.push(expense.clone());
+                continue;
+            }
+            if dry_run {
+                report.deleted.push(expense.clone());
+                continue;
+            }
+            match self.delete_expense(expense.id).await {
+                Ok(_) => report.deleted.push(expense.clone()),
+                Err(e) => report.failed.push((expense.clone(), e.to_string())),
+            }
+        }
+
+        Ok(report)
+    }
+
     // MARK: - Genre APIs
 
     /// ジャンルの一覧を取得
@@ -152,13 +489,22 @@ impl ShishutsukanClient {
     /// # }
     /// ```
     pub async fn get_genres(&self) -> Result<Vec<GenreWithId>> {
+        let generation = match self.cache.as_ref().map(|cache| cache.genres()) {
+            Some(Ok(genres)) => return Ok(genres),
+            Some(Err(generation)) => Some(generation),
+            None => None,
+        };
+
         let url = format!("{}/genres", self.base_url);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
 
         self.validate_response(&response)?;
 
         let genres: Vec<GenreWithId> = response.json().await?;
+        if let (Some(cache), Some(generation)) = (self.cache.as_ref(), generation) {
+            cache.put_genres(generation, genres.clone());
+        }
         Ok(genres)
     }
 
@@ -182,7 +528,9 @@ impl ShishutsukanClient {
     pub async fn add_genre(&self, genre: &Genre) -> Result<ApiMessage> {
         let url = format!("{}/genres", self.base_url);
 
-        let response = self.client.post(&url).json(genre).send().await?;
+        let response = self.send(self.client.post(&url).json(genre)).await;
+        self.invalidate_genres();
+        let response = response?;
 
         self.validate_response(&response)?;
 
@@ -213,7 +561,9 @@ impl ShishutsukanClient {
     pub async fn delete_genre(&self, id: i32) -> Result<ApiMessage> {
         let url = format!("{}/genres/{}", self.base_url, id);
 
-        let response = self.client.delete(&url).send().await?;
+        let response = self.send(self.client.delete(&url)).await;
+        self.invalidate_genres();
+        let response = response?;
 
         self.validate_response(&response)?;
 
@@ -225,8 +575,231 @@ impl ShishutsukanClient {
         Ok(message)
     }
 
+    // MARK: - Backup APIs
+
+    /// ジャンルと支出データをすべて書き出す
+    ///
+    /// IDとジャンルの作成日時を含むバージョン付きのJSONまたはJSON Linesとして書き出し、
+    /// 書き出したバックアップを返します。
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - 書き込み先
+    /// * `format` - 書き出し形式
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use shishutsukan::{BackupFormat, ShishutsukanClient};
+    /// # use std::fs::File;
+    /// # use std::io::BufWriter;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ShishutsukanClient::new("http://localhost:8000");
+    /// let file = BufWriter::new(File::create("backup.jsonl")?);
+    /// let backup = client.backup(file, BackupFormat::Ndjson).await?;
+    /// println!("支出データ {} 件を保存しました", backup.expenses.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn backup<W: Write>(&self, writer: W, format: BackupFormat) -> Result<Backup> {
+        let genres = self.get_genres().await?;
+        let expenses = self.get_expenses().await?;
+        let backup = Backup::new(genres, expenses);
+        backup.write_to(writer, format)?;
+        Ok(backup)
+    }
+
+    /// バックアップから復元
+    ///
+    /// ジャンルを先に作成してから支出データを追加します。同じ名前のジャンルが
+    /// サーバーにある場合はそれを使い、[`RestoreMode::Merge`] では日付・ジャンル・金額が
+    /// 同じ支出データも追加しません。IDはサーバーが振り直すため、バックアップ上のIDとの
+    /// 対応を結果に含めます。ジャンルの作成日時は復元されません。
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - 読み込み元（JSONまたはJSON Lines）
+    /// * `mode` - 復元の方法
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use shishutsukan::{RestoreMode, ShishutsukanClient};
+    /// # use std::fs::File;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ShishutsukanClient::new("http://localhost:8000");
+    /// let summary = client.restore(File::open("backup.jsonl")?, RestoreMode::Merge).await?;
+    /// println!(
+    ///     "追加 {} 件, 既存 {} 件, 失敗 {} 件",
+    ///     summary.expenses_restored,
+    ///     summary.expenses_existing,
+    ///     summary.failed_expenses.len()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restore<R: Read>(&self, reader: R, mode: RestoreMode) -> Result<RestoreSummary> {
+        let backup = Backup::read_from(reader)?;
+        let existing_expenses = self.get_expenses().await?;
+        if mode == RestoreMode::Empty && !existing_expenses.is_empty() {
+            return Err(ShishutsukanError::ServerNotEmpty(existing_expenses.len()));
+        }
+        let mut summary = RestoreSummary::default();
+
+        let mut genre_ids: HashMap<String, i32> = self
+            .get_genres()
+            .await?
+            .into_iter()
+            .map(|genre| (genre.name, genre.id))
+            .collect();
+        let mut created_genres = Vec::new();
+        for genre in &backup.genres {
+            if let Some(&id) = genre_ids.get(&genre.name) {
+                summary.genre_ids.insert(genre.id, id);
+                summary.genres_existing += 1;
+                continue;
+            }
+            match self.add_genre(&Genre::new(genre.name.clone())).await {
+                Ok(_) => created_genres.push(genre),
+                Err(e) => summary.failed_genres.push((genre.clone(), e.to_string())),
+            }
+        }
+        if !created_genres.is_empty() {
+            genre_ids.extend(
+                self.get_genres()
+                    .await?
+                    .into_iter()
+                    .map(|genre| (genre.name, genre.id)),
+            );
+            for genre in created_genres {
+                if let Some(&id) = genre_ids.get(&genre.name) {
+                    summary.genre_ids.insert(genre.id, id);
+                }
+                summary.genres_restored += 1;
+            }
+        }
+
+        let mut existing = ContentIndex::new(&existing_expenses);
+        let mut added_expenses = Vec::new();
+        for expense in &backup.expenses {
+            let content = expense.to_expense();
+            if let Some(id) = existing.claim(&content) {
+                summary.expense_ids.insert(expense.id, id);
+                summary.expenses_existing += 1;
+                continue;
+            }
+            match self.add_expense(&content).await {
+                Ok(_) => added_expenses.push(expense),
+                Err(e) => summary
+                    .failed_expenses
+                    .push((expense.clone(), e.to_string())),
+            }
+        }
+        if !added_expenses.is_empty() {
+            // 追加APIはIDを返さないため、復元前になかった支出データと内容で対応付ける
+            let known: HashSet<i32> = existing_expenses.iter().map(|e| e.id).collect();
+            let current = self.get_expenses().await?;
+            let mut added = ContentIndex::new(current.iter().filter(|e| !known.contains(&e.id)));
+            for expense in added_expenses {
+                if let Some(id) = added.claim(&expense.to_expense()) {
+                    summary.expense_ids.insert(expense.id, id);
+                }
+                summary.expenses_restored += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    // MARK: - Cache APIs
+
+    /// キャッシュを破棄し、ジャンルと支出データを取得し直す
+    ///
+    /// キャッシュが無効な場合は何もしません。
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use shishutsukan::ShishutsukanClient;
+    /// # use std::time::Duration;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = ShishutsukanClient::new("http://localhost:8000")
+    ///     .with_cache(Duration::from_secs(60));
+    /// client.refresh().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn refresh(&self) -> Result<()> {
+        let Some(cache) = self.cache.as_ref() else {
+            return Ok(());
+        };
+
+        cache.invalidate_all();
+        self.get_genres().await?;
+        self.get_expenses().await?;
+        Ok(())
+    }
+
+    /// キャッシュの統計情報を取得
+    ///
+    /// キャッシュが無効な場合は `None` を返します。
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
     // MARK: - Helper Methods
 
+    async fn add_expense_checked(
+        &self,
+        expense: &Expense,
+        key: Option<&str>,
+        ledger: &mut ExpenseLedger,
+        existing: &[ExpenseWithId],
+        window: Duration,
+        considered: usize,
+    ) -> Result<IdempotentAddOutcome> {
+        if let Some((reason, existing)) =
+            ledger.find_duplicate(expense, key, existing, window, considered)
+        {
+            if let Some(key) = key {
+                ledger.commit_key(key)?;
+            }
+            return Ok(IdempotentAddOutcome::Skipped { reason, existing });
+        }
+
+        let index = ledger.record_pending(expense, key)?;
+        let message = self.add_expense(expense).await?;
+        ledger.commit(index)?;
+        Ok(IdempotentAddOutcome::Added(message))
+    }
+
+    /// レート制限に従ってリクエストを送信
+    async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+            rate_limiter.acquire().await;
+        }
+        let request = match self.auth.as_ref() {
+            Some(Auth::Bearer(token)) => request.bearer_auth(token),
+            Some(Auth::Basic { username, password }) => {
+                request.basic_auth(username, password.as_ref())
+            }
+            None => request,
+        };
+        request.send().await
+    }
+
+    fn invalidate_expenses(&self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.invalidate_expenses();
+        }
+    }
+
+    fn invalidate_genres(&self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.invalidate_genres();
+        }
+    }
+
     fn validate_response(&self, response: &reqwest::Response) -> Result<()> {
         let status = response.status();
         if !status.is_success() {
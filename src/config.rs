@@ -0,0 +1,358 @@
+//! 設定ファイルとプロファイル
+//!
+//! 接続先のサーバーごとの設定を、名前付きのプロファイルとしてTOMLファイルに保存します。
+//! 設定ファイルは `$XDG_CONFIG_HOME/shishutsukan/config.toml`（環境変数
+//! `SHISHUTSUKAN_CONFIG` で変更可）から読み込みます。
+//!
+//! ```toml
+//! # プロファイルを指定しない場合に使うプロファイル
+//! default_profile = "home"
+//!
+//! # トップレベルの項目はすべてのプロファイルの既定値になる
+//! timeout_secs = 10
+//!
+//! [profiles.home]
+//! base_url = "http://home-server:8000"
+//! token = "secret"
+//! default_genre = "食費"
+//!
+//! [profiles.staging]
+//! base_url = "https://staging.example.com"
+//! username = "family"
+//! password = "secret"
+//!
+//! [profiles.local]
+//! base_url = "http://localhost:8000"
+//! connect_timeout_secs = 1
+//! ```
+//!
+//! プロファイルの値は次の環境変数で上書きできます。
+//!
+//! | 環境変数 | 項目 |
+//! |----------|------|
+//! | `SHISHUTSUKAN_PROFILE` | 使うプロファイル（名前を指定しなかった場合） |
+//! | `SHISHUTSUKAN_URL` | `base_url` |
+//! | `SHISHUTSUKAN_TOKEN` | `token` |
+//! | `SHISHUTSUKAN_USERNAME` | `username` |
+//! | `SHISHUTSUKAN_PASSWORD` | `password` |
+//! | `SHISHUTSUKAN_TIMEOUT` | `timeout_secs` |
+//! | `SHISHUTSUKAN_CONNECT_TIMEOUT` | `connect_timeout_secs` |
+//! | `SHISHUTSUKAN_DEFAULT_GENRE` | `default_genre` |
+//!
+//! ```no_run
+//! use shishutsukan::config::Config;
+//!
+//! # fn main() -> shishutsukan::Result<()> {
+//! let profile = Config::load()?.profile(Some("staging"))?;
+//! let client = profile.client()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::{Auth, ShishutsukanClient};
+use crate::error::{Result, ShishutsukanError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// `base_url` を指定しなかった場合のサーバーのURL
+pub const DEFAULT_BASE_URL: &str = "http://localhost:8000";
+
+/// 接続先のサーバーの設定
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Profile {
+    /// サーバーのURL
+    pub base_url: Option<String>,
+    /// Bearerトークン
+    pub token: Option<String>,
+    /// Basic認証のユーザー名
+    pub username: Option<String>,
+    /// Basic認証のパスワード
+    pub password: Option<String>,
+    /// リクエスト全体のタイムアウト（秒）
+    pub timeout_secs: Option<u64>,
+    /// 接続のタイムアウト（秒）
+    pub connect_timeout_secs: Option<u64>,
+    /// 支出データを追加するときのジャンルの既定値
+    pub default_genre: Option<String>,
+}
+
+impl Profile {
+    /// サーバーのURL（指定がない場合は [`DEFAULT_BASE_URL`]）
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL)
+    }
+
+    /// 認証情報
+    ///
+    /// `token` がある場合はBearerトークン、`username` がある場合はBasic認証を使います。
+    pub fn auth(&self) -> Option<Auth> {
+        match (&self.token, &self.username) {
+            (Some(token), _) => Some(Auth::Bearer(token.clone())),
+            (None, Some(username)) => Some(Auth::Basic {
+                username: username.clone(),
+                password: self.password.clone(),
+            }),
+            (None, None) => None,
+        }
+    }
+
+    /// 環境変数による上書きを適用する
+    ///
+    /// # Errors
+    ///
+    /// タイムアウトの環境変数が秒数として解釈できない場合
+    pub fn with_env(self) -> Result<Self> {
+        self.with_vars(|name| std::env::var(name).ok())
+    }
+
+    /// プロファイルの設定でクライアントを作成
+    ///
+    /// # Errors
+    ///
+    /// HTTPクライアントを初期化できない場合
+    pub fn client(&self) -> Result<ShishutsukanClient> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        let client = ShishutsukanClient::with_client(self.base_url(), builder.build()?);
+        Ok(match self.auth() {
+            Some(auth) => client.with_auth(auth),
+            None => client,
+        })
+    }
+
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let secs = |name: &str| -> Result<Option<u64>> {
+            var(name)
+                .map(|value| {
+                    value.trim().parse().map_err(|_| {
+                        ShishutsukanError::ConfigError(format!(
+                            "{} は秒数で指定してください: {}",
+                            name, value
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        self.base_url = var("SHISHUTSUKAN_URL").or(self.base_url);
+        self.token = var("SHISHUTSUKAN_TOKEN").or(self.token);
+        self.username = var("SHISHUTSUKAN_USERNAME").or(self.username);
+        self.password = var("SHISHUTSUKAN_PASSWORD").or(self.password);
+        self.timeout_secs = secs("SHISHUTSUKAN_TIMEOUT")?.or(self.timeout_secs);
+        self.connect_timeout_secs =
+            secs("SHISHUTSUKAN_CONNECT_TIMEOUT")?.or(self.connect_timeout_secs);
+        self.default_genre = var("SHISHUTSUKAN_DEFAULT_GENRE").or(self.default_genre);
+        Ok(self)
+    }
+
+    /// `other` で指定された項目を上書きする
+    fn merge(self, other: &Profile) -> Self {
+        Self {
+            base_url: other.base_url.clone().or(self.base_url),
+            token: other.token.clone().or(self.token),
+            username: other.username.clone().or(self.username),
+            password: other.password.clone().or(self.password),
+            timeout_secs: other.timeout_secs.or(self.timeout_secs),
+            connect_timeout_secs: other.connect_timeout_secs.or(self.connect_timeout_secs),
+            default_genre: other.default_genre.clone().or(self.default_genre),
+        }
+    }
+}
+
+/// 設定ファイル
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Config {
+    /// プロファイルを指定しない場合に使うプロファイル名
+    pub default_profile: Option<String>,
+    /// 名前付きのプロファイル
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// すべてのプロファイルの既定値（トップレベルの項目）
+    #[serde(flatten)]
+    pub defaults: Profile,
+}
+
+impl Config {
+    /// 設定ファイルのパス
+    ///
+    /// 環境変数 `SHISHUTSUKAN_CONFIG` があればそのパス、なければ
+    /// `$XDG_CONFIG_HOME/shishutsukan/config.toml` です。設定ディレクトリが
+    /// 分からない場合は `None` を返します。
+    pub fn path() -> Option<PathBuf> {
+        match std::env::var_os("SHISHUTSUKAN_CONFIG") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => dirs::config_dir().map(|dir| dir.join("shishutsukan").join("config.toml")),
+        }
+    }
+
+    /// 既定のパスから設定ファイルを読み込む
+    ///
+    /// ファイルがない場合は空の設定を返します。
+    ///
+    /// # Errors
+    ///
+    /// ファイルを読み込めない場合や、TOMLとして正しくない場合
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) => Self::open(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// 設定ファイルを読み込む
+    ///
+    /// ファイルがない場合は空の設定を返します。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 設定ファイルのパス
+    ///
+    /// # Errors
+    ///
+    /// ファイルを読み込めない場合や、TOMLとして正しくない場合
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        Self::from_toml(&text).map_err(|e| match e {
+            ShishutsukanError::ConfigError(message) => {
+                ShishutsukanError::ConfigError(format!("{}: {}", path.display(), message))
+            }
+            e => e,
+        })
+    }
+
+    /// TOML文字列から設定を読み込む
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - 設定ファイルの内容
+    ///
+    /// # Errors
+    ///
+    /// TOMLとして正しくない場合
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| ShishutsukanError::ConfigError(e.to_string()))
+    }
+
+    /// プロファイルを選び、環境変数による上書きを適用する
+    ///
+    /// `name` を指定しなかった場合は、環境変数 `SHISHUTSUKAN_PROFILE`、
+    /// `default_profile` の順にプロファイルを選びます。いずれもなければ
+    /// トップレベルの項目だけを使います。
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - プロファイル名
+    ///
+    /// # Errors
+    ///
+    /// 選んだプロファイルが設定ファイルにない場合
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile> {
+        self.resolve(name, |name| std::env::var(name).ok())
+    }
+
+    fn resolve(&self, name: Option<&str>, var: impl Fn(&str) -> Option<String>) -> Result<Profile> {
+        let name = name
+            .map(str::to_string)
+            .or_else(|| var("SHISHUTSUKAN_PROFILE"))
+            .or_else(|| self.default_profile.clone());
+        let profile = match name {
+            Some(name) => {
+                let profile = self.profiles.get(&name).ok_or_else(|| {
+                    ShishutsukanError::ConfigError(format!("プロファイル {} がありません", name))
+                })?;
+                self.defaults.clone().merge(profile)
+            }
+            None => self.defaults.clone(),
+        };
+        profile.with_vars(var)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+default_profile = "home"
+timeout_secs = 10
+
+[profiles.home]
+base_url = "http://home-server:8000"
+token = "secret"
+default_genre = "食費"
+
+[profiles.staging]
+base_url = "https://staging.example.com"
+username = "family"
+timeout_secs = 30
+"#;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_resolve_profile() {
+        let config = Config::from_toml(CONFIG).unwrap();
+
+        let home = config.resolve(None, no_env).unwrap();
+        assert_eq!(home.base_url(), "http://home-server:8000");
+        assert_eq!(home.auth(), Some(Auth::Bearer("secret".to_string())));
+        assert_eq!(home.timeout_secs, Some(10));
+
+        let staging = config.resolve(Some("staging"), no_env).unwrap();
+        assert_eq!(staging.timeout_secs, Some(30));
+        assert_eq!(staging.default_genre, None);
+        assert_eq!(
+            staging.auth(),
+            Some(Auth::Basic {
+                username: "family".to_string(),
+                password: None
+            })
+        );
+
+        assert!(matches!(
+            config.resolve(Some("missing"), no_env),
+            Err(ShishutsukanError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let config = Config::from_toml(CONFIG).unwrap();
+        let env = |name: &str| match name {
+            "SHISHUTSUKAN_PROFILE" => Some("staging".to_string()),
+            "SHISHUTSUKAN_URL" => Some("http://localhost:9000".to_string()),
+            "SHISHUTSUKAN_TIMEOUT" => Some("5".to_string()),
+            _ => None,
+        };
+        let profile = config.resolve(None, env).unwrap();
+        assert_eq!(profile.base_url(), "http://localhost:9000");
+        assert_eq!(profile.username.as_deref(), Some("family"));
+        assert_eq!(profile.timeout_secs, Some(5));
+
+        let invalid = |name: &str| (name == "SHISHUTSUKAN_TIMEOUT").then(|| "soon".to_string());
+        assert!(config.resolve(None, invalid).is_err());
+    }
+
+    #[test]
+    fn test_top_level_only() {
+        let config = Config::from_toml("base_url = \"http://home-server:8000\"\n").unwrap();
+        let profile = config.resolve(None, no_env).unwrap();
+        assert_eq!(profile.base_url(), "http://home-server:8000");
+        assert_eq!(Profile::default().base_url(), DEFAULT_BASE_URL);
+        assert!(Config::from_toml("timeout_secs = \"ten\"").is_err());
+    }
+}
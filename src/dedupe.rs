@@ -0,0 +1,236 @@
+use crate::models::ExpenseWithId;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// 重複とみなす条件
+///
+/// ジャンルが同じで、日付の差が `max_days_apart` 日以内かつ金額の差が
+/// `amount_tolerance` 円以内の支出データを重複候補とします。
+/// デフォルトは日付・ジャンル・金額が完全に一致するものだけです。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DuplicateCriteria {
+    /// 許容する日付の差（日）
+    pub max_days_apart: u32,
+    /// 許容する金額の差（円）
+    pub amount_tolerance: i32,
+}
+
+impl DuplicateCriteria {
+    /// 完全一致だけを重複とみなす条件を作成
+    pub fn exact() -> Self {
+        Self::default()
+    }
+
+    /// 日付と金額の差を許容する条件を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `max_days_apart` - 許容する日付の差（日）
+    /// * `amount_tolerance` - 許容する金額の差（円）
+    pub fn near(max_days_apart: u32, amount_tolerance: i32) -> Self {
+        Self {
+            max_days_apart,
+            amount_tolerance,
+        }
+    }
+}
+
+/// 重複の可能性がある支出データのグループ
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// 残す支出データ（グループ内で最も小さいID）
+    pub keep: ExpenseWithId,
+    /// 重複とみなした支出データ（ID順。いずれも `keep` と条件を満たす）
+    pub duplicates: Vec<ExpenseWithId>,
+    /// 重複らしさ（0.0〜1.0）。完全一致なら1.0
+    ///
+    /// `keep` と各重複候補との類似度のうち最小のもの。
+    pub score: f64,
+}
+
+impl DuplicateGroup {
+    /// グループ内がすべて完全一致かどうか
+    pub fn is_exact(&self) -> bool {
+        self.score >= 1.0
+    }
+}
+
+/// 支出データの一覧から重複の可能性があるグループを抽出
+///
+/// 結果は重複らしさの高い順に並びます。グループには残す支出データ（最も小さいID）と
+/// 条件を満たすものだけが入り、重複候補どうしが似ているだけの支出データは含みません。
+/// 日付を解釈できない支出データは、日付の文字列が完全に一致する場合だけ比較対象になります。
+///
+/// # Arguments
+///
+/// * `expenses` - 支出データの一覧
+/// * `criteria` - 重複とみなす条件
+///
+/// # Examples
+///
+/// ```
+/// use shishutsukan::{find_duplicates, DuplicateCriteria, ExpenseWithId};
+///
+/// let expenses = vec![
+///     ExpenseWithId { id: 1, date: "2025-01-15".to_string(), genre: "食費".to_string(), amount: 1000 },
+///     ExpenseWithId { id: 2, date: "2025-01-15".to_string(), genre: "食費".to_string(), amount: 1000 },
+/// ];
+/// let groups = find_duplicates(&expenses, &DuplicateCriteria::exact());
+/// assert_eq!(groups[0].keep.id, 1);
+/// assert_eq!(groups[0].duplicates[0].id, 2);
+/// ```
+pub fn find_duplicates(
+    expenses: &[ExpenseWithId],
+    criteria: &DuplicateCriteria,
+) -> Vec<DuplicateGroup> {
+    let mut candidates: Vec<Candidate> = expenses
+        .iter()
+        .map(|expense| Candidate {
+            expense,
+            date: expense.naive_date(),
+        })
+        .collect();
+    candidates.sort_by_key(|candidate| candidate.expense.id);
+
+    let mut by_genre: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        by_genre
+            .entry(candidate.expense.genre.as_str())
+            .or_default()
+            .push(index);
+    }
+
+    // ID の小さい順に残す支出データを決め、それと条件を満たすものだけをグループにする。
+    // 重複候補どうしが似ているだけのもの（連鎖）は同じグループに入れない
+    let max_days = chrono::Days::new(u64::from(criteria.max_days_apart));
+    let mut assigned = vec![false; candidates.len()];
+    let mut groups = Vec::new();
+    for indices in by_genre.values() {
+        // 日付順（日付を解釈できないものが先頭）
+        let mut by_date = indices.clone();
+        by_date.sort_by_key(|&index| (candidates[index].date, index));
+
+        for &keep in indices {
+            if assigned[keep] {
+                continue;
+            }
+            let window = match candidates[keep].date {
+                Some(date) => {
+                    let from = date.checked_sub_days(max_days).unwrap_or(NaiveDate::MIN);
+                    let until = date.checked_add_days(max_days).unwrap_or(NaiveDate::MAX);
+                    let start = by_date.partition_point(|&i| candidates[i].date < Some(from));
+                    let end = by_date.partition_point(|&i| candidates[i].date <= Some(until));
+                    &by_date[start..end]
+                }
+                None => {
+                    let end = by_date.partition_point(|&i| candidates[i].date.is_none());
+                    &by_date[..end]
+                }
+            };
+            let mut members: Vec<(usize, f64)> = window
+                .iter()
+                .filter(|&&index| index > keep && !assigned[index])
+                .filter_map(|&index| {
+                    let score = candidates[keep].similarity(&candidates[index], criteria)?;
+                    Some((index, score))
+                })
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            members.sort_by_key(|&(index, _)| index);
+            for &(index, _) in &members {
+                assigned[index] = true;
+            }
+            groups.push(DuplicateGroup {
+                keep: candidates[keep].expense.clone(),
+                duplicates: members
+                    .iter()
+                    .map(|&(index, _)| candidates[index].expense.clone())
+                    .collect(),
+                score: members.iter().map(|&(_, score)| score).fold(1.0, f64::min),
+            });
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.keep.id.cmp(&b.keep.id))
+    });
+    groups
+}
+
+/// 日付を解釈済みの比較対象
+struct Candidate<'a> {
+    expense: &'a ExpenseWithId,
+    date: Option<NaiveDate>,
+}
+
+impl Candidate<'_> {
+    /// 2件の支出データの類似度を計算する。条件を満たさない場合は `None`
+    fn similarity(&self, other: &Candidate, criteria: &DuplicateCriteria) -> Option<f64> {
+        if self.expense.genre != other.expense.genre {
+            return None;
+        }
+
+        let days_apart = match (self.date, other.date) {
+            (Some(a), Some(b)) => (a - b).num_days().unsigned_abs(),
+            _ if self.expense.date == other.expense.date => 0,
+            _ => return None,
+        };
+        let amount_diff =
+            (i64::from(self.expense.amount) - i64::from(other.expense.amount)).unsigned_abs();
+
+        let max_days = u64::from(criteria.max_days_apart);
+        let max_amount = u64::from(criteria.amount_tolerance.unsigned_abs());
+        if days_apart > max_days || amount_diff > max_amount {
+            return None;
+        }
+
+        // 差が許容範囲の上限に近いほど低くなる。日付と金額の寄与は半分ずつ
+        let day_penalty = days_apart as f64 / (max_days + 1) as f64;
+        let amount_penalty = amount_diff as f64 / (max_amount + 1) as f64;
+        Some(1.0 - 0.5 * day_penalty - 0.5 * amount_penalty)
+    }
+}
+
+/// 重複削除の計画
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DedupePlan {
+    /// 削除する支出データ
+    pub deletions: Vec<ExpenseWithId>,
+}
+
+impl DedupePlan {
+    /// 重複グループから削除計画を作成
+    ///
+    /// 重複らしさが `min_score` 以上のグループについて、`keep` 以外の支出データを
+    /// 削除対象にします。
+    ///
+    /// # Arguments
+    ///
+    /// * `groups` - [`find_duplicates`] の結果
+    /// * `min_score` - 削除対象にする重複らしさの下限
+    pub fn from_groups(groups: &[DuplicateGroup], min_score: f64) -> Self {
+        let deletions = groups
+            .iter()
+            .filter(|group| group.score >= min_score)
+            .flat_map(|group| group.duplicates.iter().cloned())
+            .collect();
+        Self { deletions }
+    }
+}
+
+/// 重複削除の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DedupeReport {
+    /// ドライランかどうか
+    pub dry_run: bool,
+    /// 削除した（ドライランでは削除する予定の）支出データ
+    pub deleted: Vec<ExpenseWithId>,
+    /// サーバー上に見つからなかった支出データ
+    pub missing: Vec<ExpenseWithId>,
+    /// 削除に失敗した支出データとエラーメッセージ
+    pub failed: Vec<(ExpenseWithId, String)>,
+}
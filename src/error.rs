@@ -22,6 +22,42 @@ pub enum ShishutsukanError {
     /// サーバーエラー
     #[error("サーバーエラー: {0}")]
     ServerError(String),
+
+    /// 入出力エラー
+    #[error("入出力エラー: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// 取り込むデータの形式が正しくない
+    #[error("解析エラー: {0}")]
+    ParseError(String),
+
+    /// 復元先のサーバーに支出データが既にある
+    #[error("復元先のサーバーに支出データが {0} 件あります")]
+    ServerNotEmpty(usize),
+
+    /// 設定ファイルの誤り
+    #[cfg(feature = "config")]
+    #[error("設定エラー: {0}")]
+    ConfigError(String),
+
+    /// データベースエラー
+    #[cfg(feature = "mirror")]
+    #[error("データベースエラー: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+
+    /// スプレッドシートの作成エラー
+    #[cfg(feature = "xlsx")]
+    #[error("スプレッドシートエラー: {0}")]
+    SpreadsheetError(#[from] rust_xlsxwriter::XlsxError),
+}
+
+impl ShishutsukanError {
+    /// サーバーに接続できなかったことによるエラーかどうか
+    ///
+    /// 接続の失敗とタイムアウトが該当します。
+    pub fn is_connectivity_error(&self) -> bool {
+        matches!(self, Self::NetworkError(e) if e.is_connect() || e.is_timeout())
+    }
 }
 
 /// Result型のエイリアス
@@ -0,0 +1,6 @@
+//! 支出データとジャンルのエクスポート
+
+pub mod csv;
+pub mod ledger;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
@@ -0,0 +1,366 @@
+//! CSV形式でのエクスポート
+//!
+//! ```
+//! use shishutsukan::export::csv::{write_expenses, CsvOptions, ExpenseColumn};
+//! use shishutsukan::ExpenseWithId;
+//!
+//! let expenses = vec![ExpenseWithId {
+//!     id: 1,
+//!     date: "2025-01-15".to_string(),
+//!     genre: "食費".to_string(),
+//!     amount: 1000,
+//! }];
+//!
+//! let mut output = Vec::new();
+//! write_expenses(&mut output, &expenses, &ExpenseColumn::ALL, &CsvOptions::default()).unwrap();
+//! assert_eq!(String::from_utf8(output).unwrap(), "ID,日付,ジャンル,金額\n1,2025-01-15,食費,1000\n");
+//! ```
+
+use crate::error::{Result, ShishutsukanError};
+use crate::models::{ExpenseWithId, GenreWithId};
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+use std::io::Write;
+
+/// UTF-8のBOM
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// ヘッダー行の言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderLanguage {
+    /// 日本語（例: 日付, ジャンル, 金額）
+    #[default]
+    Japanese,
+    /// 英語（例: date, genre, amount）
+    English,
+}
+
+/// CSVの出力設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// 区切り文字
+    pub delimiter: char,
+    /// ヘッダー行の言語（`None` の場合はヘッダー行を出力しない）
+    pub header: Option<HeaderLanguage>,
+    /// 支出データの日付のフォーマット（`chrono` の書式。`None` の場合はそのまま出力）
+    pub date_format: Option<String>,
+    /// 先頭にUTF-8のBOMを出力するかどうか
+    pub bom: bool,
+    /// 改行にCRLFを使うかどうか
+    pub crlf: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            header: Some(HeaderLanguage::Japanese),
+            date_format: None,
+            bom: false,
+            crlf: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Excelで文字化けせずに開ける設定（BOM付き・CRLF・日本語ヘッダー・`2025/01/15` 形式）
+    pub fn excel() -> Self {
+        Self {
+            date_format: Some("%Y/%m/%d".to_string()),
+            bom: true,
+            crlf: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// CSVの列
+pub trait CsvColumn: Copy {
+    /// ヘッダー行に出力する列名
+    fn header(self, language: HeaderLanguage) -> &'static str;
+}
+
+/// CSVの1行として出力できるデータ
+pub trait CsvRecord {
+    /// 列の種類
+    type Column: CsvColumn;
+
+    /// 列の値を文字列にする
+    fn field(&self, column: Self::Column, options: &CsvOptions) -> String;
+}
+
+/// 支出データの列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpenseColumn {
+    /// ID
+    Id,
+    /// 日付
+    Date,
+    /// ジャンル
+    Genre,
+    /// 金額
+    Amount,
+}
+
+impl ExpenseColumn {
+    /// すべての列
+    pub const ALL: [Self; 4] = [Self::Id, Self::Date, Self::Genre, Self::Amount];
+}
+
+impl CsvColumn for ExpenseColumn {
+    fn header(self, language: HeaderLanguage) -> &'static str {
+        match (self, language) {
+            (Self::Id, HeaderLanguage::Japanese) => "ID",
+            (Self::Date, HeaderLanguage::Japanese) => "日付",
+            (Self::Genre, HeaderLanguage::Japanese) => "ジャンル",
+            (Self::Amount, HeaderLanguage::Japanese) => "金額",
+            (Self::Id, HeaderLanguage::English) => "id",
+            (Self::Date, HeaderLanguage::English) => "date",
+            (Self::Genre, HeaderLanguage::English) => "genre",
+            (Self::Amount, HeaderLanguage::English) => "amount",
+        }
+    }
+}
+
+impl CsvRecord for ExpenseWithId {
+    type Column = ExpenseColumn;
+
+    fn field(&self, column: ExpenseColumn, options: &CsvOptions) -> String {
+        match column {
+            ExpenseColumn::Id => self.id.to_string(),
+            ExpenseColumn::Date => match (&options.date_format, self.naive_date()) {
+                (Some(format), Some(date)) => date.format(format).to_string(),
+                _ => self.date.clone(),
+            },
+            ExpenseColumn::Genre => self.genre.clone(),
+            ExpenseColumn::Amount => self.amount.to_string(),
+        }
+    }
+}
+
+/// ジャンルの列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenreColumn {
+    /// ID
+    Id,
+    /// ジャンル名
+    Name,
+    /// 作成日時
+    CreatedAt,
+}
+
+impl GenreColumn {
+    /// すべての列
+    pub const ALL: [Self; 3] = [Self::Id, Self::Name, Self::CreatedAt];
+}
+
+impl CsvColumn for GenreColumn {
+    fn header(self, language: HeaderLanguage) -> &'static str {
+        match (self, language) {
+            (Self::Id, HeaderLanguage::Japanese) => "ID",
+            (Self::Name, HeaderLanguage::Japanese) => "ジャンル名",
+            (Self::CreatedAt, HeaderLanguage::Japanese) => "作成日時",
+            (Self::Id, HeaderLanguage::English) => "id",
+            (Self::Name, HeaderLanguage::English) => "name",
+            (Self::CreatedAt, HeaderLanguage::English) => "created_at",
+        }
+    }
+}
+
+impl CsvRecord for GenreWithId {
+    type Column = GenreColumn;
+
+    fn field(&self, column: GenreColumn, _options: &CsvOptions) -> String {
+        match column {
+            GenreColumn::Id => self.id.to_string(),
+            GenreColumn::Name => self.name.clone(),
+            GenreColumn::CreatedAt => self.created_at.clone(),
+        }
+    }
+}
+
+/// 1行ずつ書き出すCSVライター
+///
+/// 行を文字列としてまとめずに書き出すため、大量の履歴も一定のメモリで出力できます。
+/// 書き込み先には `BufWriter` などのバッファ付きライターを推奨します。
+///
+/// # Examples
+///
+/// ```
+/// use shishutsukan::export::csv::{CsvOptions, CsvWriter, ExpenseColumn};
+/// use shishutsukan::ExpenseWithId;
+///
+/// let columns = [ExpenseColumn::Date, ExpenseColumn::Amount];
+/// let mut writer = CsvWriter::new(Vec::new(), &columns, CsvOptions::excel()).unwrap();
+/// writer
+///     .write(&ExpenseWithId { id: 1, date: "2025-01-15".to_string(), genre: "食費".to_string(), amount: 1000 })
+///     .unwrap();
+/// let output = writer.finish().unwrap();
+/// assert!(output.ends_with("2025/01/15,1000\r\n".as_bytes()));
+/// ```
+#[derive(Debug)]
+pub struct CsvWriter<W: Write, C: CsvColumn> {
+    inner: W,
+    columns: Vec<C>,
+    options: CsvOptions,
+}
+
+impl<W: Write, C: CsvColumn> CsvWriter<W, C> {
+    /// ライターを作成し、BOMとヘッダー行を書き出す
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - 書き込み先
+    /// * `columns` - 出力する列（この順に出力）
+    /// * `options` - 出力設定
+    ///
+    /// # Errors
+    ///
+    /// 日付のフォーマットが `chrono` の書式として正しくない場合や時刻・タイムゾーンを含む場合、
+    /// 書き込みに失敗した場合
+    pub fn new(mut inner: W, columns: &[C], options: CsvOptions) -> Result<Self> {
+        if let Some(format) = &options.date_format {
+            if !StrftimeItems::new(format).all(|item| is_date_item(&item)) {
+                return Err(ShishutsukanError::ParseError(format!(
+                    "日付のフォーマットが正しくありません: {}",
+                    format
+                )));
+            }
+        }
+        if options.bom {
+            inner.write_all(BOM)?;
+        }
+
+        let mut writer = Self {
+            inner,
+            columns: columns.to_vec(),
+            options,
+        };
+        if let Some(language) = writer.options.header {
+            let headers: Vec<String> = writer
+                .columns
+                .iter()
+                .map(|column| column.header(language).to_string())
+                .collect();
+            writer.write_line(&headers)?;
+        }
+        Ok(writer)
+    }
+
+    /// 1行書き出す
+    pub fn write<R: CsvRecord<Column = C>>(&mut self, record: &R) -> Result<()> {
+        let fields: Vec<String> = self
+            .columns
+            .iter()
+            .map(|&column| record.field(column, &self.options))
+            .collect();
+        self.write_line(&fields)
+    }
+
+    /// 書き込みを完了して書き込み先を返す
+    pub fn finish(mut self) -> Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_line(&mut self, fields: &[String]) -> Result<()> {
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                write!(self.inner, "{}", self.options.delimiter)?;
+            }
+            self.inner
+                .write_all(escape(field, self.options.delimiter).as_bytes())?;
+        }
+        self.inner
+            .write_all(if self.options.crlf { b"\r\n" } else { b"\n" })?;
+        Ok(())
+    }
+}
+
+/// 支出データの一覧をCSVとして書き出す
+///
+/// # Arguments
+///
+/// * `writer` - 書き込み先
+/// * `expenses` - 支出データの一覧
+/// * `columns` - 出力する列
+/// * `options` - 出力設定
+pub fn write_expenses<W: Write>(
+    writer: W,
+    expenses: &[ExpenseWithId],
+    columns: &[ExpenseColumn],
+    options: &CsvOptions,
+) -> Result<()> {
+    write_all(writer, expenses, columns, options)
+}
+
+/// ジャンルの一覧をCSVとして書き出す
+///
+/// # Arguments
+///
+/// * `writer` - 書き込み先
+/// * `genres` - ジャンルの一覧
+/// * `columns` - 出力する列
+/// * `options` - 出力設定
+pub fn write_genres<W: Write>(
+    writer: W,
+    genres: &[GenreWithId],
+    columns: &[GenreColumn],
+    options: &CsvOptions,
+) -> Result<()> {
+    write_all(writer, genres, columns, options)
+}
+
+fn write_all<W: Write, R: CsvRecord>(
+    writer: W,
+    records: &[R],
+    columns: &[R::Column],
+    options: &CsvOptions,
+) -> Result<()> {
+    let mut writer = CsvWriter::new(writer, columns, options.clone())?;
+    for record in records {
+        writer.write(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// 日付だけで出力できる書式の要素かどうか（時刻やタイムゾーンは `NaiveDate` では出力できない）
+fn is_date_item(item: &Item<'_>) -> bool {
+    match item {
+        Item::Literal(_) | Item::OwnedLiteral(_) | Item::Space(_) | Item::OwnedSpace(_) => true,
+        Item::Numeric(numeric, _) => matches!(
+            numeric,
+            Numeric::Year
+                | Numeric::YearDiv100
+                | Numeric::YearMod100
+                | Numeric::IsoYear
+                | Numeric::IsoYearDiv100
+                | Numeric::IsoYearMod100
+                | Numeric::Month
+                | Numeric::Day
+                | Numeric::WeekFromSun
+                | Numeric::WeekFromMon
+                | Numeric::IsoWeek
+                | Numeric::NumDaysFromSun
+                | Numeric::WeekdayFromMon
+                | Numeric::Ordinal
+        ),
+        Item::Fixed(fixed) => matches!(
+            fixed,
+            Fixed::ShortMonthName
+                | Fixed::LongMonthName
+                | Fixed::ShortWeekdayName
+                | Fixed::LongWeekdayName
+        ),
+        _ => false,
+    }
+}
+
+/// 区切り文字・引用符・改行を含むフィールドを引用符で囲む
+fn escape(field: &str, delimiter: char) -> std::borrow::Cow<'_, str> {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
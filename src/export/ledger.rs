@@ -0,0 +1,241 @@
+//! ledger / hledger / beancount 形式でのエクスポート
+//!
+//! 支出データを1件ずつ、ジャンルに対応する費用の勘定と支払元の勘定の間の取引として
+//! 書き出します。金額の単位は `JPY` です。
+//!
+//! ```
+//! use shishutsukan::export::ledger::{write_transactions, AccountMapping, LedgerFormat};
+//! use shishutsukan::ExpenseWithId;
+//!
+//! let expenses = vec![ExpenseWithId {
+//!     id: 1,
+//!     date: "2025-01-15".to_string(),
+//!     genre: "食費".to_string(),
+//!     amount: 1000,
+//! }];
+//! let mapping = AccountMapping::new().funding_account("Liabilities:Card");
+//!
+//! let mut output = Vec::new();
+//! write_transactions(&mut output, &expenses, &[], &mapping, LedgerFormat::Ledger).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(output).unwrap(),
+//!     "2025-01-15 * 食費\n    ; id: 1\n    Expenses:食費  1000 JPY\n    Liabilities:Card\n"
+//! );
+//! ```
+
+use crate::error::Result;
+use crate::models::{ExpenseWithId, GenreWithId};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+/// 金額の単位
+const COMMODITY: &str = "JPY";
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LedgerFormat {
+    /// ledger / hledger 形式
+    #[default]
+    Ledger,
+    /// beancount 形式（使用する勘定の `open` ディレクティブも出力）
+    Beancount,
+}
+
+/// ジャンルと勘定の対応
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountMapping {
+    /// 費用の勘定の接頭辞（ジャンル名の前に付ける）
+    pub prefix: String,
+    /// 支払元の勘定（例: `Assets:Cash`、`Liabilities:Card`）
+    pub funding_account: String,
+    /// ジャンルごとに個別に指定した勘定
+    pub accounts: HashMap<String, String>,
+}
+
+impl Default for AccountMapping {
+    fn default() -> Self {
+        Self {
+            prefix: "Expenses".to_string(),
+            funding_account: "Assets:Cash".to_string(),
+            accounts: HashMap::new(),
+        }
+    }
+}
+
+impl AccountMapping {
+    /// `Expenses:<ジャンル名>` と `Assets:Cash` を使う対応を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 費用の勘定の接頭辞を指定
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// 支払元の勘定を指定
+    pub fn funding_account(mut self, account: impl Into<String>) -> Self {
+        self.funding_account = account.into();
+        self
+    }
+
+    /// ジャンルの勘定を個別に指定
+    ///
+    /// # Arguments
+    ///
+    /// * `genre` - ジャンル名
+    /// * `account` - 勘定（接頭辞は付けずにそのまま使う）
+    pub fn account(mut self, genre: impl Into<String>, account: impl Into<String>) -> Self {
+        self.accounts.insert(genre.into(), account.into());
+        self
+    }
+
+    /// ジャンルに対応する勘定
+    ///
+    /// 個別の指定がない場合は接頭辞とジャンル名をつなげ、出力形式で使えない文字を
+    /// `-` に置き換えます。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::export::ledger::{AccountMapping, LedgerFormat};
+    ///
+    /// let mapping = AccountMapping::new().account("家賃", "Expenses:Housing:Rent");
+    /// assert_eq!(mapping.account_for("家賃", LedgerFormat::Beancount), "Expenses:Housing:Rent");
+    /// assert_eq!(mapping.account_for("日用品 雑貨", LedgerFormat::Beancount), "Expenses:日用品-雑貨");
+    /// assert_eq!(mapping.account_for("日用品 雑貨", LedgerFormat::Ledger), "Expenses:日用品 雑貨");
+    /// ```
+    pub fn account_for(&self, genre: &str, format: LedgerFormat) -> String {
+        if let Some(account) = self.accounts.get(genre) {
+            return account.clone();
+        }
+        let genre = match format {
+            LedgerFormat::Ledger => ledger_component(genre),
+            LedgerFormat::Beancount => beancount_component(genre),
+        };
+        format!("{}:{}", self.prefix, genre)
+    }
+}
+
+/// 支出データを取引として書き出す
+///
+/// 取引は日付順（同じ日付ではID順）に並べ、支出データのIDを取引のメタデータとして
+/// 出力します。beancount 形式では、`genres` のジャンルと支出データで使われている
+/// ジャンルの勘定、支払元の勘定の `open` ディレクティブを最初の取引の日付で出力します。
+///
+/// # Arguments
+///
+/// * `writer` - 書き込み先
+/// * `expenses` - 支出データの一覧
+/// * `genres` - ジャンルの一覧（支出データがないジャンルの勘定も開く場合に指定）
+/// * `mapping` - ジャンルと勘定の対応
+/// * `format` - 出力形式
+pub fn write_transactions<W: Write>(
+    mut writer: W,
+    expenses: &[ExpenseWithId],
+    genres: &[GenreWithId],
+    mapping: &AccountMapping,
+    format: LedgerFormat,
+) -> Result<()> {
+    let mut expenses: Vec<&ExpenseWithId> = expenses.iter().collect();
+    expenses.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+
+    if format == LedgerFormat::Beancount {
+        if let Some(first) = expenses.first() {
+            let accounts: BTreeSet<String> = genres
+                .iter()
+                .map(|genre| genre.name.as_str())
+                .chain(expenses.iter().map(|expense| expense.genre.as_str()))
+                .map(|genre| mapping.account_for(genre, format))
+                .chain(std::iter::once(mapping.funding_account.clone()))
+                .collect();
+            for account in accounts {
+                writeln!(writer, "{} open {} {}", first.date, account, COMMODITY)?;
+            }
+            writeln!(writer)?;
+        }
+    }
+
+    for (index, expense) in expenses.iter().enumerate() {
+        if index > 0 {
+            writeln!(writer)?;
+        }
+        let account = mapping.account_for(&expense.genre, format);
+        match format {
+            LedgerFormat::Ledger => {
+                writeln!(writer, "{} * {}", expense.date, expense.genre)?;
+                writeln!(writer, "    ; id: {}", expense.id)?;
+            }
+            LedgerFormat::Beancount => {
+                writeln!(
+                    writer,
+                    "{} * \"{}\"",
+                    expense.date,
+                    expense.genre.replace('\\', "\\\\").replace('"', "\\\"")
+                )?;
+                writeln!(writer, "  id: \"{}\"", expense.id)?;
+            }
+        }
+        let indent = match format {
+            LedgerFormat::Ledger => "    ",
+            LedgerFormat::Beancount => "  ",
+        };
+        writeln!(
+            writer,
+            "{}{}  {} {}",
+            indent, account, expense.amount, COMMODITY
+        )?;
+        writeln!(writer, "{}{}", indent, mapping.funding_account)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// ledger の勘定名として使えるようにする（連続する空白とタブは勘定名の終わりとみなされる）
+fn ledger_component(genre: &str) -> String {
+    genre.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// beancount の勘定名として使えるようにする（英数字・`-`・ASCII以外の文字のみ使える）
+fn beancount_component(genre: &str) -> String {
+    genre
+        .split(':')
+        .map(|component| {
+            let mut component: String = component
+                .trim()
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' || !c.is_ascii() {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect();
+            // 各階層の先頭は大文字または数字である必要がある
+            if let Some(first) = component.get(..1) {
+                if first.starts_with(|c: char| c.is_ascii_lowercase()) {
+                    component.replace_range(..1, &first.to_ascii_uppercase());
+                } else if first == "-" {
+                    component.insert(0, 'X');
+                }
+            }
+            component
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beancount_component() {
+        assert_eq!(beancount_component("food & drink"), "Food---drink");
+        assert_eq!(beancount_component("食費:外食"), "食費:外食");
+        assert_eq!(beancount_component("_misc"), "X-misc");
+    }
+}
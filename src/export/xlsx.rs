@@ -0,0 +1,253 @@
+//! Excel形式（.xlsx）でのエクスポート
+//!
+//! `xlsx` フィーチャーを有効にすると利用できます。外部のツールなしで、次の3シートからなる
+//! ブックを書き出します。金額のセルには円の表示形式を設定します。
+//!
+//! * `支出一覧` - 支出データの一覧と合計
+//! * `ジャンル別` - ジャンル×月の合計金額
+//! * `月別集計` - 月ごとの件数・合計金額・平均金額
+//!
+//! ```no_run
+//! use shishutsukan::export::xlsx::write_workbook;
+//! use shishutsukan::ShishutsukanClient;
+//! use std::fs::File;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ShishutsukanClient::new("http://localhost:8000");
+//! let genres = client.get_genres().await?;
+//! let expenses = client.get_expenses().await?;
+//! write_workbook(File::create("支出.xlsx")?, &expenses, &genres)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Result;
+use crate::models::{ExpenseWithId, GenreWithId};
+use crate::report::Report;
+use chrono::Datelike;
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatBorder, Workbook, Worksheet};
+use std::io::Write;
+
+/// 円の表示形式
+const YEN_FORMAT: &str = "[$¥-411]#,##0";
+/// 日付の表示形式
+const DATE_FORMAT: &str = "yyyy/mm/dd";
+
+/// 書式の組
+struct Formats {
+    header: Format,
+    yen: Format,
+    date: Format,
+    total_label: Format,
+    total_yen: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        let header = Format::new()
+            .set_bold()
+            .set_background_color(0xDDEBF7)
+            .set_border_bottom(FormatBorder::Thin);
+        let yen = Format::new().set_num_format(YEN_FORMAT);
+        let total_label = Format::new().set_bold().set_border_top(FormatBorder::Thin);
+        let total_yen = total_label.clone().set_num_format(YEN_FORMAT);
+        Self {
+            header,
+            yen,
+            date: Format::new().set_num_format(DATE_FORMAT),
+            total_label,
+            total_yen,
+        }
+    }
+}
+
+/// 支出データとジャンルからブックを作成して書き出す
+///
+/// 日付を解釈できない支出データは `支出一覧` に文字列のまま出力し、集計には含めません。
+///
+/// # Arguments
+///
+/// * `writer` - 書き込み先
+/// * `expenses` - 支出データの一覧
+/// * `genres` - ジャンルの一覧（支出データがないジャンルも `ジャンル別` に行を出力）
+pub fn write_workbook<W: Write + Send>(
+    writer: W,
+    expenses: &[ExpenseWithId],
+    genres: &[GenreWithId],
+) -> Result<()> {
+    let mut workbook = build_workbook(expenses, genres)?;
+    workbook.save_to_writer(writer)?;
+    Ok(())
+}
+
+fn build_workbook(expenses: &[ExpenseWithId], genres: &[GenreWithId]) -> Result<Workbook> {
+    let formats = Formats::new();
+    let mut sorted: Vec<&ExpenseWithId> = expenses.iter().collect();
+    sorted.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+
+    let mut workbook = Workbook::new();
+    write_expense_sheet(workbook.add_worksheet(), &sorted, &formats)?;
+    let report = Report::new(expenses);
+    write_genre_sheet(workbook.add_worksheet(), &sorted, genres, &report, &formats)?;
+    write_monthly_sheet(workbook.add_worksheet(), &report, &formats)?;
+    Ok(workbook)
+}
+
+fn write_expense_sheet(
+    sheet: &mut Worksheet,
+    expenses: &[&ExpenseWithId],
+    formats: &Formats,
+) -> Result<()> {
+    sheet.set_name("支出一覧")?;
+    write_headers(sheet, &["ID", "日付", "ジャンル", "金額"], formats)?;
+    sheet.set_column_width(1, 12)?;
+    sheet.set_column_width(2, 16)?;
+    sheet.set_column_width(3, 12)?;
+
+    let mut row = 1;
+    for expense in expenses {
+        sheet.write_number(row, 0, expense.id)?;
+        match expense.naive_date().and_then(excel_date) {
+            Some(date) => sheet.write_datetime_with_format(row, 1, &date, &formats.date)?,
+            None => sheet.write_string(row, 1, &expense.date)?,
+        };
+        sheet.write_string(row, 2, &expense.genre)?;
+        sheet.write_number_with_format(row, 3, expense.amount, &formats.yen)?;
+        row += 1;
+    }
+    if !expenses.is_empty() {
+        sheet.autofilter(0, 0, row - 1, 3)?;
+    }
+
+    let total: i64 = expenses
+        .iter()
+        .map(|expense| i64::from(expense.amount))
+        .sum();
+    sheet.write_string_with_format(row, 2, "合計", &formats.total_label)?;
+    sheet.write_number_with_format(row, 3, total as f64, &formats.total_yen)?;
+    Ok(())
+}
+
+fn write_genre_sheet(
+    sheet: &mut Worksheet,
+    expenses: &[&ExpenseWithId],
+    genres: &[GenreWithId],
+    report: &Report,
+    formats: &Formats,
+) -> Result<()> {
+    sheet.set_name("ジャンル別")?;
+
+    // ジャンルはサーバーの一覧の順、その後に一覧にないジャンルを出現順に並べる
+    let mut genre_names: Vec<&str> = genres.iter().map(|genre| genre.name.as_str()).collect();
+    for expense in expenses {
+        if !genre_names.contains(&expense.genre.as_str()) {
+            genre_names.push(&expense.genre);
+        }
+    }
+    let months = report.month_keys();
+
+    let mut headers = vec!["ジャンル"];
+    headers.extend(months.iter().copied());
+    headers.push("合計");
+    write_headers(sheet, &headers, formats)?;
+    sheet.set_column_width(0, 16)?;
+
+    let total_col = months.len() as u16 + 1;
+    for (index, genre) in genre_names.iter().enumerate() {
+        let row = index as u32 + 1;
+        sheet.write_string(row, 0, *genre)?;
+        let summary = report.genres.iter().find(|summary| summary.genre == *genre);
+        for (col, month) in months.iter().enumerate() {
+            let total = summary.map_or(0, |summary| summary.month_total(month));
+            sheet.write_number_with_format(row, col as u16 + 1, total as f64, &formats.yen)?;
+        }
+        let row_total = summary.map_or(0, |summary| summary.stats.total);
+        sheet.write_number_with_format(row, total_col, row_total as f64, &formats.total_yen)?;
+    }
+
+    let row = genre_names.len() as u32 + 1;
+    sheet.write_string_with_format(row, 0, "合計", &formats.total_label)?;
+    for (col, summary) in report.months.iter().enumerate() {
+        let total = summary.stats.total as f64;
+        sheet.write_number_with_format(row, col as u16 + 1, total, &formats.total_yen)?;
+    }
+    let grand_total = report.overall.map_or(0, |stats| stats.total);
+    sheet.write_number_with_format(row, total_col, grand_total as f64, &formats.total_yen)?;
+    Ok(())
+}
+
+fn write_monthly_sheet(sheet: &mut Worksheet, report: &Report, formats: &Formats) -> Result<()> {
+    sheet.set_name("月別集計")?;
+    write_headers(sheet, &["月", "件数", "合計金額", "平均金額"], formats)?;
+    sheet.set_column_width(0, 10)?;
+    sheet.set_column_width(2, 14)?;
+    sheet.set_column_width(3, 14)?;
+
+    let mut row = 1;
+    for summary in &report.months {
+        let stats = &summary.stats;
+        sheet.write_string(row, 0, &summary.period)?;
+        sheet.write_number(row, 1, stats.count as u32)?;
+        sheet.write_number_with_format(row, 2, stats.total as f64, &formats.yen)?;
+        sheet.write_number_with_format(row, 3, stats.average.round(), &formats.yen)?;
+        row += 1;
+    }
+
+    let (count, total) = report
+        .overall
+        .map_or((0, 0), |stats| (stats.count as u32, stats.total));
+    sheet.write_string_with_format(row, 0, "合計", &formats.total_label)?;
+    sheet.write_number_with_format(row, 1, count, &formats.total_label)?;
+    sheet.write_number_with_format(row, 2, total as f64, &formats.total_yen)?;
+    Ok(())
+}
+
+fn write_headers(sheet: &mut Worksheet, headers: &[&str], formats: &Formats) -> Result<()> {
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, &formats.header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn excel_date(date: chrono::NaiveDate) -> Option<ExcelDateTime> {
+    ExcelDateTime::from_ymd(
+        u16::try_from(date.year()).ok()?,
+        date.month() as u8,
+        date.day() as u8,
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(id: i32, date: &str, genre: &str, amount: i32) -> ExpenseWithId {
+        ExpenseWithId {
+            id,
+            date: date.to_string(),
+            genre: genre.to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_workbook_has_three_sheets() {
+        let expenses = vec![
+            expense(1, "2025-01-15", "食費", 1000),
+            expense(2, "2025-02-01", "交通費", 500),
+            expense(3, "不明", "食費", 300),
+        ];
+        let mut workbook = build_workbook(&expenses, &[]).unwrap();
+        let names: Vec<String> = workbook
+            .worksheets()
+            .iter()
+            .map(|sheet| sheet.name())
+            .collect();
+        assert_eq!(names, vec!["支出一覧", "ジャンル別", "月別集計"]);
+
+        let bytes = workbook.save_to_buffer().unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
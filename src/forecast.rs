@@ -0,0 +1,422 @@
+//! 月末・年末の支出の見込み
+//!
+//! これまでの支出データから、ジャンルごとに今月末と今年末までの合計金額の見込みを求めます。
+//! 見込みは次の3つを組み合わせて求め、過去の同じ期間のばらつきから見込みの幅を付けます。
+//!
+//! * これまでのペース（1日あたりの使用額が期間の終わりまで続くとした額）
+//! * 前年以前の同じ時期の平均
+//! * 定期的な支出のテンプレートから分かっている今後の支出
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use shishutsukan::forecast::{forecast, ForecastOptions};
+//! use shishutsukan::ExpenseWithId;
+//!
+//! let expenses = vec![ExpenseWithId {
+//!     id: 1,
+//!     date: "2025-06-05".to_string(),
+//!     genre: "食費".to_string(),
+//!     amount: 10000,
+//! }];
+//! let as_of = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+//! let result = forecast(&expenses, &[], as_of, &ForecastOptions::default());
+//!
+//! let food = &result.month[0];
+//! assert_eq!(food.actual, 10000);
+//! // 10日で ¥10,000 のペースなら30日で ¥30,000
+//! assert_eq!(food.projected, 30000);
+//! ```
+
+use crate::models::ExpenseWithId;
+use crate::recurring::RecurringTemplate;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashSet};
+
+/// 見込みを求める期間
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Horizon {
+    /// 今月末まで
+    EndOfMonth,
+    /// 今年末まで
+    EndOfYear,
+}
+
+impl Horizon {
+    /// 日付を含む期間の初日と最終日
+    pub fn bounds(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::EndOfMonth => (
+                date.with_day(1).unwrap_or(date),
+                month_end(date.year(), date.month()),
+            ),
+            Self::EndOfYear => (
+                NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
+                NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date),
+            ),
+        }
+    }
+}
+
+/// 見込みの条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastOptions {
+    /// 月末の見込みの幅を求めるのに使う過去の月数（既定値: 12）
+    pub history_months: u32,
+    /// 前年以前の同じ時期と比べる年数（既定値: 3）
+    pub history_years: u32,
+    /// 見込みの幅の標準偏差に対する倍率（既定値: 1.28、約80%の範囲）
+    pub band_width: f64,
+}
+
+impl Default for ForecastOptions {
+    fn default() -> Self {
+        Self {
+            history_months: 12,
+            history_years: 3,
+            band_width: 1.28,
+        }
+    }
+}
+
+impl ForecastOptions {
+    /// 月末の見込みの幅を求めるのに使う過去の月数を指定
+    pub fn history_months(mut self, months: u32) -> Self {
+        self.history_months = months;
+        self
+    }
+
+    /// 前年以前の同じ時期と比べる年数を指定
+    pub fn history_years(mut self, years: u32) -> Self {
+        self.history_years = years;
+        self
+    }
+
+    /// 見込みの幅の標準偏差に対する倍率を指定
+    pub fn band_width(mut self, width: f64) -> Self {
+        self.band_width = width;
+        self
+    }
+}
+
+/// ジャンルごとの見込み
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenreForecast {
+    /// ジャンル
+    pub genre: String,
+    /// 見込みを求めた期間
+    pub horizon: Horizon,
+    /// 期間の初日
+    pub start: NaiveDate,
+    /// 期間の最終日
+    pub end: NaiveDate,
+    /// 基準日までの使用額
+    pub actual: i64,
+    /// 定期的な支出のテンプレートから分かっている残りの期間の支出
+    pub recurring: i64,
+    /// これまでのペースで求めた残りの期間の支出（定期的な支出を除く）
+    pub run_rate: i64,
+    /// 前年以前の同じ時期の平均（定期的な支出を除く。比べられる年がない場合は `None`）
+    pub seasonal: Option<i64>,
+    /// 期間の終わりの合計金額の見込み
+    pub projected: i64,
+    /// 見込みの下限
+    pub low: i64,
+    /// 見込みの上限
+    pub high: i64,
+}
+
+/// 見込みの結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    /// 基準日
+    pub as_of: NaiveDate,
+    /// 今月末の見込み（見込みの大きい順）
+    pub month: Vec<GenreForecast>,
+    /// 今年末の見込み（見込みの大きい順）
+    pub year: Vec<GenreForecast>,
+}
+
+impl Forecast {
+    /// すべてのジャンルの見込み・下限・上限の合計
+    pub fn total(&self, horizon: Horizon) -> (i64, i64, i64) {
+        let forecasts = match horizon {
+            Horizon::EndOfMonth => &self.month,
+            Horizon::EndOfYear => &self.year,
+        };
+        forecasts
+            .iter()
+            .fold((0, 0, 0), |(projected, low, high), f| {
+                (projected + f.projected, low + f.low, high + f.high)
+            })
+    }
+}
+
+/// 月末・年末の支出の見込みを求める
+///
+/// 定期的な支出のテンプレートの発生分と日付・ジャンル・金額が一致する支出データは、
+/// ペースや前年以前の平均の計算から除き、残りの期間の発生分をそのまま見込みに加えます。
+/// テンプレートの開始日より前の支出データも、同じ規則の日付・ジャンル・金額に一致すれば
+/// 定期的な支出として扱います（前年の同じ支出を二重に数えないため）。
+/// 見込みの幅は、過去の同じ残りの期間（月末は過去の各月、年末は前年以前の各年）の
+/// 使用額の標準偏差から求めます。比べられる期間が2つ未満の場合は見込みの±50%とします。
+/// 日付を解釈できない支出データは無視します。
+///
+/// # Arguments
+///
+/// * `expenses` - 支出データの一覧
+/// * `templates` - 定期的な支出のテンプレートの一覧
+/// * `as_of` - 基準日（この日までの支出を実績とする）
+/// * `options` - 見込みの条件
+pub fn forecast(
+    expenses: &[ExpenseWithId],
+    templates: &[RecurringTemplate],
+    as_of: NaiveDate,
+    options: &ForecastOptions,
+) -> Forecast {
+    let dated: Vec<(NaiveDate, &ExpenseWithId)> = expenses
+        .iter()
+        .filter_map(|expense| Some((expense.naive_date()?, expense)))
+        .collect();
+    let earliest = dated.iter().map(|(date, _)| *date).min();
+
+    let known: HashSet<(NaiveDate, &str, i32)> = templates
+        .iter()
+        .flat_map(|template| {
+            let from = earliest.map_or(template.starts_on, |date| date.min(template.starts_on));
+            let until = template.ends_on.map_or(as_of, |end| end.min(as_of));
+            template
+                .schedule
+                .occurrences(from, until)
+                .into_iter()
+                .map(move |date| (date, template.genre.as_str(), template.amount))
+        })
+        .collect();
+    let genres: BTreeSet<&str> = dated
+        .iter()
+        .map(|(_, expense)| expense.genre.as_str())
+        .chain(templates.iter().map(|template| template.genre.as_str()))
+        .collect();
+
+    let mut result = Forecast {
+        as_of,
+        month: Vec::new(),
+        year: Vec::new(),
+    };
+    for genre in genres {
+        let rows: Vec<(NaiveDate, i64, bool)> = dated
+            .iter()
+            .filter(|(_, expense)| expense.genre == genre)
+            .map(|(date, expense)| {
+                let recurring = known.contains(&(*date, genre, expense.amount));
+                (*date, i64::from(expense.amount), recurring)
+            })
+            .collect();
+        let history = History {
+            rows: &rows,
+            earliest,
+        };
+        let genre_templates: Vec<&RecurringTemplate> = templates
+            .iter()
+            .filter(|template| template.genre == genre)
+            .collect();
+
+        for horizon in [Horizon::EndOfMonth, Horizon::EndOfYear] {
+            let forecast = project(genre, horizon, &history, &genre_templates, as_of, options);
+            match horizon {
+                Horizon::EndOfMonth => result.month.push(forecast),
+                Horizon::EndOfYear => result.year.push(forecast),
+            }
+        }
+    }
+    result.month.sort_by_key(|f| Reverse(f.projected));
+    result.year.sort_by_key(|f| Reverse(f.projected));
+    result
+}
+
+/// ジャンルの支出データ（日付・金額・定期的な支出かどうか）
+struct History<'a> {
+    rows: &'a [(NaiveDate, i64, bool)],
+    earliest: Option<NaiveDate>,
+}
+
+impl History<'_> {
+    fn spent(&self, start: NaiveDate, end: NaiveDate, variable_only: bool) -> i64 {
+        self.rows
+            .iter()
+            .filter(|(date, _, recurring)| {
+                (start..=end).contains(date) && !(variable_only && *recurring)
+            })
+            .map(|(_, amount, _)| amount)
+            .sum()
+    }
+
+    /// 期間の初日より前から支出データがあるかどうか
+    fn covers(&self, start: NaiveDate) -> bool {
+        self.earliest.is_some_and(|earliest| earliest <= start)
+    }
+}
+
+fn project(
+    genre: &str,
+    horizon: Horizon,
+    history: &History<'_>,
+    templates: &[&RecurringTemplate],
+    as_of: NaiveDate,
+    options: &ForecastOptions,
+) -> GenreForecast {
+    let (start, end) = horizon.bounds(as_of);
+    let actual = history.spent(start, as_of, false);
+    let remaining_start = as_of + Duration::days(1);
+
+    let recurring: i64 = templates
+        .iter()
+        .map(|template| {
+            let from = remaining_start.max(template.starts_on);
+            let until = template.ends_on.map_or(end, |ends_on| ends_on.min(end));
+            template.schedule.occurrences(from, until).len() as i64 * i64::from(template.amount)
+        })
+        .sum();
+
+    let elapsed = (as_of - start).num_days() + 1;
+    let remaining = (end - as_of).num_days();
+    let run_rate = (history.spent(start, as_of, true) as f64 * remaining as f64 / elapsed as f64)
+        .round() as i64;
+
+    // 前年以前の同じ残りの期間
+    let seasonal_samples: Vec<f64> = (1..=options.history_years)
+        .filter_map(|years| {
+            let (window_start, window_end) = (
+                shift_years(remaining_start, years)?,
+                shift_years(end, years)?,
+            );
+            history
+                .covers(shift_years(start, years)?)
+                .then(|| history.spent(window_start, window_end, true) as f64)
+        })
+        .collect();
+    let seasonal = (!seasonal_samples.is_empty()).then(|| mean(&seasonal_samples).round() as i64);
+
+    // 見込みの幅に使う過去の同じ残りの期間
+    let band_samples = match horizon {
+        Horizon::EndOfYear => seasonal_samples,
+        Horizon::EndOfMonth => (1..=options.history_months)
+            .filter_map(|months| {
+                let month_start = shift_months(start, months)?;
+                let day = remaining_start
+                    .day()
+                    .min(month_end(month_start.year(), month_start.month()).day());
+                let window_start = month_start.with_day(day)?;
+                let window_end = month_end(month_start.year(), month_start.month());
+                (remaining > 0 && history.covers(month_start))
+                    .then(|| history.spent(window_start, window_end, true) as f64)
+            })
+            .collect(),
+    };
+
+    let variable = match seasonal {
+        Some(seasonal) if remaining > 0 => (run_rate + seasonal) / 2,
+        _ => run_rate,
+    };
+    let projected = actual + recurring + variable;
+    let spread = if band_samples.len() >= 2 {
+        (std_dev(&band_samples) * options.band_width).round() as i64
+    } else {
+        variable / 2
+    };
+
+    GenreForecast {
+        genre: genre.to_string(),
+        horizon,
+        start,
+        end,
+        actual,
+        recurring,
+        run_rate,
+        seasonal,
+        projected,
+        low: (projected - spread).max(actual + recurring),
+        high: projected + spread,
+    }
+}
+
+fn month_end(year: i32, month: u32) -> NaiveDate {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|date| date.pred_opt())
+        .unwrap_or_default()
+}
+
+/// 月の初日を指定した月数だけ前にずらす
+fn shift_months(month_start: NaiveDate, months: u32) -> Option<NaiveDate> {
+    month_start.checked_sub_months(chrono::Months::new(months))
+}
+
+/// 日付を指定した年数だけ前にずらす（2月29日は2月28日）
+fn shift_years(date: NaiveDate, years: u32) -> Option<NaiveDate> {
+    date.checked_sub_months(chrono::Months::new(years * 12))
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// 標本標準偏差
+fn std_dev(values: &[f64]) -> f64 {
+    let mean = mean(values);
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_years_clamps_leap_day() {
+        let leap_day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(
+            shift_years(leap_day, 1),
+            NaiveDate::from_ymd_opt(2023, 2, 28)
+        );
+        assert_eq!(month_end(2024, 2), leap_day);
+    }
+
+    #[test]
+    fn test_recurring_before_template_start_is_not_double_counted() {
+        use crate::recurring::Schedule;
+
+        // 2024年1月から毎月1日に支払っているが、テンプレートは2025年3月から登録した
+        let expenses: Vec<ExpenseWithId> = (0..18)
+            .map(|index| ExpenseWithId {
+                id: index + 1,
+                date: format!("{}-{:02}-01", 2024 + index / 12, index % 12 + 1),
+                genre: "サブスク".to_string(),
+                amount: 1000,
+            })
+            .collect();
+        let template = RecurringTemplate::new(
+            "動画配信",
+            "サブスク",
+            1000,
+            Schedule::MonthlyOnDay(1),
+            NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+        );
+        let as_of = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+        let result = forecast(&expenses, &[template], as_of, &ForecastOptions::default());
+
+        let year = &result.year[0];
+        assert_eq!((year.actual, year.recurring), (6000, 6000));
+        // 前年の7月〜12月の支払いは定期的な支出なので、前年の平均には含めない
+        assert_eq!((year.run_rate, year.seasonal), (0, Some(0)));
+        assert_eq!(year.projected, 12000);
+    }
+}
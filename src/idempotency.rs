@@ -0,0 +1,264 @@
+use crate::error::Result;
+use crate::models::{ApiMessage, Expense, ExpenseWithId};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 冪等追加の台帳エントリ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LedgerEntry {
+    /// クライアントが生成した冪等キー
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub key: Option<String>,
+    /// 追加しようとした支出データ
+    pub expense: Expense,
+    /// 記録日時（UNIX秒）
+    pub recorded_at: u64,
+    /// サーバーが追加を確認したかどうか
+    ///
+    /// 送信前に `false` で記録し、成功レスポンスを受け取った時点で `true` にする。
+    /// タイムアウトなどで `false` のまま残ったエントリは、次回の追加時に
+    /// サーバー上の支出データと照合される。
+    pub committed: bool,
+}
+
+/// 冪等追加のためのローカル台帳
+///
+/// [`ShishutsukanClient::add_expense_idempotent`](crate::ShishutsukanClient::add_expense_idempotent)
+/// が追加を試みた支出データを記録します。ファイルを指定した場合は
+/// 更新のたびにJSONとして保存されます。
+#[derive(Debug, Clone, Default)]
+pub struct ExpenseLedger {
+    path: Option<PathBuf>,
+    entries: Vec<LedgerEntry>,
+}
+
+impl ExpenseLedger {
+    /// メモリ上だけに保持する台帳を作成
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// ファイルに保存される台帳を開く
+    ///
+    /// ファイルが存在しない場合は空の台帳を作成し、最初の更新時にファイルを作成します。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 台帳ファイルのパス
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            entries,
+        })
+    }
+
+    /// 台帳のエントリ一覧
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// 指定した期間より前に記録された確認済みエントリを削除
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - 保持する期間
+    pub fn prune(&mut self, max_age: Duration) -> Result<()> {
+        let threshold = unix_now().saturating_sub(max_age.as_secs());
+        self.entries
+            .retain(|entry| !entry.committed || entry.recorded_at >= threshold);
+        self.save()
+    }
+
+    /// 重複を判定する
+    ///
+    /// キー付きの追加はキーだけで判定し、キーなしの追加は `window` 内に
+    /// 同じ内容を追加した記録があり、かつサーバーに同じ内容の行がある場合に
+    /// 重複とみなす。時間窓の判定には先頭 `considered` 件のエントリだけを使う。
+    pub(crate) fn find_duplicate(
+        &self,
+        expense: &Expense,
+        key: Option<&str>,
+        existing: &[ExpenseWithId],
+        window: Duration,
+        considered: usize,
+    ) -> Option<(DuplicateReason, Option<ExpenseWithId>)> {
+        let on_server = existing.iter().find(|row| row.matches(expense)).cloned();
+
+        if let Some(key) = key {
+            let entry = self.find_key(key)?;
+            // 未確認のエントリはサーバーに行が残っていれば追加済みとみなす
+            return (entry.committed || on_server.is_some())
+                .then_some((DuplicateReason::KeyMatched, on_server));
+        }
+
+        let threshold = unix_now().saturating_sub(window.as_secs());
+        let recently_added = self.entries[..considered.min(self.entries.len())]
+            .iter()
+            .any(|entry| {
+                entry.key.is_none() && entry.expense == *expense && entry.recorded_at >= threshold
+            });
+
+        match on_server {
+            Some(row) if recently_added => Some((DuplicateReason::RecentlyAdded, Some(row))),
+            _ => None,
+        }
+    }
+
+    /// 送信前に未確認のエントリを記録し、そのインデックスを返す
+    pub(crate) fn record_pending(&mut self, expense: &Expense, key: Option<&str>) -> Result<usize> {
+        let recorded_at = unix_now();
+        let index = match key.and_then(|key| self.position_of_key(key)) {
+            Some(index) => {
+                let entry = &mut self.entries[index];
+                entry.expense = expense.clone();
+                entry.recorded_at = recorded_at;
+                entry.committed = false;
+                index
+            }
+            None => {
+                self.entries.push(LedgerEntry {
+                    key: key.map(str::to_string),
+                    expense: expense.clone(),
+                    recorded_at,
+                    committed: false,
+                });
+                self.entries.len() - 1
+            }
+        };
+        self.save()?;
+        Ok(index)
+    }
+
+    /// エントリを確認済みにする
+    pub(crate) fn commit(&mut self, index: usize) -> Result<()> {
+        self.entries[index].committed = true;
+        self.save()
+    }
+
+    /// キーに対応するエントリを確認済みにする
+    pub(crate) fn commit_key(&mut self, key: &str) -> Result<()> {
+        match self.position_of_key(key) {
+            Some(index) if !self.entries[index].committed => self.commit(index),
+            _ => Ok(()),
+        }
+    }
+
+    fn find_key(&self, key: &str) -> Option<&LedgerEntry> {
+        self.position_of_key(key).map(|index| &self.entries[index])
+    }
+
+    fn position_of_key(&self, key: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.key.as_deref() == Some(key))
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        // 書き込み途中で中断しても台帳が壊れないよう、一時ファイル経由で置き換える
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&self.entries)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// 重複と判定した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateReason {
+    /// 同じ冪等キーで追加済み
+    KeyMatched,
+    /// 時間窓内に同じ内容を追加済みで、サーバーにも同じ行がある
+    RecentlyAdded,
+}
+
+/// 冪等追加の結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotentAddOutcome {
+    /// 追加した
+    Added(ApiMessage),
+    /// 重複のため追加しなかった
+    Skipped {
+        /// 重複と判定した理由
+        reason: DuplicateReason,
+        /// サーバー上の同じ内容の支出データ
+        existing: Option<ExpenseWithId>,
+    },
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense() -> Expense {
+        Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000)
+    }
+
+    fn row(id: i32) -> ExpenseWithId {
+        ExpenseWithId {
+            id,
+            date: "2025-01-15".to_string(),
+            genre: "食費".to_string(),
+            amount: 1000,
+        }
+    }
+
+    #[test]
+    fn test_pending_key_without_server_row_is_retried() {
+        let mut ledger = ExpenseLedger::in_memory();
+        ledger.record_pending(&expense(), Some("k1")).unwrap();
+
+        let window = Duration::from_secs(600);
+        assert_eq!(
+            ledger.find_duplicate(&expense(), Some("k1"), &[], window, 1),
+            None
+        );
+        assert_eq!(
+            ledger.find_duplicate(&expense(), Some("k1"), &[row(3)], window, 1),
+            Some((DuplicateReason::KeyMatched, Some(row(3))))
+        );
+    }
+
+    #[test]
+    fn test_unkeyed_duplicate_requires_recent_record_and_server_row() {
+        let mut ledger = ExpenseLedger::in_memory();
+        let window = Duration::from_secs(600);
+
+        // 同じ内容の行がサーバーにあっても、台帳に記録がなければ別の支出とみなす
+        assert_eq!(
+            ledger.find_duplicate(&expense(), None, &[row(1)], window, 0),
+            None
+        );
+
+        let index = ledger.record_pending(&expense(), None).unwrap();
+        ledger.commit(index).unwrap();
+        assert_eq!(
+            ledger.find_duplicate(&expense(), None, &[row(1)], window, 1),
+            Some((DuplicateReason::RecentlyAdded, Some(row(1))))
+        );
+        // 同じ呼び出し内で記録したエントリは考慮しない
+        assert_eq!(
+            ledger.find_duplicate(&expense(), None, &[row(1)], window, 0),
+            None
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! 外部データからの支出データのインポート
+//!
+//! 各形式のインポーターで共通して使う、日本語の日付・金額表記の解釈を提供します。
+
+pub mod csv;
+pub mod household;
+pub mod ofx;
+pub mod qif;
+pub mod statement;
+
+use chrono::NaiveDate;
+
+/// 全角の数字・記号を半角に変換する
+///
+/// 長音符（`ー`）はカタカナの一部なので変換しません。
+pub(crate) fn normalize_width(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+                char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+            }
+            '，' => ',',
+            '．' => '.',
+            '／' => '/',
+            '－' | '−' => '-',
+            '　' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// 日本語の日付表記を解釈する
+///
+/// 次の形式に対応します（全角数字も可）。
+///
+/// * `2025-01-15` / `2025/1/15` / `2025.1.15`
+/// * `2025年1月15日`
+/// * `20250115`
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use shishutsukan::import::parse_japanese_date;
+///
+/// let expected = NaiveDate::from_ymd_opt(2025, 1, 15);
+/// assert_eq!(parse_japanese_date("2025年1月15日"), expected);
+/// assert_eq!(parse_japanese_date("２０２５/０１/１５"), expected);
+/// assert_eq!(parse_japanese_date("15/01/2025"), None);
+/// ```
+pub fn parse_japanese_date(input: &str) -> Option<NaiveDate> {
+    let normalized = normalize_width(input.trim());
+
+    if normalized.len() == 8 && normalized.bytes().all(|b| b.is_ascii_digit()) {
+        return NaiveDate::parse_from_str(&normalized, "%Y%m%d").ok();
+    }
+
+    let parts: Vec<&str> = normalized
+        .trim_end_matches('日')
+        .split(['-', '/', '.', '年', '月'])
+        .collect();
+    let [year, month, day] = parts.as_slice() else {
+        return None;
+    };
+    if year.len() != 4 {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(
+        year.trim().parse().ok()?,
+        month.trim().parse().ok()?,
+        day.trim().parse().ok()?,
+    )
+}
+
+/// 日本語の金額表記を解釈する
+///
+/// `1,234円`・`¥1,234`・`￥１，２３４`・`-1234` などに対応します。
+/// 小数点以下がある場合や `i32` に収まらない場合は `None` を返します。
+///
+/// # Examples
+///
+/// ```
+/// use shishutsukan::import::parse_japanese_amount;
+///
+/// assert_eq!(parse_japanese_amount("1,234円"), Some(1234));
+/// assert_eq!(parse_japanese_amount("￥１，２３４"), Some(1234));
+/// assert_eq!(parse_japanese_amount("-500"), Some(-500));
+/// assert_eq!(parse_japanese_amount("12.5"), None);
+/// ```
+pub fn parse_japanese_amount(input: &str) -> Option<i32> {
+    let normalized = normalize_width(input.trim());
+    let digits: String = normalized
+        .trim_start_matches(['¥', '￥', '\\'])
+        .trim_end_matches('円')
+        .chars()
+        .filter(|c| !matches!(c, ',' | ' '))
+        .collect();
+    let digits = digits.strip_prefix(['¥', '￥', '\\']).unwrap_or(&digits);
+    digits.parse().ok()
+}
+
+/// UTF-8またはShift_JIS（Windows-31J）のテキストを文字列にする
+///
+/// UTF-8として正しくない場合はShift_JISとして変換します。先頭のBOMは取り除きます。
+///
+/// # Examples
+///
+/// ```
+/// use shishutsukan::import::decode_text;
+///
+/// // 「食費」のShift_JIS表現
+/// assert_eq!(decode_text(b"\x90\x48\x94\xef"), "食費");
+/// assert_eq!(decode_text("\u{FEFF}食費".as_bytes()), "食費");
+/// ```
+pub fn decode_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => strip_bom(text).to_string(),
+        Err(_) => encoding_rs::SHIFT_JIS.decode(bytes).0.into_owned(),
+    }
+}
+
+/// CSVなどのテキストを読み込む前に先頭のBOMを取り除く
+pub(crate) fn strip_bom(input: &str) -> &str {
+    input.strip_prefix('\u{FEFF}').unwrap_or(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_width() {
+        assert_eq!(normalize_width("ＡＢＣ１２３／４．５"), "ABC123/4.5");
+        assert_eq!(normalize_width("－１００　−２"), "-100 -2");
+        // 長音符はそのまま残す
+        assert_eq!(normalize_width("スーパー　コーヒー"), "スーパー コーヒー");
+        assert_eq!(parse_japanese_amount("ー500"), None);
+        assert_eq!(parse_japanese_amount("－５００"), Some(-500));
+    }
+}
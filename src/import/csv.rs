@@ -0,0 +1,532 @@
+//! CSV形式からのインポート
+//!
+//! 取り込みは「解析 → ジャンルの確認 → 登録」の順に行います。登録前に
+//! [`ImportReport`] で行ごとの結果を確認できます。
+//!
+//! ```
+//! use shishutsukan::import::csv::{check_genres, parse, ColumnMapping};
+//! use shishutsukan::GenreWithId;
+//!
+//! let input = "利用日,費目,金額\n2025年1月15日,食費,\"1,234円\"\n2025年1月16日,交際費,500円\n";
+//! let mapping = ColumnMapping::new("利用日", "金額").genre("費目");
+//! let rows = parse(input, &mapping).unwrap();
+//!
+//! let genres = vec![GenreWithId {
+//!     id: 1,
+//!     name: "食費".to_string(),
+//!     created_at: "2025-01-01T00:00:00".to_string(),
+//! }];
+//! let report = check_genres(rows, &genres, false);
+//! assert_eq!(report.valid_rows().count(), 1);
+//! assert_eq!(report.invalid_rows().count(), 1);
+//! ```
+
+use crate::client::ShishutsukanClient;
+use crate::error::{Result, ShishutsukanError};
+use crate::import::{parse_japanese_amount, parse_japanese_date, strip_bom};
+use crate::models::{Expense, Genre, GenreWithId, DATE_FORMAT};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// 列の指定
+///
+/// 設定ファイルでは、数値は列番号（0始まり）、文字列はヘッダー行の列名として扱います。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnRef {
+    /// 列番号（0始まり）
+    Index(usize),
+    /// ヘッダー行の列名
+    Name(String),
+}
+
+impl From<usize> for ColumnRef {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for ColumnRef {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl fmt::Display for ColumnRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "{}列目", index + 1),
+            Self::Name(name) => write!(f, "「{}」列", name),
+        }
+    }
+}
+
+/// CSVの列と支出データの項目の対応付け
+///
+/// JSONなどの設定ファイルから読み込むこともできます。
+///
+/// ```json
+/// { "date": "利用日", "amount": 3, "genre": "費目", "default_genre": "雑費" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    /// 日付の列
+    pub date: ColumnRef,
+    /// 金額の列
+    pub amount: ColumnRef,
+    /// ジャンルの列（`None` の場合はすべての行で `default_genre` を使う）
+    #[serde(default)]
+    pub genre: Option<ColumnRef>,
+    /// ジャンルが空の行に使うジャンル
+    #[serde(default)]
+    pub default_genre: Option<String>,
+    /// 区切り文字
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// 1行目がヘッダー行かどうか
+    #[serde(default = "default_has_header")]
+    pub has_header: bool,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_has_header() -> bool {
+    true
+}
+
+impl ColumnMapping {
+    /// 日付と金額の列を指定して対応付けを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - 日付の列（列名または列番号）
+    /// * `amount` - 金額の列（列名または列番号）
+    pub fn new(date: impl Into<ColumnRef>, amount: impl Into<ColumnRef>) -> Self {
+        Self {
+            date: date.into(),
+            amount: amount.into(),
+            genre: None,
+            default_genre: None,
+            delimiter: default_delimiter(),
+            has_header: default_has_header(),
+        }
+    }
+
+    /// ジャンルの列を指定
+    pub fn genre(mut self, column: impl Into<ColumnRef>) -> Self {
+        self.genre = Some(column.into());
+        self
+    }
+
+    /// ジャンルが空の行に使うジャンルを指定
+    pub fn default_genre(mut self, genre: impl Into<String>) -> Self {
+        self.default_genre = Some(genre.into());
+        self
+    }
+
+    /// 区切り文字を指定
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// 1行目もデータとして読み込む
+    pub fn without_header(mut self) -> Self {
+        self.has_header = false;
+        self
+    }
+}
+
+/// 行ごとの問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowIssue {
+    /// 列が足りない
+    MissingColumn(String),
+    /// 日付を解釈できない
+    InvalidDate(String),
+    /// 金額を解釈できない
+    InvalidAmount(String),
+    /// ジャンルが空で、既定のジャンルも指定されていない
+    MissingGenre,
+    /// ジャンルがサーバー上に存在しない
+    UnknownGenre(String),
+}
+
+impl fmt::Display for RowIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingColumn(column) => write!(f, "{}がありません", column),
+            Self::InvalidDate(value) => write!(f, "日付を解釈できません: {}", value),
+            Self::InvalidAmount(value) => write!(f, "金額を解釈できません: {}", value),
+            Self::MissingGenre => write!(f, "ジャンルが空です"),
+            Self::UnknownGenre(genre) => write!(f, "ジャンルが存在しません: {}", genre),
+        }
+    }
+}
+
+/// 解析した1行
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// CSV上の行番号（1始まり。複数行にまたがる場合は開始行）
+    pub line: usize,
+    /// 元のフィールド
+    pub fields: Vec<String>,
+    /// 変換した支出データ（問題がない場合）
+    pub expense: Option<Expense>,
+    /// 見つかった問題
+    pub issues: Vec<RowIssue>,
+}
+
+impl ImportRow {
+    /// 登録できる行かどうか
+    pub fn is_valid(&self) -> bool {
+        self.expense.is_some() && self.issues.is_empty()
+    }
+}
+
+/// 登録前の確認結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportReport {
+    /// 行ごとの結果
+    pub rows: Vec<ImportRow>,
+    /// 登録時に作成するジャンル
+    pub new_genres: Vec<String>,
+}
+
+impl ImportReport {
+    /// 登録できる行
+    pub fn valid_rows(&self) -> impl Iterator<Item = &ImportRow> {
+        self.rows.iter().filter(|row| row.is_valid())
+    }
+
+    /// 問題のある行
+    pub fn invalid_rows(&self) -> impl Iterator<Item = &ImportRow> {
+        self.rows.iter().filter(|row| !row.is_valid())
+    }
+
+    /// すべての行が登録できるかどうか
+    pub fn is_clean(&self) -> bool {
+        self.rows.iter().all(ImportRow::is_valid)
+    }
+}
+
+/// 登録の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitReport {
+    /// 作成したジャンル
+    pub created_genres: Vec<String>,
+    /// 登録した行の行番号
+    pub added: Vec<usize>,
+    /// 問題があるため登録しなかった行の行番号
+    pub skipped: Vec<usize>,
+    /// 登録に失敗した行の行番号とエラーメッセージ
+    pub failed: Vec<(usize, String)>,
+}
+
+/// CSVを解析して行ごとの結果を返す
+///
+/// 日付は `2025年1月15日` や `2025/1/15`、金額は `1,234円` や `¥1,234` などの
+/// 表記を解釈します。空行は読み飛ばします。
+///
+/// # Arguments
+///
+/// * `input` - CSVの内容
+/// * `mapping` - 列の対応付け
+///
+/// # Errors
+///
+/// 引用符が閉じられていない場合や、指定した列名がヘッダー行に見つからない場合は
+/// [`ShishutsukanError::ParseError`] を返します。
+pub fn parse(input: &str, mapping: &ColumnMapping) -> Result<Vec<ImportRow>> {
+    let mut records = read_records(input, mapping.delimiter)?.into_iter();
+    let headers = if mapping.has_header {
+        records.next().map(|(_, fields)| fields).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let date = resolve(&mapping.date, &headers)?;
+    let amount = resolve(&mapping.amount, &headers)?;
+    let genre = mapping
+        .genre
+        .as_ref()
+        .map(|column| resolve(column, &headers))
+        .transpose()?;
+
+    Ok(records
+        .map(|(line, fields)| {
+            let mut issues = Vec::new();
+            let field = |index: usize, column: &ColumnRef, issues: &mut Vec<RowIssue>| {
+                let value = fields.get(index).map(|value| value.trim());
+                if value.is_none() {
+                    issues.push(RowIssue::MissingColumn(column.to_string()));
+                }
+                value
+            };
+
+            let date = field(date, &mapping.date, &mut issues).and_then(|value| {
+                let parsed = parse_japanese_date(value);
+                if parsed.is_none() {
+                    issues.push(RowIssue::InvalidDate(value.to_string()));
+                }
+                parsed
+            });
+            let amount = field(amount, &mapping.amount, &mut issues).and_then(|value| {
+                let parsed = parse_japanese_amount(value);
+                if parsed.is_none() {
+                    issues.push(RowIssue::InvalidAmount(value.to_string()));
+                }
+                parsed
+            });
+            let genre = match (genre, &mapping.genre) {
+                (Some(index), Some(column)) => field(index, column, &mut issues),
+                _ => Some(""),
+            }
+            .map(|value| match value {
+                "" => mapping.default_genre.as_deref(),
+                value => Some(value),
+            });
+            let genre = match genre {
+                Some(None) => {
+                    issues.push(RowIssue::MissingGenre);
+                    None
+                }
+                genre => genre.flatten(),
+            };
+
+            let expense = match (date, genre, amount) {
+                (Some(date), Some(genre), Some(amount)) if issues.is_empty() => Some(Expense::new(
+                    date.format(DATE_FORMAT).to_string(),
+                    genre.to_string(),
+                    amount,
+                )),
+                _ => None,
+            };
+            ImportRow {
+                line,
+                fields: fields.clone(),
+                expense,
+                issues,
+            }
+        })
+        .collect())
+}
+
+/// 行のジャンルをサーバー上のジャンルと照合する
+///
+/// `auto_create` が `true` の場合、存在しないジャンルは
+/// [`ImportReport::new_genres`] に加え、登録時に作成します。
+/// `false` の場合は [`RowIssue::UnknownGenre`] として行に記録します。
+///
+/// # Arguments
+///
+/// * `rows` - [`parse`] の結果
+/// * `genres` - サーバー上のジャンル一覧
+/// * `auto_create` - 存在しないジャンルを作成するかどうか
+pub fn check_genres(
+    mut rows: Vec<ImportRow>,
+    genres: &[GenreWithId],
+    auto_create: bool,
+) -> ImportReport {
+    let known: HashSet<&str> = genres.iter().map(|genre| genre.name.as_str()).collect();
+    let mut new_genres: Vec<String> = Vec::new();
+
+    for row in &mut rows {
+        let Some(expense) = &row.expense else {
+            continue;
+        };
+        if known.contains(expense.genre.as_str()) {
+            continue;
+        }
+        if auto_create {
+            if !new_genres.contains(&expense.genre) {
+                new_genres.push(expense.genre.clone());
+            }
+        } else {
+            row.issues
+                .push(RowIssue::UnknownGenre(expense.genre.clone()));
+        }
+    }
+
+    ImportReport { rows, new_genres }
+}
+
+/// CSVを解析し、サーバー上のジャンルと照合した確認結果を返す
+///
+/// サーバーへの書き込みは行いません。結果を確認してから [`commit`] で登録します。
+///
+/// # Arguments
+///
+/// * `client` - APIクライアント
+/// * `input` - CSVの内容
+/// * `mapping` - 列の対応付け
+/// * `auto_create` - 存在しないジャンルを登録時に作成するかどうか
+///
+/// # Examples
+///
+/// ```no_run
+/// # use shishutsukan::ShishutsukanClient;
+/// # use shishutsukan::import::csv::{self, ColumnMapping};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = ShishutsukanClient::new("http://localhost:8000");
+/// let input = std::fs::read_to_string("receipts.csv")?;
+/// let mapping = ColumnMapping::new("日付", "金額").genre("費目");
+///
+/// let report = csv::preview(&client, &input, &mapping, true).await?;
+/// for row in report.invalid_rows() {
+///     for issue in &row.issues {
+///         println!("{}行目: {}", row.line, issue);
+///     }
+/// }
+///
+/// let result = csv::commit(&client, &report).await?;
+/// println!("{} 件を登録しました", result.added.len());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn preview(
+    client: &ShishutsukanClient,
+    input: &str,
+    mapping: &ColumnMapping,
+    auto_create: bool,
+) -> Result<ImportReport> {
+    let rows = parse(input, mapping)?;
+    let genres = client.get_genres().await?;
+    Ok(check_genres(rows, &genres, auto_create))
+}
+
+/// 確認結果のうち、問題のない行を登録する
+///
+/// 先に [`ImportReport::new_genres`] のジャンルを作成し、その後で支出データを
+/// 1件ずつ `add_expense` で登録します。ジャンルの作成に失敗した場合は、
+/// 支出データを登録せずにエラーを返します。
+///
+/// # Arguments
+///
+/// * `client` - APIクライアント
+/// * `report` - [`preview`] または [`check_genres`] の結果
+pub async fn commit(client: &ShishutsukanClient, report: &ImportReport) -> Result<CommitReport> {
+    let mut result = CommitReport::default();
+
+    for name in &report.new_genres {
+        client.add_genre(&Genre::new(name.clone())).await?;
+        result.created_genres.push(name.clone());
+    }
+
+    for row in &report.rows {
+        let Some(expense) = row.expense.as_ref().filter(|_| row.is_valid()) else {
+            result.skipped.push(row.line);
+            continue;
+        };
+        match client.add_expense(expense).await {
+            Ok(_) => result.added.push(row.line),
+            Err(e) => result.failed.push((row.line, e.to_string())),
+        }
+    }
+
+    Ok(result)
+}
+
+/// 列の指定を列番号に変換する
+fn resolve(column: &ColumnRef, headers: &[String]) -> Result<usize> {
+    match column {
+        ColumnRef::Index(index) => Ok(*index),
+        ColumnRef::Name(name) => headers
+            .iter()
+            .position(|header| header.trim() == name)
+            .ok_or_else(|| {
+                ShishutsukanError::ParseError(format!("ヘッダー行に「{}」列がありません", name))
+            }),
+    }
+}
+
+/// CSVをレコードに分割する
+///
+/// 引用符で囲まれたフィールド内の区切り文字・改行と、`""` による引用符の
+/// エスケープに対応します。空行は読み飛ばし、各レコードの開始行番号（1始まり）を返します。
+pub(crate) fn read_records(input: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = strip_bom(input).chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                fields.push(std::mem::take(&mut field));
+                let record = std::mem::take(&mut fields);
+                // 空行（空のフィールドが1つだけのレコード）は読み飛ばす
+                if record != [String::new()] {
+                    records.push((start_line, record));
+                }
+                line += 1;
+                start_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(ShishutsukanError::ParseError(format!(
+            "{}行目の引用符が閉じられていません",
+            start_line
+        )));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_records_handles_quotes_and_newlines() {
+        let input = "\u{FEFF}a,b\r\n\"x,\"\"y\"\"\",\"1\n2\"\n\nlast,";
+        let records = read_records(input, ',').unwrap();
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (2, vec!["x,\"y\"".to_string(), "1\n2".to_string()]),
+                (5, vec!["last".to_string(), String::new()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_records_rejects_unterminated_quote() {
+        let error = read_records("a,b\n\"open,c\n", ',').unwrap_err();
+        assert!(matches!(error, ShishutsukanError::ParseError(_)));
+    }
+}
@@ -0,0 +1,509 @@
+//! 家計簿アプリ（Zaim・マネーフォワード ME）のCSVからのインポート
+//!
+//! 各アプリが書き出すCSV（UTF-8またはShift_JIS）から支出の行だけを読み込み、
+//! アプリのカテゴリとジャンルの対応表（[`GenreMappingTable`]）を通して支出データに変換します。
+//! 収入・振替・集計対象外の行は読み飛ばして件数だけを数えます。
+//!
+//! ```
+//! use shishutsukan::import::household::{parse, GenreMappingTable, HouseholdApp};
+//! use shishutsukan::GenreWithId;
+//!
+//! let input = "計算対象,日付,内容,金額（円）,保有金融機関,大項目,中項目,メモ,振替,ID\n\
+//!              1,2025/01/15,スーパー,-1280,カード,食費,食料品,,0,mf-1\n\
+//!              1,2025/01/25,給与,250000,銀行,収入,給与,,0,mf-2\n";
+//! let import = parse(input.as_bytes(), HouseholdApp::MoneyForward).unwrap();
+//! assert_eq!(import.transactions.len(), 1);
+//! assert_eq!(import.skipped_income, 1);
+//!
+//! let genres = vec![GenreWithId {
+//!     id: 1,
+//!     name: "食費".to_string(),
+//!     created_at: "2025-01-01T00:00:00".to_string(),
+//! }];
+//! let table = GenreMappingTable::build(&import.transactions, &genres);
+//! assert_eq!(table.genre_for("食費", "食料品"), Some("食費"));
+//! assert!(table.missing_genres().is_empty());
+//! ```
+
+use crate::client::ShishutsukanClient;
+use crate::error::{Result, ShishutsukanError};
+use crate::import::csv::read_records;
+use crate::import::{decode_text, parse_japanese_amount, parse_japanese_date};
+use crate::models::{Expense, GenreWithId, DATE_FORMAT};
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+
+/// CSVを書き出した家計簿アプリ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HouseholdApp {
+    /// Zaim
+    Zaim,
+    /// マネーフォワード ME
+    MoneyForward,
+}
+
+/// 家計簿アプリの支出1件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HouseholdTransaction {
+    /// 重複判定に使うID（マネーフォワード ME ではアプリのID、Zaimでは内容から生成）
+    pub id: String,
+    /// 日付
+    pub date: NaiveDate,
+    /// カテゴリ（大項目）
+    pub category: String,
+    /// カテゴリの内訳（中項目）。ない場合は空文字列
+    pub subcategory: String,
+    /// 金額（正の値）
+    pub amount: i32,
+    /// 内容・お店・品目・メモをまとめた説明
+    pub description: String,
+}
+
+/// 家計簿アプリのCSVの読み込み結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HouseholdImport {
+    /// 支出
+    pub transactions: Vec<HouseholdTransaction>,
+    /// 読み飛ばした収入の行数
+    pub skipped_income: usize,
+    /// 読み飛ばした振替の行数
+    pub skipped_transfers: usize,
+    /// 読み飛ばした集計対象外の行数
+    pub skipped_excluded: usize,
+}
+
+/// 家計簿アプリのCSVを読み込む
+///
+/// UTF-8として正しくない場合はShift_JISとして読み込みます。列はヘッダー行の列名で探すため、
+/// 列の順序が変わっていても読み込めます。
+///
+/// # Arguments
+///
+/// * `bytes` - CSVファイルの内容
+/// * `app` - CSVを書き出したアプリ
+///
+/// # Errors
+///
+/// 必要な列がない場合や、支出の行の日付・金額を解釈できない場合は
+/// [`ShishutsukanError::ParseError`] を返します。
+pub fn parse(bytes: &[u8], app: HouseholdApp) -> Result<HouseholdImport> {
+    let text = decode_text(bytes);
+    let mut records = read_records(&text, ',')?.into_iter();
+    let headers: Vec<String> = records
+        .next()
+        .map(|(_, fields)| fields.iter().map(|field| normalize_header(field)).collect())
+        .unwrap_or_default();
+    let columns = Columns { headers: &headers };
+
+    match app {
+        HouseholdApp::Zaim => parse_zaim(records, &columns),
+        HouseholdApp::MoneyForward => parse_money_forward(records, &columns),
+    }
+}
+
+/// Zaimの行を読み込む
+///
+/// `方法` 列が `payment`（支出）の行だけを取り込みます。
+fn parse_zaim(
+    records: impl Iterator<Item = (usize, Vec<String>)>,
+    columns: &Columns<'_>,
+) -> Result<HouseholdImport> {
+    let method = columns.find("方法")?;
+    let date = columns.find("日付")?;
+    let category = columns.find("カテゴリ")?;
+    let subcategory = columns.find("カテゴリの内訳")?;
+    let amount = columns.find("支出")?;
+    let aggregation = columns.find("集計の設定").ok();
+    let details: Vec<usize> = ["お店", "品目", "メモ"]
+        .into_iter()
+        .filter_map(|name| columns.find(name).ok())
+        .collect();
+
+    let mut import = HouseholdImport::default();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for (line, fields) in records {
+        let field = |index: usize| fields.get(index).map(|value| value.trim()).unwrap_or("");
+        match field(method) {
+            "payment" | "支出" => {}
+            "income" | "収入" => {
+                import.skipped_income += 1;
+                continue;
+            }
+            "transfer" | "振替" => {
+                import.skipped_transfers += 1;
+                continue;
+            }
+            _ => {
+                import.skipped_excluded += 1;
+                continue;
+            }
+        }
+        if aggregation.is_some_and(|index| field(index) == "集計に含めない") {
+            import.skipped_excluded += 1;
+            continue;
+        }
+
+        let date = parse_field(line, field(date), "日付", parse_japanese_date)?;
+        let amount = parse_field(line, field(amount), "金額", parse_japanese_amount)?;
+        let category = field(category).to_string();
+        let subcategory = match field(subcategory) {
+            "-" => String::new(),
+            value => value.to_string(),
+        };
+        let description = join_details(details.iter().map(|&index| field(index)));
+
+        let content = format!(
+            "{}:{}:{}:{}:{}",
+            date, amount, category, subcategory, description
+        );
+        let occurrence = occurrences.entry(content.clone()).or_default();
+        *occurrence += 1;
+
+        import.transactions.push(HouseholdTransaction {
+            id: format!("zaim:{}:{}", content, occurrence),
+            date,
+            category,
+            subcategory,
+            amount,
+            description,
+        });
+    }
+    Ok(import)
+}
+
+/// マネーフォワード ME の行を読み込む
+///
+/// 金額が負の行を支出として取り込みます。`計算対象` が `0` の行は集計対象外、
+/// `振替` が `1` の行は振替として読み飛ばします。`ID` が空の行は、Zaimと同じく
+/// 内容と出現回数から冪等キーを作ります。
+fn parse_money_forward(
+    records: impl Iterator<Item = (usize, Vec<String>)>,
+    columns: &Columns<'_>,
+) -> Result<HouseholdImport> {
+    let included = columns.find("計算対象")?;
+    let date = columns.find("日付")?;
+    let content = columns.find("内容")?;
+    let amount = columns.find("金額(円)")?;
+    let category = columns.find("大項目")?;
+    let subcategory = columns.find("中項目")?;
+    let memo = columns.find("メモ").ok();
+    let transfer = columns.find("振替")?;
+    let id = columns.find("ID")?;
+
+    let mut import = HouseholdImport::default();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for (line, fields) in records {
+        let field = |index: usize| fields.get(index).map(|value| value.trim()).unwrap_or("");
+        if field(transfer) == "1" {
+            import.skipped_transfers += 1;
+            continue;
+        }
+        if field(included) == "0" {
+            import.skipped_excluded += 1;
+            continue;
+        }
+        let signed = parse_field(line, field(amount), "金額", parse_japanese_amount)?;
+        if signed >= 0 {
+            import.skipped_income += 1;
+            continue;
+        }
+
+        let date = parse_field(line, field(date), "日付", parse_japanese_date)?;
+        let amount = signed
+            .checked_neg()
+            .ok_or_else(|| invalid(line, "金額", field(amount)))?;
+        let category = field(category).to_string();
+        let subcategory = field(subcategory).to_string();
+        let description = join_details([field(content), memo.map(field).unwrap_or("")]);
+
+        // IDが空の行を同じキーにすると、2行目以降が重複として読み飛ばされてしまう
+        let id = match field(id) {
+            "" => {
+                let content = format!(
+                    "{}:{}:{}:{}:{}",
+                    date, amount, category, subcategory, description
+                );
+                let occurrence = occurrences.entry(content.clone()).or_default();
+                *occurrence += 1;
+                format!("mf:{}:{}", content, occurrence)
+            }
+            id => format!("mf:{}", id),
+        };
+
+        import.transactions.push(HouseholdTransaction {
+            id,
+            date,
+            category,
+            subcategory,
+            amount,
+            description,
+        });
+    }
+    Ok(import)
+}
+
+/// ヘッダー行の列名から列番号を探す
+struct Columns<'a> {
+    headers: &'a [String],
+}
+
+impl Columns<'_> {
+    fn find(&self, name: &str) -> Result<usize> {
+        self.headers
+            .iter()
+            .position(|header| header == name)
+            .ok_or_else(|| {
+                ShishutsukanError::ParseError(format!("ヘッダー行に「{}」列がありません", name))
+            })
+    }
+}
+
+/// 列名の全角括弧と空白の違いを吸収する
+fn normalize_header(header: &str) -> String {
+    header
+        .trim()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '（' => '(',
+            '）' => ')',
+            _ => c,
+        })
+        .collect()
+}
+
+fn parse_field<T>(
+    line: usize,
+    value: &str,
+    name: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<T> {
+    parse(value).ok_or_else(|| invalid(line, name, value))
+}
+
+fn invalid(line: usize, name: &str, value: &str) -> ShishutsukanError {
+    ShishutsukanError::ParseError(format!("{}行目の{}を解釈できません: {}", line, name, value))
+}
+
+fn join_details<'a>(details: impl IntoIterator<Item = &'a str>) -> String {
+    details
+        .into_iter()
+        .filter(|detail| !detail.is_empty())
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+// MARK: - Genre Mapping
+
+/// アプリのカテゴリとジャンルの対応1件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenreMappingEntry {
+    /// カテゴリ（大項目）
+    pub category: String,
+    /// カテゴリの内訳（中項目）
+    pub subcategory: String,
+    /// 割り当てるジャンル
+    pub genre: String,
+    /// ジャンルがサーバー上に存在するかどうか
+    pub exists: bool,
+    /// このカテゴリの支出の件数
+    pub count: usize,
+}
+
+/// アプリのカテゴリとジャンルの対応表
+///
+/// [`build`](Self::build) は、内訳と同じ名前のジャンルがあればそれを、なければカテゴリと
+/// 同じ名前のジャンルを割り当てます。どちらもない場合はカテゴリ名を新しいジャンルとして
+/// 提案し、[`missing_genres`](Self::missing_genres) に含めます。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenreMappingTable {
+    /// 対応の一覧（カテゴリ・内訳の初出順）
+    pub entries: Vec<GenreMappingEntry>,
+    known: HashSet<String>,
+}
+
+impl GenreMappingTable {
+    /// 支出とサーバー上のジャンルから対応表を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - 家計簿アプリの支出
+    /// * `genres` - サーバー上のジャンル一覧
+    pub fn build(transactions: &[HouseholdTransaction], genres: &[GenreWithId]) -> Self {
+        let known: HashSet<String> = genres.iter().map(|genre| genre.name.clone()).collect();
+        let mut table = Self {
+            entries: Vec::new(),
+            known,
+        };
+
+        for transaction in transactions {
+            if let Some(entry) = table.entry_mut(&transaction.category, &transaction.subcategory) {
+                entry.count += 1;
+                continue;
+            }
+            let genre = [&transaction.subcategory, &transaction.category]
+                .into_iter()
+                .find(|name| table.known.contains(*name))
+                .unwrap_or(&transaction.category)
+                .clone();
+            table.entries.push(GenreMappingEntry {
+                category: transaction.category.clone(),
+                subcategory: transaction.subcategory.clone(),
+                exists: table.known.contains(&genre),
+                genre,
+                count: 1,
+            });
+        }
+        table
+    }
+
+    /// カテゴリに割り当てるジャンルを変更
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - カテゴリ（大項目）
+    /// * `subcategory` - 内訳（`None` の場合はカテゴリのすべての内訳）
+    /// * `genre` - 割り当てるジャンル
+    pub fn set(&mut self, category: &str, subcategory: Option<&str>, genre: impl Into<String>) {
+        let genre = genre.into();
+        let exists = self.known.contains(&genre);
+        for entry in self.entries.iter_mut().filter(|entry| {
+            entry.category == category && subcategory.is_none_or(|sub| entry.subcategory == sub)
+        }) {
+            entry.genre = genre.clone();
+            entry.exists = exists;
+        }
+    }
+
+    /// カテゴリと内訳に割り当てたジャンル
+    pub fn genre_for(&self, category: &str, subcategory: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.category == category && entry.subcategory == subcategory)
+            .map(|entry| entry.genre.as_str())
+    }
+
+    /// サーバー上に存在しないジャンル（登録前に作成が必要）
+    pub fn missing_genres(&self) -> Vec<String> {
+        let mut missing: Vec<String> = Vec::new();
+        for entry in self.entries.iter().filter(|entry| !entry.exists) {
+            if !missing.contains(&entry.genre) {
+                missing.push(entry.genre.clone());
+            }
+        }
+        missing
+    }
+
+    /// 支出を支出データと冪等キーの組に変換する
+    ///
+    /// 結果は `add_expenses_idempotent` にそのまま渡せます。対応表にないカテゴリの
+    /// 支出は含めません。
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - 家計簿アプリの支出
+    pub fn to_expenses(
+        &self,
+        transactions: &[HouseholdTransaction],
+    ) -> Vec<(Expense, Option<String>)> {
+        transactions
+            .iter()
+            .filter_map(|transaction| {
+                let genre = self.genre_for(&transaction.category, &transaction.subcategory)?;
+                let expense = Expense::new(
+                    transaction.date.format(DATE_FORMAT).to_string(),
+                    genre.to_string(),
+                    transaction.amount,
+                );
+                Some((expense, Some(transaction.id.clone())))
+            })
+            .collect()
+    }
+
+    fn entry_mut(&mut self, category: &str, subcategory: &str) -> Option<&mut GenreMappingEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.category == category && entry.subcategory == subcategory)
+    }
+}
+
+/// サーバー上のジャンルを取得して対応表を作成
+///
+/// # Arguments
+///
+/// * `client` - APIクライアント
+/// * `transactions` - 家計簿アプリの支出
+///
+/// # Examples
+///
+/// ```no_run
+/// # use shishutsukan::import::household::{self, HouseholdApp};
+/// # use shishutsukan::{ExpenseLedger, Genre, ShishutsukanClient};
+/// # use std::time::Duration;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = ShishutsukanClient::new("http://localhost:8000");
+/// let import = household::parse(&std::fs::read("zaim.csv")?, HouseholdApp::Zaim)?;
+///
+/// let mut table = household::mapping_table(&client, &import.transactions).await?;
+/// table.set("交際費", None, "娯楽費");
+/// for name in table.missing_genres() {
+///     client.add_genre(&Genre::new(name)).await?;
+/// }
+///
+/// let mut ledger = ExpenseLedger::open("ledger.json")?;
+/// let expenses = table.to_expenses(&import.transactions);
+/// client.add_expenses_idempotent(&expenses, &mut ledger, Duration::from_secs(600)).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn mapping_table(
+    client: &ShishutsukanClient,
+    transactions: &[HouseholdTransaction],
+) -> Result<GenreMappingTable> {
+    let genres = client.get_genres().await?;
+    Ok(GenreMappingTable::build(transactions, &genres))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_header() {
+        assert_eq!(normalize_header(" 金額（円） "), "金額(円)");
+    }
+
+    #[test]
+    fn test_parse_zaim_shift_jis() {
+        let text = "日付,方法,カテゴリ,カテゴリの内訳,支払元,入金先,品目,メモ,お店,通貨,収入,支出,振替,残高調整,通貨変換前の金額,集計の設定\n\
+                    2025-01-15,payment,食費,外食,財布,,ランチ,,定食屋,JPY,0,900,0,0,0,常に集計に含める\n\
+                    2025-01-16,transfer,-,-,銀行,財布,,,,JPY,0,0,10000,0,0,常に集計に含める\n\
+                    2025-01-17,payment,日用雑貨,-,財布,,,,薬局,JPY,0,450,0,0,0,集計に含めない\n";
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(text);
+
+        let import = parse(&bytes, HouseholdApp::Zaim).unwrap();
+        assert_eq!(import.skipped_transfers, 1);
+        assert_eq!(import.skipped_excluded, 1);
+        assert_eq!(import.transactions.len(), 1);
+        let transaction = &import.transactions[0];
+        assert_eq!(transaction.subcategory, "外食");
+        assert_eq!(transaction.amount, 900);
+        assert_eq!(transaction.description, "定食屋 / ランチ");
+    }
+
+    #[test]
+    fn test_parse_money_forward_without_id() {
+        let text = "計算対象,日付,内容,金額（円）,保有金融機関,大項目,中項目,メモ,振替,ID\n\
+                    1,2025/02/01,書店,-800,カード,教養・教育,書籍,,0,\n\
+                    1,2025/02/01,書店,-800,カード,教養・教育,書籍,,0,\n\
+                    1,2025/02/02,居酒屋,-4000,カード,食費,外食,,0,\n\
+                    1,2025/02/03,薬局,-450,カード,日用品,ドラッグストア,,0,x1\n";
+
+        let import = parse(text.as_bytes(), HouseholdApp::MoneyForward).unwrap();
+        let ids: Vec<&str> = import.transactions.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids[0], "mf:2025-02-01:800:教養・教育:書籍:書店:1");
+        assert_eq!(ids[1], "mf:2025-02-01:800:教養・教育:書籍:書店:2");
+        assert!(ids[2].starts_with("mf:2025-02-02:4000:"));
+        assert_eq!(ids[3], "mf:x1");
+    }
+}
@@ -0,0 +1,162 @@
+//! OFX形式の明細の読み込み
+//!
+//! OFX 1.x のSGML形式（終了タグの省略あり）と OFX 2.x のXML形式の両方に対応します。
+//! 銀行口座（`BANKTRANLIST`）とクレジットカード（`CCSTMTRS`）の取引を読み込みます。
+//!
+//! ```
+//! use shishutsukan::import::ofx;
+//!
+//! let input = "OFXHEADER:100\nDATA:OFXSGML\n\n<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><BANKTRANLIST>
+//! <STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20250115120000[+9:JST]<TRNAMT>-1,234<FITID>A001<NAME>スーパーマーケット
+//! </STMTTRN></BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>";
+//! let transactions = ofx::parse(input).unwrap();
+//! assert_eq!(transactions[0].id, "ofx:A001");
+//! assert_eq!(transactions[0].amount, -1234);
+//! ```
+
+use crate::error::{Result, ShishutsukanError};
+use crate::import::statement::{parse_decimal_amount, StatementTransaction};
+use crate::import::strip_bom;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// OFX形式の明細から取引を読み込む
+///
+/// # Arguments
+///
+/// * `input` - OFXの内容（Shift_JISなどの場合はUTF-8に変換しておく）
+///
+/// # Errors
+///
+/// タグが閉じられていない場合や、取引に日付・金額がない場合は
+/// [`ShishutsukanError::ParseError`] を返します。
+pub fn parse(input: &str) -> Result<Vec<StatementTransaction>> {
+    let mut transactions = Vec::new();
+    let mut current: Option<HashMap<String, String>> = None;
+    // SGML形式の先頭にある `OFXHEADER:100` などのヘッダーは最初のタグまで読み飛ばす
+    let mut rest = strip_bom(input);
+
+    while let Some(start) = rest.find('<') {
+        let end = rest[start..]
+            .find('>')
+            .map(|end| start + end)
+            .ok_or_else(|| {
+                ShishutsukanError::ParseError("OFXのタグが閉じられていません".to_string())
+            })?;
+        let tag = rest[start + 1..end].trim().to_ascii_uppercase();
+        let after = &rest[end + 1..];
+        let text_end = after.find('<').unwrap_or(after.len());
+        let text = after[..text_end].trim();
+        rest = &after[text_end..];
+
+        match tag.as_str() {
+            "STMTTRN" => current = Some(HashMap::new()),
+            "/STMTTRN" => {
+                if let Some(fields) = current.take() {
+                    transactions.push(transaction(&fields)?);
+                }
+            }
+            _ if tag.starts_with(['/', '?', '!']) => {}
+            _ => {
+                if let Some(fields) = current.as_mut() {
+                    if !text.is_empty() {
+                        fields.insert(tag, unescape(text));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(transactions)
+}
+
+fn transaction(fields: &HashMap<String, String>) -> Result<StatementTransaction> {
+    let field = |name: &str| fields.get(name).map(String::as_str);
+    let date = field("DTPOSTED")
+        .and_then(parse_date)
+        .ok_or_else(|| missing("DTPOSTED", fields))?;
+    let amount = field("TRNAMT")
+        .and_then(parse_decimal_amount)
+        .ok_or_else(|| missing("TRNAMT", fields))?;
+    let payee = field("NAME").or(field("PAYEE")).unwrap_or_default();
+    let memo = field("MEMO").unwrap_or_default();
+
+    // FITIDは必須項目だが、省略している金融機関もあるため内容から生成する
+    let id = match field("FITID") {
+        Some(fitid) => format!("ofx:{}", fitid),
+        None => format!("ofx:{}:{}:{}", date, amount, payee),
+    };
+
+    Ok(StatementTransaction {
+        id,
+        date,
+        amount,
+        payee: payee.to_string(),
+        memo: memo.to_string(),
+    })
+}
+
+fn missing(name: &str, fields: &HashMap<String, String>) -> ShishutsukanError {
+    let fitid = fields.get("FITID").map(String::as_str).unwrap_or("?");
+    ShishutsukanError::ParseError(format!(
+        "取引（FITID: {}）の{}がないか、解釈できません",
+        fitid, name
+    ))
+}
+
+/// `20250115`・`20250115120000.000[+9:JST]` などの日時から日付を取り出す
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xml_credit_card_statement() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<?OFX OFXHEADER="200" VERSION="220"?>
+<OFX>
+  <CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS><BANKTRANLIST>
+    <STMTTRN>
+      <TRNTYPE>DEBIT</TRNTYPE>
+      <DTPOSTED>20250201</DTPOSTED>
+      <TRNAMT>-3300.00</TRNAMT>
+      <FITID>C-1</FITID>
+      <NAME>Books &amp; Cafe</NAME>
+      <MEMO>2月分</MEMO>
+    </STMTTRN>
+    <STMTTRN>
+      <TRNTYPE>CREDIT</TRNTYPE>
+      <DTPOSTED>20250205</DTPOSTED>
+      <TRNAMT>500</TRNAMT>
+      <FITID>C-2</FITID>
+    </STMTTRN>
+  </BANKTRANLIST></CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1>
+</OFX>"#;
+        let transactions = parse(input).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].payee, "Books & Cafe");
+        assert_eq!(transactions[0].memo, "2月分");
+        assert_eq!(transactions[0].amount, -3300);
+        assert!(!transactions[1].is_debit());
+    }
+
+    #[test]
+    fn test_parse_rejects_transaction_without_amount() {
+        let input = "<OFX><STMTTRN><DTPOSTED>20250101<FITID>X</STMTTRN></OFX>";
+        assert!(matches!(
+            parse(input),
+            Err(ShishutsukanError::ParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+//! QIF形式の明細の読み込み
+//!
+//! `!Type:Bank`・`!Type:CCard`・`!Type:Cash` などの取引のセクションを読み込み、
+//! 勘定科目やカテゴリの一覧などのセクションは読み飛ばします。
+//!
+//! ```
+//! use shishutsukan::import::qif;
+//!
+//! let input = "!Type:CCard\nD01/15/2025\nT-1,234.00\nPコンビニ\n^\nD2025/01/16\nT-500\nPカフェ\n^\n";
+//! let transactions = qif::parse(input).unwrap();
+//! assert_eq!(transactions.len(), 2);
+//! assert_eq!(transactions[0].amount, -1234);
+//! assert_eq!(transactions[1].date.to_string(), "2025-01-16");
+//! ```
+
+use crate::error::{Result, ShishutsukanError};
+use crate::import::statement::{parse_decimal_amount, StatementTransaction};
+use crate::import::{normalize_width, parse_japanese_date, strip_bom};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// 取引として読み込むセクション
+const TRANSACTION_TYPES: [&str; 6] = ["bank", "cash", "ccard", "invst", "oth a", "oth l"];
+
+/// 読み込み途中の取引
+#[derive(Default)]
+struct Record {
+    line: usize,
+    date: Option<String>,
+    amount: Option<String>,
+    payee: String,
+    memo: String,
+}
+
+/// QIF形式の明細から取引を読み込む
+///
+/// 日付は `01/15/2025`・`1/15'25`（米国式）と `2025/01/15`（年が先頭）に対応します。
+/// QIFには取引のIDがないため、日付・金額・支払先と、同じ内容の取引の出現順から
+/// IDを生成します。
+///
+/// # Arguments
+///
+/// * `input` - QIFの内容
+///
+/// # Errors
+///
+/// 取引に日付・金額がない場合は [`ShishutsukanError::ParseError`] を返します。
+pub fn parse(input: &str) -> Result<Vec<StatementTransaction>> {
+    let mut transactions = Vec::new();
+    let mut occurrences: HashMap<(NaiveDate, i64, String), usize> = HashMap::new();
+    let mut in_transactions = true;
+    let mut record: Option<Record> = None;
+
+    for (index, line) in strip_bom(input).lines().enumerate() {
+        let line = line.trim_end();
+        let Some(code) = line.chars().next() else {
+            continue;
+        };
+        let value = line[code.len_utf8()..].trim();
+
+        if code == '!' {
+            in_transactions = value
+                .to_ascii_lowercase()
+                .strip_prefix("type:")
+                .is_some_and(|kind| TRANSACTION_TYPES.contains(&kind.trim()));
+            continue;
+        }
+        if !in_transactions {
+            continue;
+        }
+
+        if code == '^' {
+            if let Some(record) = record.take() {
+                transactions.push(finish(record, &mut occurrences)?);
+            }
+            continue;
+        }
+
+        let current = record.get_or_insert_with(|| Record {
+            line: index + 1,
+            ..Record::default()
+        });
+        match code {
+            'D' => current.date = Some(value.to_string()),
+            'T' | 'U' => current.amount = Some(value.to_string()),
+            'P' => current.payee = value.to_string(),
+            'M' => current.memo = value.to_string(),
+            _ => {}
+        }
+    }
+
+    if let Some(record) = record {
+        transactions.push(finish(record, &mut occurrences)?);
+    }
+    Ok(transactions)
+}
+
+fn finish(
+    record: Record,
+    occurrences: &mut HashMap<(NaiveDate, i64, String), usize>,
+) -> Result<StatementTransaction> {
+    let invalid = |name: &str| {
+        ShishutsukanError::ParseError(format!(
+            "{}行目から始まる取引の{}がないか、解釈できません",
+            record.line, name
+        ))
+    };
+    let date = record
+        .date
+        .as_deref()
+        .and_then(parse_date)
+        .ok_or_else(|| invalid("日付"))?;
+    let amount = record
+        .amount
+        .as_deref()
+        .and_then(parse_decimal_amount)
+        .ok_or_else(|| invalid("金額"))?;
+
+    let occurrence = occurrences
+        .entry((date, amount, record.payee.clone()))
+        .or_default();
+    *occurrence += 1;
+
+    Ok(StatementTransaction {
+        id: format!("qif:{}:{}:{}:{}", date, amount, record.payee, occurrence),
+        date,
+        amount,
+        payee: record.payee,
+        memo: record.memo,
+    })
+}
+
+/// QIFの日付を解釈する
+///
+/// `'` の後の2桁の年は2000年代、`/` の後の2桁の年は1970〜2069年とみなします。
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    if let Some(date) = parse_japanese_date(value) {
+        return Some(date);
+    }
+
+    let normalized = normalize_width(value);
+    let apostrophe = normalized.contains('\'');
+    let parts: Vec<&str> = normalized.split(['/', '-', '.', '\'']).collect();
+    let [month, day, year] = parts.as_slice() else {
+        return None;
+    };
+    let year: i32 = year.trim().parse().ok()?;
+    let year = match year {
+        0..=99 if apostrophe => 2000 + year,
+        0..=69 => 2000 + year,
+        70..=99 => 1900 + year,
+        _ => year,
+    };
+    NaiveDate::from_ymd_opt(year, month.trim().parse().ok()?, day.trim().parse().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_variants() {
+        let expected = NaiveDate::from_ymd_opt(2025, 1, 5);
+        assert_eq!(parse_date("01/05/2025"), expected);
+        assert_eq!(parse_date("1/ 5'25"), expected);
+        assert_eq!(parse_date("1/5/25"), expected);
+        assert_eq!(parse_date("2025-01-05"), expected);
+        assert_eq!(
+            parse_date("12/31/99"),
+            NaiveDate::from_ymd_opt(1999, 12, 31)
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_non_transaction_sections_and_numbers_duplicates() {
+        let input = "!Account\nNお財布\nTCash\n^\n!Type:Cash\nD1/15/2025\nT-300\nPカフェ\n^\nD1/15/2025\nT-300\nPカフェ\n^\n!Type:Cat\nN食費\n^\n";
+        let transactions = parse(input).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].id, "qif:2025-01-15:-300:カフェ:1");
+        assert_eq!(transactions[1].id, "qif:2025-01-15:-300:カフェ:2");
+    }
+}
@@ -0,0 +1,212 @@
+//! 銀行・カードの明細の取引と、支払先によるジャンルの割り当て
+//!
+//! [`ofx`](super::ofx) や [`qif`](super::qif) で読み込んだ取引を [`to_expenses`] で
+//! 支出データに変換し、`add_expenses_idempotent` に渡して重複を除きながら登録します。
+//!
+//! ```no_run
+//! use shishutsukan::import::{ofx, statement::{to_expenses, GenreRules}};
+//! use shishutsukan::{ExpenseLedger, ShishutsukanClient};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ShishutsukanClient::new("http://localhost:8000");
+//! let transactions = ofx::parse(&std::fs::read_to_string("statement.ofx")?)?;
+//! let rules = GenreRules::new()
+//!     .rule("スーパー", "食費")
+//!     .rule("JR", "交通費")
+//!     .default_genre("雑費");
+//!
+//! let import = to_expenses(&transactions, &rules);
+//! let mut ledger = ExpenseLedger::open("ledger.json")?;
+//! let results = client
+//!     .add_expenses_idempotent(&import.expenses, &mut ledger, Duration::from_secs(600))
+//!     .await?;
+//! println!("{} 件を処理しました", results.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::import::normalize_width;
+use crate::models::{Expense, DATE_FORMAT};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// 明細の1取引
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementTransaction {
+    /// 重複判定に使うID（OFXでは `ofx:` に続けてFITID、QIFでは内容から生成）
+    pub id: String,
+    /// 日付
+    pub date: NaiveDate,
+    /// 金額（出金は負の値）
+    pub amount: i64,
+    /// 支払先
+    pub payee: String,
+    /// メモ
+    pub memo: String,
+}
+
+impl StatementTransaction {
+    /// 出金かどうか
+    pub fn is_debit(&self) -> bool {
+        self.amount < 0
+    }
+}
+
+/// 支払先・メモに含まれる文字列とジャンルの対応
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenreRule {
+    /// 支払先またはメモに含まれる文字列（大文字・小文字、全角・半角を区別しない）
+    pub keyword: String,
+    /// 割り当てるジャンル
+    pub genre: String,
+}
+
+/// 取引にジャンルを割り当てる規則
+///
+/// 上から順に照合し、最初に一致した規則のジャンルを使います。
+/// JSONなどの設定ファイルから読み込むこともできます。
+///
+/// ```json
+/// { "rules": [{ "keyword": "スーパー", "genre": "食費" }], "default_genre": "雑費" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenreRules {
+    /// 規則の一覧
+    #[serde(default)]
+    pub rules: Vec<GenreRule>,
+    /// どの規則にも一致しない取引に使うジャンル
+    #[serde(default)]
+    pub default_genre: Option<String>,
+}
+
+impl GenreRules {
+    /// 空の規則を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 規則を追加
+    ///
+    /// # Arguments
+    ///
+    /// * `keyword` - 支払先またはメモに含まれる文字列
+    /// * `genre` - 割り当てるジャンル
+    pub fn rule(mut self, keyword: impl Into<String>, genre: impl Into<String>) -> Self {
+        self.rules.push(GenreRule {
+            keyword: keyword.into(),
+            genre: genre.into(),
+        });
+        self
+    }
+
+    /// どの規則にも一致しない取引に使うジャンルを指定
+    pub fn default_genre(mut self, genre: impl Into<String>) -> Self {
+        self.default_genre = Some(genre.into());
+        self
+    }
+
+    /// 取引に割り当てるジャンル
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::import::statement::GenreRules;
+    ///
+    /// let rules = GenreRules::new().rule("amazon", "日用品");
+    /// assert_eq!(rules.genre_for("ＡＭＡＺＯＮ．ＣＯ．ＪＰ", ""), Some("日用品"));
+    /// assert_eq!(rules.genre_for("コンビニ", ""), None);
+    /// ```
+    pub fn genre_for(&self, payee: &str, memo: &str) -> Option<&str> {
+        let payee = fold(payee);
+        let memo = fold(memo);
+        self.rules
+            .iter()
+            .find(|rule| {
+                let keyword = fold(&rule.keyword);
+                payee.contains(&keyword) || memo.contains(&keyword)
+            })
+            .map(|rule| rule.genre.as_str())
+            .or(self.default_genre.as_deref())
+    }
+}
+
+/// 明細の変換結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatementImport {
+    /// 登録する支出データと冪等キーの組（`add_expenses_idempotent` にそのまま渡せる）
+    pub expenses: Vec<(Expense, Option<String>)>,
+    /// どの規則にも一致しなかった出金
+    pub unmatched: Vec<StatementTransaction>,
+    /// 金額が大きすぎて登録できない出金
+    pub invalid: Vec<StatementTransaction>,
+    /// 読み飛ばした入金の件数
+    pub skipped_credits: usize,
+}
+
+/// 明細の取引を支出データに変換する
+///
+/// 出金だけを対象にし、金額は正の値にします。取引のIDを冪等キーにするため、
+/// 同じ明細を何度取り込んでも重複して登録されません。
+///
+/// # Arguments
+///
+/// * `transactions` - 明細の取引
+/// * `rules` - ジャンルを割り当てる規則
+pub fn to_expenses(transactions: &[StatementTransaction], rules: &GenreRules) -> StatementImport {
+    let mut import = StatementImport::default();
+
+    for transaction in transactions {
+        if !transaction.is_debit() {
+            import.skipped_credits += 1;
+            continue;
+        }
+        let Some(genre) = rules.genre_for(&transaction.payee, &transaction.memo) else {
+            import.unmatched.push(transaction.clone());
+            continue;
+        };
+        let Ok(amount) = i32::try_from(-transaction.amount) else {
+            import.invalid.push(transaction.clone());
+            continue;
+        };
+
+        let expense = Expense::new(
+            transaction.date.format(DATE_FORMAT).to_string(),
+            genre.to_string(),
+            amount,
+        );
+        import
+            .expenses
+            .push((expense, Some(transaction.id.clone())));
+    }
+
+    import
+}
+
+/// `-1,234.00` のような小数表記の金額を円単位に丸めて解釈する
+pub(crate) fn parse_decimal_amount(input: &str) -> Option<i64> {
+    let normalized: String = normalize_width(input.trim())
+        .chars()
+        .filter(|c| !matches!(c, ',' | ' ' | '+'))
+        .collect();
+    let value: f64 = normalized.parse().ok()?;
+    value.is_finite().then(|| value.round() as i64)
+}
+
+/// 照合用に全角を半角にし、小文字にそろえる
+fn fold(text: &str) -> String {
+    normalize_width(text).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_decimal_amount() {
+        assert_eq!(parse_decimal_amount("-1,234.00"), Some(-1234));
+        assert_eq!(parse_decimal_amount("+500"), Some(500));
+        assert_eq!(parse_decimal_amount("-99.5"), Some(-100));
+        assert_eq!(parse_decimal_amount("abc"), None);
+    }
+}
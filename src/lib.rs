@@ -5,7 +5,7 @@
 //!
 //! ## 特徴
 //!
-//! - ✅ 最小限の依存関係（reqwest、serde、tokio）
+//! - ✅ 最小限の依存関係（reqwest、serde、tokio、chrono、encoding_rs）
 //! - ✅ Async/Await対応
 //! - ✅ 型安全なAPIインターフェース
 //! - ✅ エラーハンドリング
@@ -20,6 +20,17 @@
 //! tokio = { version = "1", features = ["full"] }
 //! ```
 //!
+//! ## フィーチャー
+//!
+//! | フィーチャー | 内容 |
+//! |-------------|------|
+//! | `mirror` | ローカルSQLiteミラー（[`mirror`] モジュール） |
+//! | `xlsx` | Excel形式（.xlsx）のレポート出力（[`export::xlsx`] モジュール） |
+//! | `config` | 設定ファイルと名前付きプロファイル（[`config`] モジュール） |
+//! | `rules` | 規則によるジャンルの自動割り当て（[`rules`] モジュール） |
+//! | `cli` | `shishutsukan` コマンド（支出データ・ジャンルの追加・一覧・削除） |
+//! | `tui` | `shishutsukan-tui` 端末用UI（支出データの閲覧・追加・削除・絞り込み） |
+//!
 //! ## 使い方
 //!
 //! ### 基本的な初期化
@@ -77,11 +88,79 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ### レスポンスキャッシュ
+//!
+//! ```no_run
+//! use shishutsukan::ShishutsukanClient;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ShishutsukanClient::new("http://localhost:8000")
+//!     .with_cache(Duration::from_secs(300));
+//!
+//! // 2回目以降はキャッシュから応答（add_* / delete_* で自動的に破棄）
+//! let genres = client.get_genres().await?;
+//! let genres = client.get_genres().await?;
+//!
+//! // 明示的に取得し直す
+//! client.refresh().await?;
+//! println!("{:?}", client.cache_stats());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ### レート制限
+//!
+//! ```no_run
+//! use shishutsukan::{ShishutsukanClient, Expense};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! // 平均2件/秒、最大5件まで連続送信（クローン間で共有）
+//! let client = ShishutsukanClient::new("http://localhost:8000").with_rate_limit(2.0, 5);
+//!
+//! let expenses = vec![Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000)];
+//! for result in client.add_expenses(&expenses).await {
+//!     result?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
+mod backup;
+mod cache;
 mod client;
+mod dedupe;
 mod error;
+mod idempotency;
 mod models;
+mod offline;
+mod rate_limit;
+
+pub mod anomaly;
+pub mod budget;
+pub mod export;
+pub mod forecast;
+pub mod import;
+pub mod quick_entry;
+pub mod recurring;
+pub mod report;
+pub mod sync;
+
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "mirror")]
+pub mod mirror;
+#[cfg(feature = "rules")]
+pub mod rules;
 
-pub use client::ShishutsukanClient;
+pub use backup::{Backup, BackupFormat, RestoreMode, RestoreSummary, BACKUP_VERSION};
+pub use cache::CacheStats;
+pub use client::{Auth, ShishutsukanClient};
+pub use dedupe::{find_duplicates, DedupePlan, DedupeReport, DuplicateCriteria, DuplicateGroup};
 pub use error::{Result, ShishutsukanError};
+pub use idempotency::{DuplicateReason, ExpenseLedger, IdempotentAddOutcome, LedgerEntry};
 pub use models::{ApiMessage, Expense, ExpenseWithId, Genre, GenreWithId};
+pub use offline::{
+    ConflictKind, FlushConflict, FlushReport, OfflineClient, PendingOperation, WriteOutcome,
+};
@@ -0,0 +1,342 @@
+//! サーバーデータのローカルSQLiteミラー
+//!
+//! `mirror` フィーチャーを有効にすると利用できます。
+//!
+//! ```no_run
+//! use shishutsukan::mirror::Mirror;
+//! use shishutsukan::ShishutsukanClient;
+//! use chrono::NaiveDate;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ShishutsukanClient::new("http://localhost:8000");
+//! let mut mirror = Mirror::open("shishutsukan.sqlite3")?;
+//!
+//! let summary = mirror.sync(&client).await?;
+//! println!("追加 {} 件, 削除 {} 件", summary.expenses_inserted, summary.expenses_deleted);
+//!
+//! let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+//! let to = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+//! for (genre, total) in mirror.totals_by_genre(from, to)? {
+//!     println!("{}: ¥{}", genre, total);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::ShishutsukanClient;
+use crate::error::Result;
+use crate::models::{ExpenseWithId, GenreWithId, DATE_FORMAT};
+use crate::sync::{SyncState, SyncStore};
+use chrono::{NaiveDate, Utc};
+use rusqlite::{params, Connection, Row, Transaction};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS expenses (
+    id     INTEGER PRIMARY KEY,
+    date   TEXT    NOT NULL,
+    genre  TEXT    NOT NULL,
+    amount INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS expenses_date ON expenses (date);
+CREATE INDEX IF NOT EXISTS expenses_genre ON expenses (genre);
+CREATE TABLE IF NOT EXISTS genres (
+    id         INTEGER PRIMARY KEY,
+    name       TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS meta (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// 同期の結果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    /// 新しく取り込んだ支出データの件数
+    pub expenses_inserted: usize,
+    /// サーバーで削除されていた支出データの件数
+    pub expenses_deleted: usize,
+    /// 内容が変わっていた支出データの件数
+    pub expenses_updated: usize,
+    /// 新しく取り込んだジャンルの件数
+    pub genres_inserted: usize,
+    /// サーバーで削除されていたジャンルの件数
+    pub genres_deleted: usize,
+    /// 内容が変わっていたジャンルの件数
+    pub genres_updated: usize,
+}
+
+impl SyncSummary {
+    /// 変更がなかったかどうか
+    pub fn is_unchanged(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// サーバーデータのローカルSQLiteミラー
+#[derive(Debug)]
+pub struct Mirror {
+    conn: Connection,
+}
+
+impl Mirror {
+    /// SQLiteファイルを開く（存在しない場合は作成）
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - SQLiteファイルのパス
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// メモリ上にミラーを作成
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// サーバーからジャンルと支出データを取得してミラーを更新
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - APIクライアント
+    pub async fn sync(&mut self, client: &ShishutsukanClient) -> Result<SyncSummary> {
+        let genres = client.get_genres().await?;
+        let expenses = client.get_expenses().await?;
+        self.apply(&genres, &expenses)
+    }
+
+    /// 取得済みのジャンルと支出データでミラーを更新
+    ///
+    /// IDをキーにして、追加・削除・内容の変更を検出します。
+    ///
+    /// # Arguments
+    ///
+    /// * `genres` - サーバー上のジャンル一覧
+    /// * `expenses` - サーバー上の支出データ一覧
+    pub fn apply(
+        &mut self,
+        genres: &[GenreWithId],
+        expenses: &[ExpenseWithId],
+    ) -> Result<SyncSummary> {
+        let tx = self.conn.transaction()?;
+        let mut summary = SyncSummary::default();
+
+        let local_genres: HashMap<i32, GenreWithId> = select(
+            &tx,
+            "SELECT id, name, created_at FROM genres",
+            [],
+            genre_from_row,
+        )?
+        .into_iter()
+        .map(|genre| (genre.id, genre))
+        .collect();
+        for genre in genres {
+            match local_genres.get(&genre.id) {
+                Some(local) if local == genre => continue,
+                Some(_) => summary.genres_updated += 1,
+                None => summary.genres_inserted += 1,
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO genres (id, name, created_at) VALUES (?1, ?2, ?3)",
+                params![genre.id, genre.name, genre.created_at],
+            )?;
+        }
+        summary.genres_deleted = delete_missing(
+            &tx,
+            "DELETE FROM genres WHERE id = ?1",
+            local_genres.keys(),
+            genres.iter().map(|genre| genre.id),
+        )?;
+
+        let local_expenses: HashMap<i32, ExpenseWithId> = select(
+            &tx,
+            "SELECT id, date, genre, amount FROM expenses",
+            [],
+            expense_from_row,
+        )?
+        .into_iter()
+        .map(|expense| (expense.id, expense))
+        .collect();
+        for expense in expenses {
+            match local_expenses.get(&expense.id) {
+                Some(local) if local == expense => continue,
+                Some(_) => summary.expenses_updated += 1,
+                None => summary.expenses_inserted += 1,
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO expenses (id, date, genre, amount) VALUES (?1, ?2, ?3, ?4)",
+                params![expense.id, expense.date, expense.genre, expense.amount],
+            )?;
+        }
+        summary.expenses_deleted = delete_missing(
+            &tx,
+            "DELETE FROM expenses WHERE id = ?1",
+            local_expenses.keys(),
+            expenses.iter().map(|expense| expense.id),
+        )?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('last_synced_at', ?1)",
+            params![Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// 最後に同期した日時（RFC 3339形式）
+    pub fn last_synced_at(&self) -> Result<Option<String>> {
+        let mut values = select(
+            &self.conn,
+            "SELECT value FROM meta WHERE key = 'last_synced_at'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(values.pop())
+    }
+
+    // MARK: - Queries
+
+    /// ジャンルの一覧（ID順）
+    pub fn genres(&self) -> Result<Vec<GenreWithId>> {
+        select(
+            &self.conn,
+            "SELECT id, name, created_at FROM genres ORDER BY id",
+            [],
+            genre_from_row,
+        )
+    }
+
+    /// 支出データの一覧（日付順）
+    pub fn expenses(&self) -> Result<Vec<ExpenseWithId>> {
+        select(
+            &self.conn,
+            "SELECT id, date, genre, amount FROM expenses ORDER BY date, id",
+            [],
+            expense_from_row,
+        )
+    }
+
+    /// 期間内の支出データ（日付順）
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - 開始日（この日を含む）
+    /// * `to` - 終了日（この日を含む）
+    pub fn expenses_between(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<ExpenseWithId>> {
+        select(
+            &self.conn,
+            "SELECT id, date, genre, amount FROM expenses
+             WHERE date BETWEEN ?1 AND ?2 ORDER BY date, id",
+            params![format_date(from), format_date(to)],
+            expense_from_row,
+        )
+    }
+
+    /// ジャンルの支出データ（日付順）
+    ///
+    /// # Arguments
+    ///
+    /// * `genre` - ジャンル名
+    pub fn expenses_by_genre(&self, genre: &str) -> Result<Vec<ExpenseWithId>> {
+        select(
+            &self.conn,
+            "SELECT id, date, genre, amount FROM expenses WHERE genre = ?1 ORDER BY date, id",
+            params![genre],
+            expense_from_row,
+        )
+    }
+
+    /// 期間内のジャンル別合計金額（金額の多い順）
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - 開始日（この日を含む）
+    /// * `to` - 終了日（この日を含む）
+    pub fn totals_by_genre(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<(String, i64)>> {
+        select(
+            &self.conn,
+            "SELECT genre, SUM(amount) AS total FROM expenses
+             WHERE date BETWEEN ?1 AND ?2 GROUP BY genre ORDER BY total DESC, genre",
+            params![format_date(from), format_date(to)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+}
+
+/// 双方向同期の状態をミラーと同じSQLiteファイルに保存する
+impl SyncStore for Mirror {
+    fn load(&self) -> Result<SyncState> {
+        let mut values: Vec<String> = select(
+            &self.conn,
+            "SELECT value FROM meta WHERE key = 'sync_state'",
+            [],
+            |row| row.get(0),
+        )?;
+        match values.pop() {
+            Some(value) => Ok(serde_json::from_str(&value)?),
+            None => Ok(SyncState::default()),
+        }
+    }
+
+    fn save(&mut self, state: &SyncState) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('sync_state', ?1)",
+            params![serde_json::to_string(state)?],
+        )?;
+        Ok(())
+    }
+}
+
+fn select<T, P: rusqlite::Params>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+    map: impl FnMut(&Row<'_>) -> rusqlite::Result<T>,
+) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, map)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<T>>>()?)
+}
+
+fn delete_missing<'a>(
+    tx: &Transaction<'_>,
+    sql: &str,
+    local_ids: impl Iterator<Item = &'a i32>,
+    server_ids: impl Iterator<Item = i32>,
+) -> Result<usize> {
+    let server_ids: HashSet<i32> = server_ids.collect();
+    let mut deleted = 0;
+    for id in local_ids.filter(|id| !server_ids.contains(id)) {
+        deleted += tx.execute(sql, params![id])?;
+    }
+    Ok(deleted)
+}
+
+fn genre_from_row(row: &Row<'_>) -> rusqlite::Result<GenreWithId> {
+    Ok(GenreWithId {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
+
+fn expense_from_row(row: &Row<'_>) -> rusqlite::Result<ExpenseWithId> {
+    Ok(ExpenseWithId {
+        id: row.get(0)?,
+        date: row.get(1)?,
+        genre: row.get(2)?,
+        amount: row.get(3)?,
+    })
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
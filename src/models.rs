@@ -1,5 +1,9 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+/// 支出データの日付フォーマット
+pub(crate) const DATE_FORMAT: &str = "%Y-%m-%d";
+
 /// 支出データモデル
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Expense {
@@ -20,6 +24,11 @@ impl Expense {
             amount,
         }
     }
+
+    /// 日付を解釈する。`YYYY-MM-DD` 形式でない場合は `None`
+    pub fn naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, DATE_FORMAT).ok()
+    }
 }
 
 /// ID付き支出データモデル
@@ -35,6 +44,23 @@ pub struct ExpenseWithId {
     pub amount: i32,
 }
 
+impl ExpenseWithId {
+    /// 日付を解釈する。`YYYY-MM-DD` 形式でない場合は `None`
+    pub fn naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, DATE_FORMAT).ok()
+    }
+
+    /// IDを除いた支出データを取得
+    pub fn to_expense(&self) -> Expense {
+        Expense::new(self.date.clone(), self.genre.clone(), self.amount)
+    }
+
+    /// 日付・ジャンル・金額が支出データと一致するかどうか
+    pub fn matches(&self, expense: &Expense) -> bool {
+        self.date == expense.date && self.genre == expense.genre && self.amount == expense.amount
+    }
+}
+
 /// ジャンルデータモデル
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Genre {
@@ -0,0 +1,364 @@
+use crate::client::ShishutsukanClient;
+use crate::error::{Result, ShishutsukanError};
+use crate::models::{ApiMessage, Expense, Genre};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 未送信の書き込み操作
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PendingOperation {
+    /// 支出データの追加
+    AddExpense {
+        /// 追加する支出データ
+        expense: Expense,
+    },
+    /// 支出データの削除
+    DeleteExpense {
+        /// 削除する支出データのID
+        id: i32,
+    },
+    /// ジャンルの追加
+    AddGenre {
+        /// 追加するジャンル
+        genre: Genre,
+    },
+}
+
+/// オフライン対応の書き込み結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteOutcome {
+    /// サーバーに送信した
+    Sent(ApiMessage),
+    /// サーバーに接続できないためジャーナルに保存した
+    Queued,
+}
+
+/// 再送時に検出した競合の種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictKind {
+    /// 削除しようとした支出データがサーバー上に存在しない
+    ExpenseAlreadyDeleted,
+    /// 追加しようとしたジャンルがサーバー上に既に存在する
+    GenreAlreadyExists,
+    /// 支出データのジャンルがサーバー上に存在しない
+    GenreMissing,
+    /// サーバーが操作を拒否した
+    Rejected(String),
+}
+
+/// 再送時に検出した競合
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlushConflict {
+    /// 競合した操作
+    pub operation: PendingOperation,
+    /// 競合の種類
+    pub kind: ConflictKind,
+}
+
+/// 再送の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlushReport {
+    /// 送信できた操作
+    pub applied: Vec<PendingOperation>,
+    /// 競合のためジャーナルから取り除いた操作
+    pub conflicts: Vec<FlushConflict>,
+    /// サーバーに接続できず、ジャーナルに残った操作の件数
+    pub remaining: usize,
+}
+
+/// オフライン対応のクライアント
+///
+/// サーバーに接続できない場合、`add_expense` / `delete_expense` / `add_genre` を
+/// ジャーナルファイル（JSON Lines）に保存し、[`flush`](Self::flush) で順に再送します。
+/// 未送信の操作がある間は、順序を保つため新しい操作もジャーナルに追加されます。
+///
+/// # Examples
+///
+/// ```no_run
+/// # use shishutsukan::{ShishutsukanClient, Expense, OfflineClient, WriteOutcome};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = ShishutsukanClient::new("http://localhost:8000");
+/// let mut offline = OfflineClient::open(client, "pending.jsonl")?;
+///
+/// let expense = Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000);
+/// if offline.add_expense(&expense).await? == WriteOutcome::Queued {
+///     println!("オフラインのため保存しました");
+/// }
+///
+/// // ネットワークに戻ったら再送
+/// let report = offline.flush().await?;
+/// println!("送信 {} 件, 競合 {} 件", report.applied.len(), report.conflicts.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct OfflineClient {
+    client: ShishutsukanClient,
+    journal_path: PathBuf,
+    pending: Vec<PendingOperation>,
+}
+
+impl OfflineClient {
+    /// ジャーナルファイルを読み込んでオフライン対応のクライアントを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - APIクライアント
+    /// * `journal_path` - ジャーナルファイルのパス（存在しない場合は必要になった時点で作成）
+    pub fn open(client: ShishutsukanClient, journal_path: impl AsRef<Path>) -> Result<Self> {
+        let journal_path = journal_path.as_ref().to_path_buf();
+        let pending = match fs::read_to_string(&journal_path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<std::result::Result<_, _>>()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            client,
+            journal_path,
+            pending,
+        })
+    }
+
+    /// 内部のAPIクライアント
+    pub fn client(&self) -> &ShishutsukanClient {
+        &self.client
+    }
+
+    /// 未送信の操作
+    pub fn pending(&self) -> &[PendingOperation] {
+        &self.pending
+    }
+
+    /// 支出データを追加（接続できない場合はジャーナルに保存）
+    ///
+    /// # Arguments
+    ///
+    /// * `expense` - 追加する支出データ
+    pub async fn add_expense(&mut self, expense: &Expense) -> Result<WriteOutcome> {
+        self.write(PendingOperation::AddExpense {
+            expense: expense.clone(),
+        })
+        .await
+    }
+
+    /// 支出データを削除（接続できない場合はジャーナルに保存）
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - 削除する支出データのID
+    pub async fn delete_expense(&mut self, id: i32) -> Result<WriteOutcome> {
+        self.write(PendingOperation::DeleteExpense { id }).await
+    }
+
+    /// ジャンルを追加（接続できない場合はジャーナルに保存）
+    ///
+    /// # Arguments
+    ///
+    /// * `genre` - 追加するジャンル
+    pub async fn add_genre(&mut self, genre: &Genre) -> Result<WriteOutcome> {
+        self.write(PendingOperation::AddGenre {
+            genre: genre.clone(),
+        })
+        .await
+    }
+
+    /// 未送信の操作を順に再送
+    ///
+    /// 支出データより後に保存されたジャンルの追加は、その支出データより先に送信します。
+    /// 削除済みの支出データの削除や既存ジャンルの追加などの競合は、ジャーナルから
+    /// 取り除いてレポートします。途中でサーバーに接続できなくなった場合は、
+    /// 残りの操作をジャーナルに残して終了します。
+    pub async fn flush(&mut self) -> Result<FlushReport> {
+        let mut report = FlushReport::default();
+        if self.pending.is_empty() {
+            return Ok(report);
+        }
+
+        let operations = order_for_replay(std::mem::take(&mut self.pending));
+        let server_state = async {
+            let genres = self.client.get_genres().await?;
+            let expenses = self.client.get_expenses().await?;
+            Ok::<_, ShishutsukanError>((genres, expenses))
+        }
+        .await;
+        let (genres, expenses) = match server_state {
+            Ok(state) => state,
+            Err(e) => {
+                self.pending = operations;
+                return if e.is_connectivity_error() {
+                    report.remaining = self.pending.len();
+                    Ok(report)
+                } else {
+                    Err(e)
+                };
+            }
+        };
+
+        let mut genre_names: HashSet<String> = genres.into_iter().map(|g| g.name).collect();
+        let expense_ids: HashSet<i32> = expenses.into_iter().map(|e| e.id).collect();
+
+        let mut operations = operations.into_iter();
+        while let Some(operation) = operations.next() {
+            let conflict = match &operation {
+                PendingOperation::AddGenre { genre } if genre_names.contains(&genre.name) => {
+                    Some(ConflictKind::GenreAlreadyExists)
+                }
+                PendingOperation::AddExpense { expense }
+                    if !genre_names.contains(&expense.genre) =>
+                {
+                    Some(ConflictKind::GenreMissing)
+                }
+                PendingOperation::DeleteExpense { id } if !expense_ids.contains(id) => {
+                    Some(ConflictKind::ExpenseAlreadyDeleted)
+                }
+                _ => None,
+            };
+            if let Some(kind) = conflict {
+                report.conflicts.push(FlushConflict { operation, kind });
+                continue;
+            }
+
+            match self.send(&operation).await {
+                Ok(_) => {
+                    if let PendingOperation::AddGenre { genre } = &operation {
+                        genre_names.insert(genre.name.clone());
+                    }
+                    report.applied.push(operation);
+                }
+                Err(e) if e.is_connectivity_error() => {
+                    self.pending.push(operation);
+                    self.pending.extend(operations);
+                    break;
+                }
+                Err(e) => report.conflicts.push(FlushConflict {
+                    operation,
+                    kind: ConflictKind::Rejected(e.to_string()),
+                }),
+            }
+        }
+
+        report.remaining = self.pending.len();
+        self.save()?;
+        Ok(report)
+    }
+
+    async fn write(&mut self, operation: PendingOperation) -> Result<WriteOutcome> {
+        if self.pending.is_empty() {
+            match self.send(&operation).await {
+                Ok(message) => return Ok(WriteOutcome::Sent(message)),
+                Err(e) if e.is_connectivity_error() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.append(&operation)?;
+        self.pending.push(operation);
+        Ok(WriteOutcome::Queued)
+    }
+
+    async fn send(&self, operation: &PendingOperation) -> Result<ApiMessage> {
+        match operation {
+            PendingOperation::AddExpense { expense } => self.client.add_expense(expense).await,
+            PendingOperation::DeleteExpense { id } => self.client.delete_expense(*id).await,
+            PendingOperation::AddGenre { genre } => self.client.add_genre(genre).await,
+        }
+    }
+
+    fn append(&self, operation: &PendingOperation) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.journal_path)?;
+        writeln!(file, "{}", serde_json::to_string(operation)?)?;
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let mut contents = String::new();
+        for operation in &self.pending {
+            contents.push_str(&serde_json::to_string(operation)?);
+            contents.push('\n');
+        }
+
+        // 書き込み途中で中断してもジャーナルが壊れないよう、一時ファイル経由で置き換える
+        let tmp_path = self.journal_path.with_extension("tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, &self.journal_path)?;
+        Ok(())
+    }
+}
+
+/// 支出データが依存するジャンルの追加を、その支出データより前に移動する
+fn order_for_replay(operations: Vec<PendingOperation>) -> Vec<PendingOperation> {
+    let mut slots: Vec<Option<PendingOperation>> = operations.into_iter().map(Some).collect();
+    let mut ordered = Vec::with_capacity(slots.len());
+
+    for index in 0..slots.len() {
+        if let Some(PendingOperation::AddExpense { expense }) = &slots[index] {
+            let genre_name = expense.genre.clone();
+            let later_genre = slots[index + 1..].iter().position(|slot| {
+                matches!(slot, Some(PendingOperation::AddGenre { genre }) if genre.name == genre_name)
+            });
+            if let Some(offset) = later_genre {
+                ordered.extend(slots[index + 1 + offset].take());
+            }
+        }
+        ordered.extend(slots[index].take());
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_expense(genre: &str) -> PendingOperation {
+        PendingOperation::AddExpense {
+            expense: Expense::new("2025-01-15".to_string(), genre.to_string(), 100),
+        }
+    }
+
+    fn add_genre(name: &str) -> PendingOperation {
+        PendingOperation::AddGenre {
+            genre: Genre::new(name.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_genre_is_replayed_before_dependent_expense() {
+        let operations = vec![
+            add_expense("食費"),
+            add_expense("新ジャンル"),
+            PendingOperation::DeleteExpense { id: 1 },
+            add_genre("新ジャンル"),
+            add_genre("別ジャンル"),
+        ];
+
+        assert_eq!(
+            order_for_replay(operations),
+            vec![
+                add_expense("食費"),
+                add_genre("新ジャンル"),
+                add_expense("新ジャンル"),
+                PendingOperation::DeleteExpense { id: 1 },
+                add_genre("別ジャンル"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_journal_line_format() {
+        let line = serde_json::to_string(&PendingOperation::DeleteExpense { id: 3 }).unwrap();
+        assert_eq!(line, r#"{"op":"delete_expense","id":3}"#);
+    }
+}
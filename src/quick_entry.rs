@@ -0,0 +1,569 @@
+//! 自然な書き方の文から支出データを作る簡易入力
+//!
+//! `昨日 ランチ 850円`・`1/15 交通費 ¥1,200`・`coffee 480 today` のような文を、空白で区切った
+//! 語ごとに日付・金額・ジャンルの手がかりとして解釈します。相対的な日付（今日・昨日・
+//! 3日前・月曜など）、全角数字、円記号や「円」の付いた金額に対応し、ジャンルはサーバー上の
+//! ジャンル名と同義語の表から選びます。ジャンルを1つに決められない場合は候補を返します。
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use shishutsukan::quick_entry::QuickEntryParser;
+//! use shishutsukan::GenreWithId;
+//!
+//! let genres: Vec<GenreWithId> = ["食費", "交通費", "日用品"]
+//!     .iter()
+//!     .enumerate()
+//!     .map(|(i, name)| GenreWithId {
+//!         id: i as i32 + 1,
+//!         name: name.to_string(),
+//!         created_at: "2025-01-01 00:00:00".to_string(),
+//!     })
+//!     .collect();
+//! let parser = QuickEntryParser::new(&genres);
+//! let today = NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+//!
+//! let entry = parser.parse("昨日 ランチ 850円", today).unwrap();
+//! assert_eq!(entry.date, NaiveDate::from_ymd_opt(2025, 1, 19).unwrap());
+//! assert_eq!((entry.genre.as_deref(), entry.amount), (Some("食費"), 850));
+//!
+//! // コンビニは食費と日用品のどちらにもなりうる
+//! let entry = parser.parse("コンビニ 300", today).unwrap();
+//! assert!(entry.is_ambiguous());
+//! assert_eq!(entry.candidates.len(), 2);
+//! ```
+
+use crate::error::{Result, ShishutsukanError};
+use crate::import::{normalize_width, parse_japanese_amount, parse_japanese_date};
+use crate::models::{Expense, GenreWithId, DATE_FORMAT};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 組み込みの同義語（ジャンル名と、そのジャンルを表す空白区切りの語）
+const BUILTIN_SYNONYMS: &[(&str, &str)] = &[
+    (
+        "食費",
+        "ランチ 昼食 昼ごはん 朝食 朝ごはん 夕食 晩ごはん 夕飯 外食 弁当 コーヒー カフェ \
+         スーパー 食材 おやつ コンビニ 飲み会 lunch dinner breakfast coffee cafe groceries \
+         food snack",
+    ),
+    (
+        "交通費",
+        "電車 バス タクシー 新幹線 定期 ガソリン 駐車場 高速 train bus taxi parking gas",
+    ),
+    (
+        "日用品",
+        "洗剤 ティッシュ トイレットペーパー ドラッグストア 薬局 消耗品 コンビニ toiletries \
+         drugstore",
+    ),
+    (
+        "娯楽費",
+        "映画 本 漫画 ゲーム カラオケ 飲み会 サブスク movie book game",
+    ),
+    (
+        "光熱費",
+        "電気 ガス 水道 電気代 ガス代 水道代 electricity water gas",
+    ),
+];
+
+/// ジャンルを表す語の表
+///
+/// 1つの語に複数のジャンルを対応させることができ、その場合は解釈の結果があいまいになります。
+/// JSONなどの設定ファイルから読み込むこともできます。
+///
+/// ```json
+/// { "ランチ": ["食費"], "コンビニ": ["食費", "日用品"] }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Synonyms {
+    entries: BTreeMap<String, Vec<String>>,
+}
+
+impl Synonyms {
+    /// 空の表を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 食費・交通費・日用品・娯楽費・光熱費のよく使う語を収めた表を作成
+    pub fn builtin() -> Self {
+        BUILTIN_SYNONYMS
+            .iter()
+            .flat_map(|(genre, words)| words.split_whitespace().map(move |word| (word, *genre)))
+            .fold(Self::new(), |synonyms, (word, genre)| {
+                synonyms.synonym(word, genre)
+            })
+    }
+
+    /// 語とジャンルの対応を追加
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - ジャンルを表す語（大文字・小文字、全角・半角を区別しない）
+    /// * `genre` - ジャンル
+    pub fn synonym(mut self, word: impl Into<String>, genre: impl Into<String>) -> Self {
+        let genres = self.entries.entry(word.into()).or_default();
+        let genre = genre.into();
+        if !genres.contains(&genre) {
+            genres.push(genre);
+        }
+        self
+    }
+
+    /// 別の表の対応を追加
+    pub fn extend(mut self, other: Synonyms) -> Self {
+        for (word, genres) in other.entries {
+            for genre in genres {
+                self = self.synonym(word.clone(), genre);
+            }
+        }
+        self
+    }
+
+    /// 語に対応するジャンルの一覧
+    pub fn genres_for(&self, word: &str) -> Vec<&str> {
+        let word = fold(word);
+        self.entries
+            .iter()
+            .filter(|(key, _)| fold(key) == word)
+            .flat_map(|(_, genres)| genres.iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// ジャンルの候補を見つけた方法（後のものほど確か）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    /// 語の一部がジャンル名または同義語と一致
+    Partial,
+    /// 同義語の表と一致
+    Synonym,
+    /// ジャンル名と一致
+    Exact,
+}
+
+/// ジャンルの候補
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenreCandidate {
+    /// ジャンル
+    pub genre: String,
+    /// 手がかりになった入力中の語
+    pub word: String,
+    /// 見つけた方法
+    pub kind: MatchKind,
+}
+
+/// 簡易入力の解釈結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickEntry {
+    /// 日付（指定がない場合は今日）
+    pub date: NaiveDate,
+    /// 金額
+    pub amount: i32,
+    /// ジャンル（1つに決められない場合は `None`）
+    pub genre: Option<String>,
+    /// ジャンルの候補（確かなものから順に）
+    pub candidates: Vec<GenreCandidate>,
+    /// 日付・金額・ジャンルのいずれとしても解釈できなかった語
+    pub unmatched: Vec<String>,
+}
+
+impl QuickEntry {
+    /// ジャンルを1つに決められなかったかどうか
+    pub fn is_ambiguous(&self) -> bool {
+        self.genre.is_none()
+    }
+
+    /// 支出データにする（ジャンルが決まっていない場合は `None`）
+    pub fn to_expense(&self) -> Option<Expense> {
+        let genre = self.genre.clone()?;
+        Some(Expense::new(
+            self.date.format(DATE_FORMAT).to_string(),
+            genre,
+            self.amount,
+        ))
+    }
+}
+
+/// 簡易入力の解釈器
+#[derive(Debug, Clone)]
+pub struct QuickEntryParser {
+    genres: Vec<String>,
+    synonyms: Synonyms,
+    default_genre: Option<String>,
+}
+
+impl QuickEntryParser {
+    /// サーバー上のジャンルと組み込みの同義語で解釈器を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `genres` - サーバー上のジャンル一覧（`get_genres` の結果）
+    pub fn new(genres: &[GenreWithId]) -> Self {
+        Self {
+            genres: genres.iter().map(|genre| genre.name.clone()).collect(),
+            synonyms: Synonyms::builtin(),
+            default_genre: None,
+        }
+    }
+
+    /// 同義語の表を指定（組み込みの表を置き換える）
+    ///
+    /// 組み込みの表に追加する場合は `Synonyms::builtin().extend(...)` を指定します。
+    pub fn synonyms(mut self, synonyms: Synonyms) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// ジャンルの手がかりがない場合に使うジャンルを指定
+    pub fn default_genre(mut self, genre: impl Into<String>) -> Self {
+        self.default_genre = Some(genre.into());
+        self
+    }
+
+    /// 文を解釈する
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - 入力された文（語は空白または読点で区切る）
+    /// * `today` - 相対的な日付の基準日
+    ///
+    /// # Errors
+    ///
+    /// 金額が見つからない場合や、金額・日付の候補が複数あって決められない場合は
+    /// [`ShishutsukanError::ParseError`] を返します。
+    pub fn parse(&self, input: &str, today: NaiveDate) -> Result<QuickEntry> {
+        let error =
+            |message: &str| ShishutsukanError::ParseError(format!("{}: {}", message, input));
+        let words: Vec<&str> = input
+            .split(|c: char| c.is_whitespace() || c == '、')
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        let mut dates = Vec::new();
+        let mut marked = Vec::new();
+        let mut plain = Vec::new();
+        let mut rest = Vec::new();
+        for (index, word) in words.iter().enumerate() {
+            if is_currency_word(word) {
+                // `480 yen` のように離れた通貨の語は直前の数値の印とする
+                if let Some(position) = plain.iter().position(|(i, _)| *i + 1 == index) {
+                    marked.push(plain.remove(position).1);
+                }
+                continue;
+            }
+            if let Some(date) = parse_date(word, today) {
+                dates.push(date);
+            } else if let Some((amount, has_marker)) = parse_amount(word) {
+                if has_marker {
+                    marked.push(amount);
+                } else {
+                    plain.push((index, amount));
+                }
+            } else {
+                rest.push(*word);
+            }
+        }
+
+        let date = match dates.as_slice() {
+            [] => today,
+            [date] => *date,
+            _ => return Err(error("日付が複数あります")),
+        };
+        let amount = match (marked.as_slice(), plain.as_slice()) {
+            ([amount], _) | ([], [(_, amount)]) => *amount,
+            ([], []) => return Err(error("金額が見つかりません")),
+            _ => return Err(error("金額を1つに決められません")),
+        };
+        if marked.len() == 1 {
+            // 印の付いた金額があれば、ほかの数値はメモとして扱う
+            rest.extend(
+                words
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| plain.iter().any(|(index, _)| index == i))
+                    .map(|(_, word)| *word),
+            );
+        }
+
+        let mut candidates: Vec<GenreCandidate> = Vec::new();
+        let mut unmatched = Vec::new();
+        for word in rest {
+            let found = self.match_word(word);
+            if found.is_empty() {
+                unmatched.push(word.to_string());
+            }
+            for candidate in found {
+                match candidates.iter_mut().find(|c| c.genre == candidate.genre) {
+                    Some(existing) if existing.kind < candidate.kind => *existing = candidate,
+                    Some(_) => {}
+                    None => candidates.push(candidate),
+                }
+            }
+        }
+        candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.kind));
+
+        let genre = match candidates.as_slice() {
+            [] => self.default_genre.clone(),
+            [only] => Some(only.genre.clone()),
+            [first, second, ..] if first.kind > second.kind && first.kind > MatchKind::Partial => {
+                Some(first.genre.clone())
+            }
+            _ => None,
+        };
+
+        Ok(QuickEntry {
+            date,
+            amount,
+            genre,
+            candidates,
+            unmatched,
+        })
+    }
+
+    /// 語に対応するジャンルの候補
+    fn match_word(&self, word: &str) -> Vec<GenreCandidate> {
+        let folded = fold(word);
+        let candidate = |genre: &str, kind| GenreCandidate {
+            genre: genre.to_string(),
+            word: word.to_string(),
+            kind,
+        };
+
+        if let Some(genre) = self.genres.iter().find(|genre| fold(genre) == folded) {
+            return vec![candidate(genre, MatchKind::Exact)];
+        }
+        let synonyms: Vec<GenreCandidate> = self
+            .synonyms
+            .genres_for(word)
+            .into_iter()
+            .filter(|genre| self.genres.iter().any(|g| g == genre))
+            .map(|genre| candidate(genre, MatchKind::Synonym))
+            .collect();
+        if !synonyms.is_empty() {
+            return synonyms;
+        }
+
+        // 「交通」「ランチ代」のように、ジャンル名や同義語の一部だけが一致する語
+        if folded.chars().count() < 2 {
+            return Vec::new();
+        }
+        let overlaps = |key: &str| {
+            let key = fold(key);
+            key.chars().count() >= 2 && (folded.contains(&key) || key.contains(&folded))
+        };
+        let mut partial: Vec<GenreCandidate> = Vec::new();
+        let genres = self.genres.iter().filter(|genre| overlaps(genre));
+        let synonym_genres = self
+            .synonyms
+            .entries
+            .iter()
+            .filter(|(key, _)| overlaps(key))
+            .flat_map(|(_, genres)| genres)
+            .filter(|genre| self.genres.contains(genre));
+        for genre in genres.chain(synonym_genres) {
+            if !partial.iter().any(|c| c.genre == *genre) {
+                partial.push(candidate(genre, MatchKind::Partial));
+            }
+        }
+        partial
+    }
+}
+
+/// 大文字・小文字、全角・半角をそろえる
+fn fold(text: &str) -> String {
+    normalize_width(text).to_lowercase()
+}
+
+fn is_currency_word(word: &str) -> bool {
+    matches!(fold(word).as_str(), "円" | "yen" | "jpy")
+}
+
+/// 金額として解釈する（通貨の印が付いているかどうかも返す）
+fn parse_amount(word: &str) -> Option<(i32, bool)> {
+    let folded = fold(word);
+    let stripped = ["yen", "jpy"]
+        .iter()
+        .find_map(|suffix| folded.strip_suffix(suffix))
+        .unwrap_or(&folded);
+    let has_marker = stripped.len() != folded.len()
+        || stripped.starts_with(['¥', '￥', '\\'])
+        || stripped.ends_with('円');
+    let amount = parse_japanese_amount(stripped)?;
+    (amount > 0).then_some((amount, has_marker))
+}
+
+/// 日付として解釈する
+fn parse_date(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let folded = fold(word);
+    let days_ago = match folded.as_str() {
+        "今日" | "きょう" | "本日" | "today" => Some(0),
+        "昨日" | "きのう" | "yesterday" => Some(1),
+        "一昨日" | "おととい" | "おとつい" => Some(2),
+        _ => folded
+            .strip_suffix("日前")
+            .and_then(|days| days.parse::<u64>().ok()),
+    };
+    if let Some(days) = days_ago {
+        return today.checked_sub_days(Days::new(days));
+    }
+    if folded == "明日" || folded == "あした" || folded == "tomorrow" {
+        return today.checked_add_days(Days::new(1));
+    }
+    if let Some(weekday) = parse_weekday(&folded) {
+        let back =
+            (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return today.checked_sub_days(Days::new(u64::from(back)));
+    }
+    if let Some(date) = parse_japanese_date(&folded) {
+        return Some(date);
+    }
+
+    // 年のない日付は、今日以前で最も近い日とする
+    let parts: Vec<&str> = folded
+        .trim_end_matches('日')
+        .split(['/', '-', '月'])
+        .collect();
+    match parts.as_slice() {
+        [month, day] if folded.contains(['/', '-', '月']) => {
+            let (month, day): (u32, u32) = (month.parse().ok()?, day.parse().ok()?);
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date > today {
+                NaiveDate::from_ymd_opt(today.year() - 1, month, day)
+            } else {
+                Some(date)
+            }
+        }
+        [day] if folded.ends_with('日') => {
+            let day: u32 = day.parse().ok()?;
+            let date = today.with_day(day)?;
+            if date > today {
+                let last_month = today.with_day(1)?.pred_opt()?;
+                last_month.with_day(day)
+            } else {
+                Some(date)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 曜日として解釈する（`月曜`・`月曜日`・`mon`・`Monday` など）
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    const JAPANESE: [&str; 7] = ["月曜", "火曜", "水曜", "木曜", "金曜", "土曜", "日曜"];
+    let japanese = word.strip_suffix('日').unwrap_or(word);
+    match JAPANESE.iter().position(|name| *name == japanese) {
+        Some(index) => Weekday::try_from(index as u8).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn genre_with_id(id: i32, name: &str) -> GenreWithId {
+        GenreWithId {
+            id,
+            name: name.to_string(),
+            created_at: "2025-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // 2025-01-15 は水曜日
+        let today = date(2025, 1, 15);
+        assert_eq!(parse_date("今日", today), Some(today));
+        assert_eq!(parse_date("Yesterday", today), Some(date(2025, 1, 14)));
+        assert_eq!(parse_date("３日前", today), Some(date(2025, 1, 12)));
+        assert_eq!(parse_date("月曜", today), Some(date(2025, 1, 13)));
+        assert_eq!(parse_date("水曜日", today), Some(today));
+        assert_eq!(parse_date("friday", today), Some(date(2025, 1, 10)));
+        assert_eq!(parse_date("1/10", today), Some(date(2025, 1, 10)));
+        assert_eq!(parse_date("12月31日", today), Some(date(2024, 12, 31)));
+        assert_eq!(parse_date("20日", today), Some(date(2024, 12, 20)));
+        assert_eq!(parse_date("2024-06-01", today), Some(date(2024, 6, 1)));
+        assert_eq!(parse_date("ランチ", today), None);
+        assert_eq!(parse_date("850", today), None);
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("850円"), Some((850, true)));
+        assert_eq!(parse_amount("¥1,200"), Some((1200, true)));
+        assert_eq!(parse_amount("￥１，２００"), Some((1200, true)));
+        assert_eq!(parse_amount("480yen"), Some((480, true)));
+        assert_eq!(parse_amount("480"), Some((480, false)));
+        assert_eq!(parse_amount("0"), None);
+        assert_eq!(parse_amount("1/15"), None);
+    }
+
+    fn parser() -> QuickEntryParser {
+        let genres: Vec<GenreWithId> = ["食費", "交通費", "日用品", "光熱費"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| genre_with_id(i as i32 + 1, name))
+            .collect();
+        QuickEntryParser::new(&genres)
+    }
+
+    #[test]
+    fn test_parse_phrases() {
+        let today = date(2025, 1, 20);
+        let parser = parser();
+
+        let entry = parser.parse("1/15 交通費 ¥1,200", today).unwrap();
+        assert_eq!(entry.date, date(2025, 1, 15));
+        assert_eq!(
+            (entry.genre.as_deref(), entry.amount),
+            (Some("交通費"), 1200)
+        );
+        assert_eq!(entry.candidates[0].kind, MatchKind::Exact);
+
+        let entry = parser.parse("coffee 480 today", today).unwrap();
+        assert_eq!((entry.date, entry.amount), (today, 480));
+        assert_eq!(entry.genre.as_deref(), Some("食費"));
+
+        // 通貨の印がある数値を金額とし、ほかの数値は残す
+        let entry = parser.parse("ランチ代 2 人 １６００円", today).unwrap();
+        assert_eq!(entry.amount, 1600);
+        assert_eq!(entry.candidates[0].kind, MatchKind::Partial);
+        assert_eq!(entry.genre.as_deref(), Some("食費"));
+        assert!(entry.unmatched.contains(&"2".to_string()));
+
+        let entry = parser.parse("gas 3000 yen", today).unwrap();
+        assert_eq!(entry.amount, 3000);
+        assert!(entry.is_ambiguous());
+        let genres: Vec<&str> = entry.candidates.iter().map(|c| c.genre.as_str()).collect();
+        assert_eq!(genres, vec!["交通費", "光熱費"]);
+
+        // ジャンル名はあいまいな同義語より優先する
+        let entry = parser.parse("コンビニ 日用品 500", today).unwrap();
+        assert_eq!(entry.genre.as_deref(), Some("日用品"));
+
+        let entry = parser.parse("おみやげ 1000", today).unwrap();
+        assert!(entry.is_ambiguous() && entry.candidates.is_empty());
+        assert_eq!(entry.unmatched, vec!["おみやげ".to_string()]);
+        let entry = parser
+            .clone()
+            .default_genre("食費")
+            .parse("おみやげ 1000", today)
+            .unwrap();
+        assert_eq!(entry.genre.as_deref(), Some("食費"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let today = date(2025, 1, 20);
+        let parser = parser();
+        assert!(parser.parse("ランチ", today).is_err());
+        assert!(parser.parse("ランチ 800 900", today).is_err());
+        assert!(parser.parse("昨日 1/15 ランチ 800", today).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// トークンバケット方式のレートリミッター
+///
+/// クライアントのクローン間で共有され、すべてのリクエストの送信前に
+/// [`RateLimiter::acquire`] で1トークンを消費する。
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// 残りトークン数。待機中のリクエストがある間は負の値になる
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second.is_finite() && requests_per_second > 0.0,
+            "requests_per_second must be a positive finite number"
+        );
+        assert!(burst > 0, "burst must be at least 1");
+
+        Self {
+            requests_per_second,
+            burst: f64::from(burst),
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                updated_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// 1トークンを予約し、送信可能になるまで待機する
+    pub(crate) async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// 1トークンを予約し、送信可能になるまでの待ち時間を返す
+    ///
+    /// 先に予約したリクエストほど早く送信できるよう、トークン不足の場合も
+    /// 残量を負にして予約を確定させる。
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.requests_per_second).min(self.burst);
+        bucket.updated_at = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_is_served_immediately() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve(now), Duration::ZERO);
+        }
+        assert_eq!(limiter.reserve(now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_tokens_refill_over_time() {
+        let limiter = RateLimiter::new(10.0, 1);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve(now + Duration::from_millis(100)),
+            Duration::ZERO
+        );
+    }
+}
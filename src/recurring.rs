@@ -0,0 +1,429 @@
+//! 定期的な支出のテンプレート
+//!
+//! 家賃・サブスクリプション・公共料金などの定期的な支出をテンプレートとして登録し、
+//! 指定した日までの発生分のうちサーバーにまだないものを追加します。
+//!
+//! 追加済みの発生分は、日付・ジャンル・金額が一致するサーバー上の支出データで判定します。
+//! テンプレートごとに追加を終えた日を記録するため、前回から間が空いた場合はその間の
+//! 発生分もまとめて追加し、追加後に手動で削除した支出データを再び追加することもありません。
+//!
+//! ```no_run
+//! use chrono::{Local, NaiveDate};
+//! use shishutsukan::recurring::{RecurringStore, RecurringTemplate, Schedule};
+//! use shishutsukan::ShishutsukanClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = ShishutsukanClient::new("http://localhost:8000");
+//! let mut store = RecurringStore::open("recurring.json")?;
+//! let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+//! store.set(RecurringTemplate::new("家賃", "住居費", 80000, Schedule::MonthlyOnDay(27), start))?;
+//! store.set(RecurringTemplate::new("電気代", "光熱費", 6000, Schedule::EndOfMonth, start))?;
+//!
+//! let report = store.materialize(&client, Local::now().date_naive()).await?;
+//! println!("{}件追加", report.added.len());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod detect;
+
+use crate::backup::ContentIndex;
+use crate::client::ShishutsukanClient;
+use crate::error::Result;
+use crate::models::{Expense, ExpenseWithId};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 発生する日の規則
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// 毎月指定した日（その月にない日は月末）
+    MonthlyOnDay(u32),
+    /// 毎月末
+    EndOfMonth,
+    /// 毎週指定した曜日
+    Weekly(Weekday),
+    /// 毎年指定した月日（2月29日は平年では2月28日）
+    Yearly {
+        /// 月
+        month: u32,
+        /// 日
+        day: u32,
+    },
+}
+
+impl Schedule {
+    /// 期間内に発生する日の一覧（古い順）
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - 期間の初日
+    /// * `until` - 期間の最終日
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use shishutsukan::recurring::Schedule;
+    ///
+    /// let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+    /// assert_eq!(
+    ///     Schedule::MonthlyOnDay(31).occurrences(date(1, 1), date(3, 1)),
+    ///     vec![date(1, 31), date(2, 28)]
+    /// );
+    /// ```
+    pub fn occurrences(&self, from: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        if from > until {
+            return dates;
+        }
+        match *self {
+            Self::Weekly(weekday) => {
+                let offset = (7 + weekday.num_days_from_monday()
+                    - from.weekday().num_days_from_monday())
+                    % 7;
+                let mut date = from + Duration::days(i64::from(offset));
+                while date <= until {
+                    dates.push(date);
+                    date += Duration::days(7);
+                }
+            }
+            Self::MonthlyOnDay(_) | Self::EndOfMonth => {
+                let (mut year, mut month) = (from.year(), from.month());
+                while (year, month) <= (until.year(), until.month()) {
+                    let day = match *self {
+                        Self::MonthlyOnDay(day) => day,
+                        _ => 31,
+                    };
+                    dates.extend(clamped_date(year, month, day));
+                    (year, month) = if month == 12 {
+                        (year + 1, 1)
+                    } else {
+                        (year, month + 1)
+                    };
+                }
+            }
+            Self::Yearly { month, day } => {
+                for year in from.year()..=until.year() {
+                    dates.extend(clamped_date(year, month.clamp(1, 12), day));
+                }
+            }
+        }
+        dates.retain(|date| (from..=until).contains(date));
+        dates
+    }
+}
+
+/// 月の日数を超える日を月末にそろえた日付（存在しない年月の場合は `None`）
+fn clamped_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    (1..=day.clamp(1, 31))
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+/// 定期的な支出のテンプレート
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecurringTemplate {
+    /// テンプレート名（保存先の中で一意）
+    pub name: String,
+    /// ジャンル
+    pub genre: String,
+    /// 金額
+    pub amount: i32,
+    /// 発生する日の規則
+    pub schedule: Schedule,
+    /// 開始日
+    pub starts_on: NaiveDate,
+    /// 終了日
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ends_on: Option<NaiveDate>,
+    /// 追加を終えた日（この日までの発生分は追加しない）
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub materialized_through: Option<NaiveDate>,
+}
+
+impl RecurringTemplate {
+    /// 新しいテンプレートを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - テンプレート名
+    /// * `genre` - ジャンル
+    /// * `amount` - 金額
+    /// * `schedule` - 発生する日の規則
+    /// * `starts_on` - 開始日
+    pub fn new(
+        name: impl Into<String>,
+        genre: impl Into<String>,
+        amount: i32,
+        schedule: Schedule,
+        starts_on: NaiveDate,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            genre: genre.into(),
+            amount,
+            schedule,
+            starts_on,
+            ends_on: None,
+            materialized_through: None,
+        }
+    }
+
+    /// 終了日を指定
+    pub fn ends_on(mut self, date: NaiveDate) -> Self {
+        self.ends_on = Some(date);
+        self
+    }
+
+    /// まだ追加していない発生分の日付の一覧（古い順）
+    ///
+    /// # Arguments
+    ///
+    /// * `until` - この日までの発生分を対象にする
+    pub fn pending_dates(&self, until: NaiveDate) -> Vec<NaiveDate> {
+        let from = match self.materialized_through.and_then(|date| date.succ_opt()) {
+            Some(next) => next.max(self.starts_on),
+            None => self.starts_on,
+        };
+        let until = self.ends_on.map_or(until, |end| end.min(until));
+        self.schedule.occurrences(from, until)
+    }
+
+    /// 発生日の支出データ
+    pub fn expense_on(&self, date: NaiveDate) -> Expense {
+        Expense::new(date.to_string(), self.genre.clone(), self.amount)
+    }
+}
+
+/// テンプレートから追加する予定の支出データ
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedExpense {
+    /// テンプレート名
+    pub template: String,
+    /// 支出データ
+    pub expense: Expense,
+    /// サーバーに同じ内容の支出データがある場合はそのID
+    pub existing: Option<i32>,
+}
+
+/// 追加する予定の発生分を求める
+///
+/// 同じ内容の発生分が複数ある場合も、サーバー上の支出データは1件につき1つの発生分に
+/// だけ対応させます。
+///
+/// # Arguments
+///
+/// * `templates` - テンプレートの一覧
+/// * `existing` - サーバー上の支出データ
+/// * `until` - この日までの発生分を対象にする
+pub fn plan(
+    templates: &[RecurringTemplate],
+    existing: &[ExpenseWithId],
+    until: NaiveDate,
+) -> Vec<PlannedExpense> {
+    let mut index = ContentIndex::new(existing);
+    templates
+        .iter()
+        .flat_map(|template| {
+            template
+                .pending_dates(until)
+                .into_iter()
+                .map(move |date| (template, template.expense_on(date)))
+        })
+        .map(|(template, expense)| PlannedExpense {
+            template: template.name.clone(),
+            existing: index.claim(&expense),
+            expense,
+        })
+        .collect()
+}
+
+/// 追加の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaterializeReport {
+    /// 追加した支出データ
+    pub added: Vec<(String, Expense)>,
+    /// サーバーにすでにあったため追加しなかった支出データ
+    pub existing: Vec<(String, Expense)>,
+    /// 追加に失敗した支出データとエラーの内容
+    pub failed: Vec<(String, Expense, String)>,
+}
+
+// MARK: - Store
+
+/// テンプレートの保存先
+///
+/// ファイルを指定した場合は更新のたびにJSONとして保存されます。
+#[derive(Debug, Clone, Default)]
+pub struct RecurringStore {
+    path: Option<PathBuf>,
+    templates: Vec<RecurringTemplate>,
+}
+
+impl RecurringStore {
+    /// メモリ上だけに保持する保存先を作成
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// ファイルに保存されるテンプレートを開く
+    ///
+    /// ファイルが存在しない場合は空の状態で開き、最初の更新時にファイルを作成します。
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - テンプレートファイルのパス
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let templates = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            templates,
+        })
+    }
+
+    /// テンプレートの一覧
+    pub fn templates(&self) -> &[RecurringTemplate] {
+        &self.templates
+    }
+
+    /// テンプレートを登録する（同じ名前のテンプレートがあれば置き換える）
+    pub fn set(&mut self, template: RecurringTemplate) -> Result<()> {
+        match self.templates.iter_mut().find(|t| t.name == template.name) {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+        self.save()
+    }
+
+    /// テンプレートを削除する
+    ///
+    /// # Returns
+    ///
+    /// 削除した場合は `true`
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        let before = self.templates.len();
+        self.templates.retain(|t| t.name != name);
+        let removed = self.templates.len() != before;
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// 指定した日までの発生分のうち、サーバーにないものを追加する
+    ///
+    /// 追加できたテンプレートは追加を終えた日を `until` に進めます。追加に失敗した
+    /// 発生分があるテンプレートは、その前日までに進め、次回に再び追加を試みます。
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - APIクライアント
+    /// * `until` - この日までの発生分を追加する
+    ///
+    /// # Errors
+    ///
+    /// 支出データの取得またはテンプレートの保存に失敗した場合にエラーを返します。
+    /// 個々の追加の失敗は [`MaterializeReport::failed`] に記録します。
+    pub async fn materialize(
+        &mut self,
+        client: &ShishutsukanClient,
+        until: NaiveDate,
+    ) -> Result<MaterializeReport> {
+        let existing = client.get_expenses().await?;
+        let mut report = MaterializeReport::default();
+        let mut first_failure: Vec<(String, NaiveDate)> = Vec::new();
+
+        for planned in plan(&self.templates, &existing, until) {
+            let PlannedExpense {
+                template,
+                expense,
+                existing,
+            } = planned;
+            if existing.is_some() {
+                report.existing.push((template, expense));
+                continue;
+            }
+            match client.add_expense(&expense).await {
+                Ok(_) => report.added.push((template, expense)),
+                Err(e) => {
+                    if !first_failure.iter().any(|(name, _)| *name == template) {
+                        if let Some(date) = expense.naive_date() {
+                            first_failure.push((template.clone(), date));
+                        }
+                    }
+                    report.failed.push((template, expense, e.to_string()));
+                }
+            }
+        }
+
+        for template in &mut self.templates {
+            let through = match first_failure
+                .iter()
+                .find(|(name, _)| *name == template.name)
+            {
+                Some((_, date)) => date.pred_opt(),
+                None => Some(until),
+            };
+            if through > template.materialized_through {
+                template.materialized_through = through;
+            }
+        }
+        self.save()?;
+        Ok(report)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&self.templates)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_schedules() {
+        assert_eq!(
+            Schedule::EndOfMonth.occurrences(date(2024, 1, 31), date(2024, 3, 30)),
+            vec![date(2024, 1, 31), date(2024, 2, 29)]
+        );
+        assert_eq!(
+            Schedule::Weekly(Weekday::Fri).occurrences(date(2025, 1, 1), date(2025, 1, 17)),
+            vec![date(2025, 1, 3), date(2025, 1, 10), date(2025, 1, 17)]
+        );
+        assert_eq!(
+            Schedule::Yearly { month: 2, day: 29 }.occurrences(date(2024, 3, 1), date(2026, 1, 1)),
+            vec![date(2025, 2, 28)]
+        );
+    }
+
+    #[test]
+    fn test_clamped_date() {
+        assert_eq!(clamped_date(2025, 2, 31), Some(date(2025, 2, 28)));
+        assert_eq!(clamped_date(2025, 4, 0), Some(date(2025, 4, 1)));
+        // 存在しない年月は1970-01-01などにせず、発生分から除く
+        assert_eq!(clamped_date(2025, 13, 1), None);
+        assert_eq!(clamped_date(i32::MAX, 1, 1), None);
+    }
+}
@@ -0,0 +1,326 @@
+//! 履歴からの定期的な支出の検出
+//!
+//! 同じジャンルで金額が近い支出データが一定の間隔（毎週・毎月・毎年）で繰り返されている
+//! ものを探し、周期・確からしさ・次に発生する見込みの日を求めます。検出したパターンは
+//! [`RecurringTemplate`] に変換できるため、登録し忘れているサブスクリプションなどを
+//! 見つけるのに使えます。
+//!
+//! ```
+//! use shishutsukan::recurring::detect::{detect, DetectionOptions};
+//! use shishutsukan::recurring::Schedule;
+//! use shishutsukan::ExpenseWithId;
+//!
+//! let expenses: Vec<ExpenseWithId> = (1..=4)
+//!     .map(|month| ExpenseWithId {
+//!         id: month,
+//!         date: format!("2025-{:02}-05", month),
+//!         genre: "サブスク".to_string(),
+//!         amount: 980,
+//!     })
+//!     .collect();
+//!
+//! let patterns = detect(&expenses, &DetectionOptions::default());
+//! assert_eq!(patterns[0].schedule, Schedule::MonthlyOnDay(5));
+//! assert_eq!(patterns[0].next_expected.to_string(), "2025-05-05");
+//!
+//! let template = patterns[0].to_template("動画配信");
+//! assert_eq!(template.materialized_through, Some(patterns[0].last_date));
+//! ```
+
+use super::{RecurringTemplate, Schedule};
+use crate::models::ExpenseWithId;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// 検出の条件
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DetectionOptions {
+    /// パターンとみなす最小の件数（既定値: 3）
+    pub min_occurrences: usize,
+    /// 同じパターンとみなす金額の差の割合（既定値: 0.1）
+    pub amount_tolerance: f64,
+    /// 報告する最小の確からしさ（既定値: 0.6）
+    pub min_confidence: f64,
+}
+
+impl Default for DetectionOptions {
+    fn default() -> Self {
+        Self {
+            min_occurrences: 3,
+            amount_tolerance: 0.1,
+            min_confidence: 0.6,
+        }
+    }
+}
+
+impl DetectionOptions {
+    /// パターンとみなす最小の件数を指定
+    pub fn min_occurrences(mut self, count: usize) -> Self {
+        self.min_occurrences = count;
+        self
+    }
+
+    /// 同じパターンとみなす金額の差の割合を指定
+    pub fn amount_tolerance(mut self, tolerance: f64) -> Self {
+        self.amount_tolerance = tolerance;
+        self
+    }
+
+    /// 報告する最小の確からしさを指定
+    pub fn min_confidence(mut self, confidence: f64) -> Self {
+        self.min_confidence = confidence;
+        self
+    }
+}
+
+/// 検出した定期的な支出のパターン
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurringPattern {
+    /// ジャンル
+    pub genre: String,
+    /// 金額（中央値）
+    pub amount: i32,
+    /// 最小金額
+    pub min_amount: i32,
+    /// 最大金額
+    pub max_amount: i32,
+    /// 推定した発生する日の規則
+    pub schedule: Schedule,
+    /// 発生間隔の中央値（日）
+    pub interval_days: i64,
+    /// 確からしさ（0〜1、周期に合う間隔の割合）
+    pub confidence: f64,
+    /// パターンに含まれる支出データのID（日付順）
+    pub expense_ids: Vec<i32>,
+    /// 最初の発生日
+    pub first_date: NaiveDate,
+    /// 最後の発生日
+    pub last_date: NaiveDate,
+    /// 次に発生する見込みの日
+    pub next_expected: NaiveDate,
+}
+
+impl RecurringPattern {
+    /// 次に発生する見込みの日を過ぎても発生していないかどうか
+    ///
+    /// 解約したサブスクリプションなど、すでに終わっている可能性があるパターンの判定に使います。
+    ///
+    /// # Arguments
+    ///
+    /// * `as_of` - 判定する日
+    /// * `grace_days` - 見込みの日から猶予する日数
+    pub fn is_lapsed(&self, as_of: NaiveDate, grace_days: i64) -> bool {
+        (as_of - self.next_expected).num_days() > grace_days
+    }
+
+    /// 定期的な支出のテンプレートに変換する
+    ///
+    /// 最初の発生日を開始日とし、最後の発生日までを追加済みとするため、
+    /// [`RecurringStore::materialize`](super::RecurringStore::materialize) で
+    /// 履歴の発生分が重複して追加されることはありません。
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - テンプレート名
+    pub fn to_template(&self, name: impl Into<String>) -> RecurringTemplate {
+        let mut template = RecurringTemplate::new(
+            name,
+            self.genre.clone(),
+            self.amount,
+            self.schedule,
+            self.first_date,
+        );
+        template.materialized_through = Some(self.last_date);
+        template
+    }
+}
+
+/// 周期の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cadence {
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Cadence {
+    fn from_interval(days: i64) -> Option<Self> {
+        [Self::Weekly, Self::Monthly, Self::Yearly]
+            .into_iter()
+            .find(|cadence| cadence.accepts(days))
+    }
+
+    /// 周期に合う間隔（日）かどうか
+    fn accepts(self, days: i64) -> bool {
+        match self {
+            Self::Weekly => (6..=8).contains(&days),
+            Self::Monthly => (26..=35).contains(&days),
+            Self::Yearly => (350..=380).contains(&days),
+        }
+    }
+}
+
+/// 支出データから定期的な支出のパターンを検出する
+///
+/// ジャンルごとに金額の近い支出データをまとめ、発生間隔の中央値が毎週・毎月・毎年の
+/// いずれかに当たるものをパターンとします。同じ日の支出データは1回の発生とみなし、
+/// 日付を解釈できない支出データは無視します。結果は確からしさの高い順に並べます。
+///
+/// # Arguments
+///
+/// * `expenses` - 支出データの一覧
+/// * `options` - 検出の条件
+pub fn detect(expenses: &[ExpenseWithId], options: &DetectionOptions) -> Vec<RecurringPattern> {
+    let mut by_genre: BTreeMap<&str, Vec<(&ExpenseWithId, NaiveDate)>> = BTreeMap::new();
+    for expense in expenses {
+        if let Some(date) = expense.naive_date() {
+            by_genre
+                .entry(&expense.genre)
+                .or_default()
+                .push((expense, date));
+        }
+    }
+
+    let mut patterns: Vec<RecurringPattern> = by_genre
+        .into_iter()
+        .flat_map(|(genre, mut rows)| {
+            rows.sort_by_key(|(expense, _)| (expense.amount, expense.id));
+            cluster_by_amount(rows, options.amount_tolerance)
+                .into_iter()
+                .filter_map(move |cluster| analyze(genre, cluster, options))
+        })
+        .collect();
+    patterns.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.genre.cmp(&b.genre))
+            .then(a.amount.cmp(&b.amount))
+    });
+    patterns
+}
+
+/// 金額順に並んだ支出データを、先頭の金額からの差が許容範囲内のまとまりに分ける
+fn cluster_by_amount(
+    rows: Vec<(&ExpenseWithId, NaiveDate)>,
+    tolerance: f64,
+) -> Vec<Vec<(&ExpenseWithId, NaiveDate)>> {
+    let mut clusters: Vec<Vec<(&ExpenseWithId, NaiveDate)>> = Vec::new();
+    for row in rows {
+        match clusters.last_mut() {
+            Some(cluster)
+                if f64::from(row.0.amount)
+                    <= f64::from(cluster[0].0.amount) * (1.0 + tolerance) =>
+            {
+                cluster.push(row)
+            }
+            _ => clusters.push(vec![row]),
+        }
+    }
+    clusters
+}
+
+fn analyze(
+    genre: &str,
+    mut rows: Vec<(&ExpenseWithId, NaiveDate)>,
+    options: &DetectionOptions,
+) -> Option<RecurringPattern> {
+    rows.sort_by_key(|(expense, date)| (*date, expense.id));
+    rows.dedup_by_key(|(_, date)| *date);
+    if rows.len() < options.min_occurrences.max(2) {
+        return None;
+    }
+
+    let dates: Vec<NaiveDate> = rows.iter().map(|(_, date)| *date).collect();
+    let intervals: Vec<i64> = dates
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_days())
+        .collect();
+    let interval_days = median(&intervals);
+    let cadence = Cadence::from_interval(interval_days)?;
+    let regular = intervals
+        .iter()
+        .filter(|days| cadence.accepts(**days))
+        .count();
+    let confidence = regular as f64 / intervals.len() as f64;
+    if confidence < options.min_confidence {
+        return None;
+    }
+
+    let schedule = infer_schedule(cadence, &dates)?;
+    let last_date = *dates.last()?;
+    let next_expected = schedule
+        .occurrences(last_date.succ_opt()?, last_date + Duration::days(400))
+        .first()
+        .copied()?;
+    let amounts: Vec<i64> = rows
+        .iter()
+        .map(|(expense, _)| i64::from(expense.amount))
+        .collect();
+
+    Some(RecurringPattern {
+        genre: genre.to_string(),
+        amount: median(&amounts) as i32,
+        min_amount: rows.iter().map(|(expense, _)| expense.amount).min()?,
+        max_amount: rows.iter().map(|(expense, _)| expense.amount).max()?,
+        schedule,
+        interval_days,
+        confidence,
+        expense_ids: rows.iter().map(|(expense, _)| expense.id).collect(),
+        first_date: dates[0],
+        last_date,
+        next_expected,
+    })
+}
+
+fn infer_schedule(cadence: Cadence, dates: &[NaiveDate]) -> Option<Schedule> {
+    let schedule = match cadence {
+        Cadence::Weekly => Schedule::Weekly(most_common(dates.iter().map(|date| date.weekday()))?),
+        Cadence::Monthly => {
+            // 半数より多くが月末に発生していれば月末、そうでなければ最も多い日
+            let month_ends = dates.iter().filter(|date| is_month_end(**date)).count();
+            if month_ends * 2 > dates.len() {
+                Schedule::EndOfMonth
+            } else {
+                Schedule::MonthlyOnDay(most_common(dates.iter().map(|date| date.day()))?)
+            }
+        }
+        Cadence::Yearly => {
+            let last = dates.last()?;
+            Schedule::Yearly {
+                month: last.month(),
+                day: last.day(),
+            }
+        }
+    };
+    Some(schedule)
+}
+
+fn is_month_end(date: NaiveDate) -> bool {
+    date.succ_opt()
+        .is_some_and(|next| next.month() != date.month())
+}
+
+/// 最も多い値（同数の場合は最後に現れた値）
+fn most_common<T: Copy + Eq + Hash>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: HashMap<T, (usize, usize)> = HashMap::new();
+    for (position, value) in values.enumerate() {
+        let entry = counts.entry(value).or_default();
+        entry.0 += 1;
+        entry.1 = position;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, (count, position))| (*count, *position))
+        .map(|(value, _)| value)
+}
+
+fn median(values: &[i64]) -> i64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    match sorted.len() {
+        0 => 0,
+        // 奇数個の場合は2つの添字がどちらも中央の値を指す
+        len => (sorted[(len - 1) / 2] + sorted[len / 2]) / 2,
+    }
+}
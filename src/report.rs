@@ -0,0 +1,410 @@
+//! 支出データの集計レポート
+//!
+//! `get_expenses` の結果を月・年・ジャンルごとに集計し、件数・合計・平均・最小・最大を
+//! 型付きの構造体として返します。テキストの表やJSONとして出力することもできます。
+//!
+//! ```
+//! use shishutsukan::report::Report;
+//! use shishutsukan::ExpenseWithId;
+//!
+//! let expenses = vec![
+//!     ExpenseWithId { id: 1, date: "2025-01-15".to_string(), genre: "食費".to_string(), amount: 1200 },
+//!     ExpenseWithId { id: 2, date: "2025-01-20".to_string(), genre: "交通費".to_string(), amount: 300 },
+//!     ExpenseWithId { id: 3, date: "2025-02-01".to_string(), genre: "食費".to_string(), amount: 800 },
+//! ];
+//! let report = Report::new(&expenses);
+//!
+//! assert_eq!(report.months[0].period, "2025-01");
+//! assert_eq!(report.months[0].stats.total, 1500);
+//! assert_eq!(report.genres[0].genre, "食費");
+//! assert_eq!(report.genres[0].month_total("2025-02"), 800);
+//! println!("{}", report.render_genres());
+//! ```
+
+use crate::error::Result;
+use crate::models::ExpenseWithId;
+use chrono::Datelike;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 件数・合計・平均・最小・最大
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Stats {
+    /// 件数
+    pub count: usize,
+    /// 合計金額
+    pub total: i64,
+    /// 平均金額
+    pub average: f64,
+    /// 最小金額
+    pub min: i32,
+    /// 最大金額
+    pub max: i32,
+}
+
+impl Stats {
+    /// 金額の一覧から集計する（空の場合は `None`）
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shishutsukan::report::Stats;
+    ///
+    /// let stats = Stats::from_amounts([100, 300, 200]).unwrap();
+    /// assert_eq!((stats.count, stats.total, stats.min, stats.max), (3, 600, 100, 300));
+    /// assert_eq!(stats.average, 200.0);
+    /// assert!(Stats::from_amounts([]).is_none());
+    /// ```
+    pub fn from_amounts(amounts: impl IntoIterator<Item = i32>) -> Option<Self> {
+        let mut amounts = amounts.into_iter();
+        let first = amounts.next()?;
+        let mut stats = Self {
+            count: 1,
+            total: i64::from(first),
+            average: 0.0,
+            min: first,
+            max: first,
+        };
+        for amount in amounts {
+            stats.count += 1;
+            stats.total += i64::from(amount);
+            stats.min = stats.min.min(amount);
+            stats.max = stats.max.max(amount);
+        }
+        stats.average = stats.total as f64 / stats.count as f64;
+        Some(stats)
+    }
+}
+
+/// 期間（月または年）ごとの集計
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodSummary {
+    /// 期間（月は `2025-01`、年は `2025` の形式）
+    pub period: String,
+    /// 集計
+    pub stats: Stats,
+}
+
+/// ジャンルごとの集計
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenreSummary {
+    /// ジャンル名
+    pub genre: String,
+    /// 全期間の集計
+    pub stats: Stats,
+    /// 月ごとの集計（古い順、支出のある月のみ）
+    pub months: Vec<PeriodSummary>,
+}
+
+impl GenreSummary {
+    /// 月の合計金額（支出がない月は0）
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - 月（`2025-01` の形式）
+    pub fn month_total(&self, month: &str) -> i64 {
+        self.months
+            .iter()
+            .find(|summary| summary.period == month)
+            .map_or(0, |summary| summary.stats.total)
+    }
+}
+
+/// 集計レポート
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Report {
+    /// 全期間の集計（支出データがない場合は `None`）
+    pub overall: Option<Stats>,
+    /// 年ごとの集計（古い順）
+    pub years: Vec<PeriodSummary>,
+    /// 月ごとの集計（古い順、支出のある月のみ）
+    pub months: Vec<PeriodSummary>,
+    /// ジャンルごとの集計（合計金額の多い順）
+    pub genres: Vec<GenreSummary>,
+    /// 日付を解釈できず集計に含めなかった支出データの件数
+    pub skipped: usize,
+}
+
+impl Report {
+    /// 支出データを集計する
+    ///
+    /// # Arguments
+    ///
+    /// * `expenses` - 支出データの一覧
+    pub fn new(expenses: &[ExpenseWithId]) -> Self {
+        let mut report = Self::default();
+        let mut all = Vec::new();
+        let mut years: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+        let mut months: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+        let mut genres: BTreeMap<&str, BTreeMap<String, Vec<i32>>> = BTreeMap::new();
+
+        for expense in expenses {
+            let Some(date) = expense.naive_date() else {
+                report.skipped += 1;
+                continue;
+            };
+            let month = format!("{:04}-{:02}", date.year(), date.month());
+            all.push(expense.amount);
+            years
+                .entry(format!("{:04}", date.year()))
+                .or_default()
+                .push(expense.amount);
+            months
+                .entry(month.clone())
+                .or_default()
+                .push(expense.amount);
+            genres
+                .entry(&expense.genre)
+                .or_default()
+                .entry(month)
+                .or_default()
+                .push(expense.amount);
+        }
+
+        report.overall = Stats::from_amounts(all);
+        report.years = summarize(years);
+        report.months = summarize(months);
+        report.genres = genres
+            .into_iter()
+            .filter_map(|(genre, months)| {
+                let stats = Stats::from_amounts(months.values().flatten().copied())?;
+                Some(GenreSummary {
+                    genre: genre.to_string(),
+                    stats,
+                    months: summarize(months),
+                })
+            })
+            .collect();
+        report.genres.sort_by(|a, b| {
+            b.stats
+                .total
+                .cmp(&a.stats.total)
+                .then(a.genre.cmp(&b.genre))
+        });
+        report
+    }
+
+    /// 支出のある月の一覧（古い順）
+    pub fn month_keys(&self) -> Vec<&str> {
+        self.months
+            .iter()
+            .map(|summary| summary.period.as_str())
+            .collect()
+    }
+
+    /// JSONとして出力する
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// 月ごとの集計をテキストの表として出力する
+    pub fn render_monthly(&self) -> String {
+        render_periods("月", &self.months)
+    }
+
+    /// 年ごとの集計をテキストの表として出力する
+    pub fn render_yearly(&self) -> String {
+        render_periods("年", &self.years)
+    }
+
+    /// ジャンル×月の合計金額をテキストの表として出力する
+    pub fn render_genres(&self) -> String {
+        let months = self.month_keys();
+        let mut headers = vec!["ジャンル".to_string()];
+        headers.extend(months.iter().map(|month| month.to_string()));
+        headers.push("合計".to_string());
+
+        let mut table = TextTable::new(headers);
+        for genre in &self.genres {
+            let mut row = vec![genre.genre.clone()];
+            row.extend(
+                months
+                    .iter()
+                    .map(|month| format_yen(genre.month_total(month))),
+            );
+            row.push(format_yen(genre.stats.total));
+            table.push(row);
+        }
+        let mut total_row = vec!["合計".to_string()];
+        total_row.extend(
+            self.months
+                .iter()
+                .map(|summary| format_yen(summary.stats.total)),
+        );
+        total_row.push(format_yen(self.overall.map_or(0, |stats| stats.total)));
+        table.push(total_row);
+        table.render()
+    }
+}
+
+fn summarize(groups: BTreeMap<String, Vec<i32>>) -> Vec<PeriodSummary> {
+    groups
+        .into_iter()
+        .filter_map(|(period, amounts)| {
+            Some(PeriodSummary {
+                period,
+                stats: Stats::from_amounts(amounts)?,
+            })
+        })
+        .collect()
+}
+
+fn render_periods(label: &str, periods: &[PeriodSummary]) -> String {
+    let headers = [label, "件数", "合計", "平均", "最小", "最大"];
+    let mut table = TextTable::new(headers.iter().map(|header| header.to_string()).collect());
+    for summary in periods {
+        let stats = &summary.stats;
+        table.push(vec![
+            summary.period.clone(),
+            stats.count.to_string(),
+            format_yen(stats.total),
+            format_yen(stats.average.round() as i64),
+            format_yen(i64::from(stats.min)),
+            format_yen(i64::from(stats.max)),
+        ]);
+    }
+    table.render()
+}
+
+// MARK: - Text Table
+
+/// 等幅フォント向けのテキストの表
+///
+/// 全角文字は2文字分の幅として桁をそろえます。1列目は左寄せ、それ以外の列は右寄せです。
+///
+/// # Examples
+///
+/// ```
+/// use shishutsukan::report::TextTable;
+///
+/// let mut table = TextTable::new(vec!["ジャンル".to_string(), "合計".to_string()]);
+/// table.push(vec!["食費".to_string(), "¥1,200".to_string()]);
+/// assert_eq!(
+///     table.render(),
+///     "ジャンル    合計\n--------  ------\n食費      ¥1,200\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextTable {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl TextTable {
+    /// 見出しを指定して表を作成
+    pub fn new(headers: Vec<String>) -> Self {
+        Self {
+            headers,
+            rows: Vec::new(),
+        }
+    }
+
+    /// 行を追加
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// 表を文字列にする
+    pub fn render(&self) -> String {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|col| {
+                std::iter::once(&self.headers)
+                    .chain(&self.rows)
+                    .filter_map(|row| row.get(col))
+                    .map(|cell| display_width(cell))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let mut output = String::new();
+        let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        for row in std::iter::once(&self.headers)
+            .chain(std::iter::once(&separator))
+            .chain(&self.rows)
+        {
+            let cells: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(col, width)| {
+                    let cell = row.get(col).map(String::as_str).unwrap_or("");
+                    let padding = " ".repeat(width - display_width(cell));
+                    if col == 0 {
+                        format!("{}{}", cell, padding)
+                    } else {
+                        format!("{}{}", padding, cell)
+                    }
+                })
+                .collect();
+            output.push_str(cells.join("  ").trim_end());
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// 金額を `¥1,234` の形式にする
+///
+/// # Examples
+///
+/// ```
+/// use shishutsukan::report::format_yen;
+///
+/// assert_eq!(format_yen(1234567), "¥1,234,567");
+/// assert_eq!(format_yen(-500), "-¥500");
+/// assert_eq!(format_yen(1000), "¥1,000");
+/// assert_eq!(format_yen(0), "¥0");
+/// ```
+pub fn format_yen(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let grouped = digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(",");
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{}¥{}", sign, grouped)
+}
+
+/// 等幅フォントでの表示幅（全角文字は2）
+pub(crate) fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("食費"), 4);
+        assert_eq!(display_width("ｶﾅ"), 2);
+        assert_eq!(display_width("¥1,200"), 6);
+    }
+}
@@ -0,0 +1,684 @@
+//! 取り込んだ明細・レシートへのジャンルの自動割り当て
+//!
+//! 説明文の正規表現・金額の範囲・曜日・支払先を条件とする規則を上から順に照合し、
+//! 最初に一致した規則のジャンルを割り当てます。規則はJSONファイルに書いておき、
+//! サーバー上に存在しないジャンルを指す規則は使いません。
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "name": "平日の昼食", "genre": "食費", "merchant": "セブン", "max_amount": 1500,
+//!       "weekdays": ["Mon", "Tue", "Wed", "Thu", "Fri"] },
+//!     { "name": "交通系IC", "genre": "交通費", "description": "(?i)suica|pasmo|ＪＲ" },
+//!     { "genre": "光熱費", "description": "電気|ガス|水道", "min_amount": 1000 }
+//!   ]
+//! }
+//! ```
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use shishutsukan::rules::{Item, RuleSet};
+//! use shishutsukan::GenreWithId;
+//!
+//! let rules = RuleSet::from_json(r#"{"rules": [
+//!     {"name": "交通系IC", "genre": "交通費", "description": "(?i)suica"}
+//! ]}"#).unwrap();
+//! let genres = vec![GenreWithId {
+//!     id: 1,
+//!     name: "交通費".to_string(),
+//!     created_at: "2025-01-01 00:00:00".to_string(),
+//! }];
+//!
+//! let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+//! let items = vec![
+//!     Item::new(date, 3000, "SUICA チャージ"),
+//!     Item::new(date, 1200, "書店"),
+//! ];
+//! let result = rules.categorize(&items, &genres);
+//! assert_eq!(result.items[0].genre.as_deref(), Some("交通費"));
+//! assert_eq!(result.stats.unmatched, 1);
+//!
+//! // どの規則が一致したか、しなかった規則はどの条件で外れたかを確かめる
+//! println!("{}", rules.explain(&items[1], &genres));
+//! ```
+
+use crate::error::{Result, ShishutsukanError};
+use crate::import::normalize_width;
+use crate::import::statement::StatementTransaction;
+use crate::models::{Expense, GenreWithId, DATE_FORMAT};
+use chrono::{Datelike, NaiveDate, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// ジャンルを割り当てる対象の明細・レシートの1件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Item {
+    /// 日付
+    pub date: NaiveDate,
+    /// 金額（正の値）
+    pub amount: i64,
+    /// 説明文（明細の摘要やレシートの品目など）
+    pub description: String,
+    /// 支払先
+    pub merchant: Option<String>,
+}
+
+impl Item {
+    /// 支払先のない明細を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - 日付
+    /// * `amount` - 金額
+    /// * `description` - 説明文
+    pub fn new(date: NaiveDate, amount: i64, description: impl Into<String>) -> Self {
+        Self {
+            date,
+            amount,
+            description: description.into(),
+            merchant: None,
+        }
+    }
+
+    /// 支払先を指定
+    pub fn merchant(mut self, merchant: impl Into<String>) -> Self {
+        self.merchant = Some(merchant.into());
+        self
+    }
+
+    /// 照合の対象にする支払先（なければ説明文）
+    fn key(&self) -> &str {
+        self.merchant.as_deref().unwrap_or(&self.description)
+    }
+}
+
+impl From<&StatementTransaction> for Item {
+    /// 明細の取引から作成（支払先とメモを説明文にし、金額は絶対値にする）
+    fn from(transaction: &StatementTransaction) -> Self {
+        let description = if transaction.memo.is_empty() {
+            transaction.payee.clone()
+        } else {
+            format!("{} {}", transaction.payee, transaction.memo)
+        };
+        Self {
+            date: transaction.date,
+            amount: transaction.amount.abs(),
+            description,
+            merchant: Some(transaction.payee.clone()).filter(|payee| !payee.is_empty()),
+        }
+    }
+}
+
+/// ジャンルを割り当てる規則
+///
+/// 指定したすべての条件を満たす明細に一致します。条件を1つも指定しない規則は
+/// すべての明細に一致するため、最後に置いて既定のジャンルとして使えます。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Rule {
+    /// 規則の名前（説明の表示に使う）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 割り当てるジャンル
+    pub genre: String,
+    /// 説明文に一致する正規表現
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 支払先に含まれる文字列（大文字・小文字、全角・半角を区別しない）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merchant: Option<String>,
+    /// 金額の下限（この金額を含む）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<i64>,
+    /// 金額の上限（この金額を含む）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<i64>,
+    /// 曜日（空の場合はすべての曜日）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weekdays: Vec<Weekday>,
+    #[serde(skip)]
+    regex: Option<Regex>,
+}
+
+impl Rule {
+    /// 条件のない規則を作成
+    ///
+    /// # Arguments
+    ///
+    /// * `genre` - 割り当てるジャンル
+    pub fn new(genre: impl Into<String>) -> Self {
+        Self {
+            genre: genre.into(),
+            ..Self::default()
+        }
+    }
+
+    /// 規則の名前を指定
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// 説明文に一致する正規表現を指定
+    pub fn description(mut self, pattern: impl Into<String>) -> Self {
+        self.description = Some(pattern.into());
+        self.regex = None;
+        self
+    }
+
+    /// 支払先に含まれる文字列を指定
+    pub fn merchant(mut self, merchant: impl Into<String>) -> Self {
+        self.merchant = Some(merchant.into());
+        self
+    }
+
+    /// 金額の範囲を指定（`None` は制限なし）
+    pub fn amount_range(mut self, min: Option<i64>, max: Option<i64>) -> Self {
+        self.min_amount = min;
+        self.max_amount = max;
+        self
+    }
+
+    /// 曜日を指定
+    pub fn weekdays(mut self, weekdays: impl IntoIterator<Item = Weekday>) -> Self {
+        self.weekdays = weekdays.into_iter().collect();
+        self
+    }
+
+    /// 満たさなかった条件の一覧（空なら一致）
+    fn failed_conditions(&self, item: &Item) -> Vec<Condition> {
+        let mut failed = Vec::new();
+        if let Some(regex) = &self.regex {
+            if !regex.is_match(&item.description) {
+                failed.push(Condition::Description);
+            }
+        }
+        if let Some(merchant) = &self.merchant {
+            let matches = item
+                .merchant
+                .as_deref()
+                .is_some_and(|m| fold(m).contains(&fold(merchant)));
+            if !matches {
+                failed.push(Condition::Merchant);
+            }
+        }
+        let below = self.min_amount.is_some_and(|min| item.amount < min);
+        let above = self.max_amount.is_some_and(|max| item.amount > max);
+        if below || above {
+            failed.push(Condition::Amount);
+        }
+        if !self.weekdays.is_empty() && !self.weekdays.contains(&item.date.weekday()) {
+            failed.push(Condition::Weekday);
+        }
+        failed
+    }
+
+    fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => format!("#{} {}", index + 1, name),
+            None => format!("#{}", index + 1),
+        }
+    }
+}
+
+/// 規則の条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// 説明文の正規表現
+    Description,
+    /// 支払先
+    Merchant,
+    /// 金額の範囲
+    Amount,
+    /// 曜日
+    Weekday,
+    /// ジャンルがサーバー上に存在する
+    Genre,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Description => "説明文が一致しない",
+            Self::Merchant => "支払先が一致しない",
+            Self::Amount => "金額が範囲外",
+            Self::Weekday => "曜日が対象外",
+            Self::Genre => "ジャンルがサーバーにない",
+        })
+    }
+}
+
+/// 順序付きの規則の一覧
+///
+/// 読み込むときに説明文の正規表現をコンパイルします。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(try_from = "RawRuleSet")]
+pub struct RuleSet {
+    /// 規則の一覧（上から順に照合する）
+    rules: Vec<Rule>,
+}
+
+/// 正規表現をコンパイルする前の規則の一覧
+#[derive(Deserialize)]
+struct RawRuleSet {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+impl TryFrom<RawRuleSet> for RuleSet {
+    type Error = ShishutsukanError;
+
+    fn try_from(raw: RawRuleSet) -> Result<Self> {
+        let mut rules = Self::new();
+        for rule in raw.rules {
+            rules.push(rule)?;
+        }
+        Ok(rules)
+    }
+}
+
+impl RuleSet {
+    /// 空の規則の一覧を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 規則を末尾に追加
+    ///
+    /// # Errors
+    ///
+    /// 説明文の正規表現が正しくない場合
+    pub fn push(&mut self, mut rule: Rule) -> Result<()> {
+        rule.regex = match &rule.description {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| {
+                ShishutsukanError::ParseError(format!(
+                    "規則 {} の正規表現が正しくありません: {}",
+                    rule.label(self.rules.len()),
+                    e
+                ))
+            })?),
+            None => None,
+        };
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// 規則の一覧
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// JSON文字列から読み込む
+    ///
+    /// # Errors
+    ///
+    /// JSONとして正しくない場合や、正規表現が正しくない場合
+    pub fn from_json(json: &str) -> Result<Self> {
+        let raw: RawRuleSet = serde_json::from_str(json)?;
+        raw.try_into()
+    }
+
+    /// 規則ファイルを読み込む
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 規則ファイルのパス
+    ///
+    /// # Errors
+    ///
+    /// ファイルを読み込めない場合や、内容が正しくない場合
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// JSON文字列にする
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// ジャンルがサーバー上に存在しない規則の番号（0から）
+    ///
+    /// # Arguments
+    ///
+    /// * `genres` - サーバー上のジャンル一覧（`get_genres` の結果）
+    pub fn unknown_genres(&self, genres: &[GenreWithId]) -> Vec<usize> {
+        (0..self.rules.len())
+            .filter(|index| !genre_exists(genres, &self.rules[*index].genre))
+            .collect()
+    }
+
+    /// 明細にジャンルを割り当てる
+    ///
+    /// ジャンルがサーバー上に存在しない規則は照合しません。
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - 明細の一覧
+    /// * `genres` - サーバー上のジャンル一覧（`get_genres` の結果）
+    pub fn categorize(&self, items: &[Item], genres: &[GenreWithId]) -> Categorization {
+        let usable: Vec<bool> = self
+            .rules
+            .iter()
+            .map(|rule| genre_exists(genres, &rule.genre))
+            .collect();
+        let mut stats = RuleStats {
+            rule_hits: vec![0; self.rules.len()],
+            unknown_genre_rules: self.unknown_genres(genres),
+            ..RuleStats::default()
+        };
+        let mut unmatched_keys: BTreeMap<String, (usize, i64)> = BTreeMap::new();
+
+        let items = items
+            .iter()
+            .map(|item| {
+                let rule = self.rules.iter().enumerate().position(|(index, rule)| {
+                    usable[index] && rule.failed_conditions(item).is_empty()
+                });
+                stats.total += 1;
+                match rule {
+                    Some(index) => {
+                        stats.matched += 1;
+                        stats.rule_hits[index] += 1;
+                    }
+                    None => {
+                        stats.unmatched += 1;
+                        stats.unmatched_amount += item.amount;
+                        let entry = unmatched_keys.entry(item.key().to_string()).or_default();
+                        entry.0 += 1;
+                        entry.1 += item.amount;
+                    }
+                }
+                Categorized {
+                    item: item.clone(),
+                    genre: rule.map(|index| self.rules[index].genre.clone()),
+                    rule,
+                }
+            })
+            .collect();
+
+        stats.top_unmatched = unmatched_keys
+            .into_iter()
+            .map(|(key, (count, amount))| UnmatchedKey { key, count, amount })
+            .collect();
+        stats
+            .top_unmatched
+            .sort_by(|a, b| b.count.cmp(&a.count).then(b.amount.cmp(&a.amount)));
+
+        Categorization { items, stats }
+    }
+
+    /// どの規則が一致したか、一致しなかった規則はどの条件で外れたかを調べる
+    ///
+    /// 規則ファイルを書くときの確認に使います。一致した規則より後の規則も調べます。
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - 明細
+    /// * `genres` - サーバー上のジャンル一覧（`get_genres` の結果）
+    pub fn explain(&self, item: &Item, genres: &[GenreWithId]) -> Explanation {
+        let checks: Vec<RuleCheck> = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let mut failed = rule.failed_conditions(item);
+                if !genre_exists(genres, &rule.genre) {
+                    failed.push(Condition::Genre);
+                }
+                RuleCheck {
+                    index,
+                    label: rule.label(index),
+                    genre: rule.genre.clone(),
+                    failed,
+                }
+            })
+            .collect();
+        let rule = checks.iter().position(RuleCheck::matched);
+        Explanation {
+            item: item.clone(),
+            genre: rule.map(|index| checks[index].genre.clone()),
+            rule,
+            checks,
+        }
+    }
+}
+
+/// 1件の明細に対する1つの規則の照合結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleCheck {
+    /// 規則の番号（0から）
+    pub index: usize,
+    /// 表示用の規則の名前（`#1 平日の昼食` など）
+    pub label: String,
+    /// 規則のジャンル
+    pub genre: String,
+    /// 満たさなかった条件
+    pub failed: Vec<Condition>,
+}
+
+impl RuleCheck {
+    /// 規則に一致したかどうか
+    pub fn matched(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 1件の明細の照合の説明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// 明細
+    pub item: Item,
+    /// 割り当てたジャンル
+    pub genre: Option<String>,
+    /// 一致した規則の番号（0から）
+    pub rule: Option<usize>,
+    /// すべての規則の照合結果
+    pub checks: Vec<RuleCheck>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.genre, self.rule) {
+            (Some(genre), Some(rule)) => {
+                writeln!(f, "{} に一致: {}", self.checks[rule].label, genre)?
+            }
+            _ => writeln!(f, "どの規則にも一致しません")?,
+        }
+        for check in &self.checks {
+            let result = if check.matched() {
+                "一致".to_string()
+            } else {
+                check
+                    .failed
+                    .iter()
+                    .map(Condition::to_string)
+                    .collect::<Vec<_>>()
+                    .join("、")
+            };
+            let marker = if Some(check.index) == self.rule {
+                "→"
+            } else {
+                " "
+            };
+            writeln!(
+                f,
+                "{} {} ({}): {}",
+                marker, check.label, check.genre, result
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// ジャンルを割り当てた明細
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Categorized {
+    /// 明細
+    pub item: Item,
+    /// 割り当てたジャンル（どの規則にも一致しなかった場合は `None`）
+    pub genre: Option<String>,
+    /// 一致した規則の番号（0から）
+    pub rule: Option<usize>,
+}
+
+/// どの規則にも一致しなかった支払先（または説明文）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmatchedKey {
+    /// 支払先（ない場合は説明文）
+    pub key: String,
+    /// 件数
+    pub count: usize,
+    /// 金額の合計
+    pub amount: i64,
+}
+
+/// 割り当ての統計
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    /// 明細の件数
+    pub total: usize,
+    /// ジャンルを割り当てた件数
+    pub matched: usize,
+    /// どの規則にも一致しなかった件数
+    pub unmatched: usize,
+    /// どの規則にも一致しなかった明細の金額の合計
+    pub unmatched_amount: i64,
+    /// 規則ごとの一致した件数（規則の順）
+    pub rule_hits: Vec<usize>,
+    /// ジャンルがサーバー上になく使わなかった規則の番号
+    pub unknown_genre_rules: Vec<usize>,
+    /// 一致しなかった支払先（件数の多い順）
+    pub top_unmatched: Vec<UnmatchedKey>,
+}
+
+impl RuleStats {
+    /// ジャンルを割り当てた割合（明細がない場合は `None`）
+    pub fn match_rate(&self) -> Option<f64> {
+        (self.total > 0).then(|| self.matched as f64 / self.total as f64)
+    }
+}
+
+/// 割り当ての結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Categorization {
+    /// 明細ごとの結果（入力の順）
+    pub items: Vec<Categorized>,
+    /// 統計
+    pub stats: RuleStats,
+}
+
+impl Categorization {
+    /// どの規則にも一致しなかった明細
+    pub fn unmatched(&self) -> impl Iterator<Item = &Item> {
+        self.items
+            .iter()
+            .filter(|categorized| categorized.genre.is_none())
+            .map(|categorized| &categorized.item)
+    }
+
+    /// ジャンルを割り当てた明細を支出データにする（金額が `i32` に収まらないものは除く）
+    pub fn to_expenses(&self) -> Vec<Expense> {
+        self.items
+            .iter()
+            .filter_map(|categorized| {
+                let genre = categorized.genre.clone()?;
+                let amount = i32::try_from(categorized.item.amount).ok()?;
+                Some(Expense::new(
+                    categorized.item.date.format(DATE_FORMAT).to_string(),
+                    genre,
+                    amount,
+                ))
+            })
+            .collect()
+    }
+}
+
+fn genre_exists(genres: &[GenreWithId], name: &str) -> bool {
+    genres.iter().any(|genre| genre.name == name)
+}
+
+/// 照合用に全角を半角にし、小文字にそろえる
+fn fold(text: &str) -> String {
+    normalize_width(text).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genre_with_id(id: i32, name: &str) -> GenreWithId {
+        GenreWithId {
+            id,
+            name: name.to_string(),
+            created_at: "2025-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_failed_conditions() {
+        // 2025-01-15 は水曜日
+        let item = Item::new(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(), 800, "弁当")
+            .merchant("セブン－イレブン");
+        let mut rules = RuleSet::new();
+        rules
+            .push(
+                Rule::new("食費")
+                    .merchant("セブン-イレブン")
+                    .amount_range(None, Some(1000))
+                    .weekdays([Weekday::Mon, Weekday::Wed]),
+            )
+            .unwrap();
+        rules
+            .push(
+                Rule::new("食費")
+                    .description("^ランチ")
+                    .amount_range(Some(1000), None)
+                    .weekdays([Weekday::Sat]),
+            )
+            .unwrap();
+
+        assert!(rules.rules[0].failed_conditions(&item).is_empty());
+        assert_eq!(
+            rules.rules[1].failed_conditions(&item),
+            vec![
+                Condition::Description,
+                Condition::Amount,
+                Condition::Weekday
+            ]
+        );
+        assert!(rules.push(Rule::new("食費").description("(")).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_compiles_regex() {
+        let json = r#"{"rules":[{"genre":"交通費","description":"(?i)suica"}]}"#;
+        let genres = vec![genre_with_id(1, "交通費")];
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let items = [
+            Item::new(date, 1200, "書店"),
+            Item::new(date, 3000, "SUICA"),
+        ];
+        let genre_of = |rules: &RuleSet| -> Vec<Option<String>> {
+            let result = rules.categorize(&items, &genres);
+            result.items.into_iter().map(|c| c.genre).collect()
+        };
+        let expected = vec![None, Some("交通費".to_string())];
+
+        let deserialized: RuleSet = serde_json::from_str(json).unwrap();
+        assert_eq!(genre_of(&deserialized), expected);
+        let round_trip: RuleSet = serde_json::from_str(&deserialized.to_json().unwrap()).unwrap();
+        assert_eq!(genre_of(&round_trip), expected);
+        assert_eq!(genre_of(&RuleSet::from_json(json).unwrap()), expected);
+
+        let invalid = r#"{"rules":[{"genre":"交通費","description":"("}]}"#;
+        assert!(serde_json::from_str::<RuleSet>(invalid).is_err());
+        assert!(matches!(
+            RuleSet::from_json(invalid),
+            Err(ShishutsukanError::ParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,657 @@
+use crate::client::ShishutsukanClient;
+use crate::error::{Result, ShishutsukanError};
+use crate::models::{Expense, ExpenseWithId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// ローカルストアの支出データ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LocalRecord {
+    /// サーバー上のID（未送信の場合は `None`）
+    pub server_id: Option<i32>,
+    /// 支出データ
+    pub expense: Expense,
+    /// ローカルで削除済みかどうか（次回の同期でサーバーから削除される）
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+/// 同期エンジンが保存するローカルの状態
+///
+/// `checkpoint` は前回の同期時点でのサーバーの状態で、ローカルとサーバーの
+/// どちらで変更されたかを判定するための基準になります。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SyncState {
+    /// ローカルの支出データ
+    pub records: Vec<LocalRecord>,
+    /// 前回の同期時点でのサーバーの支出データ
+    pub checkpoint: Vec<ExpenseWithId>,
+}
+
+impl SyncState {
+    /// 支出データをローカルに追加
+    pub fn add(&mut self, expense: Expense) {
+        self.records.push(LocalRecord {
+            server_id: None,
+            expense,
+            deleted: false,
+        });
+    }
+
+    /// ローカルの支出データを変更
+    ///
+    /// `index` が範囲外の場合は `false` を返します。
+    pub fn update(&mut self, index: usize, expense: Expense) -> bool {
+        match self.records.get_mut(index) {
+            Some(record) => {
+                record.expense = expense;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// ローカルの支出データを削除
+    ///
+    /// 未送信の支出データはすぐに取り除き、送信済みのものは削除済みとして
+    /// 次回の同期まで残します。`index` が範囲外の場合は `false` を返します。
+    pub fn delete(&mut self, index: usize) -> bool {
+        match self.records.get(index).map(|record| record.server_id) {
+            Some(None) => {
+                self.records.remove(index);
+                true
+            }
+            Some(Some(_)) => {
+                self.records[index].deleted = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 削除済みでないローカルの支出データ
+    pub fn expenses(&self) -> impl Iterator<Item = &LocalRecord> {
+        self.records.iter().filter(|record| !record.deleted)
+    }
+}
+
+/// 同期状態の保存先
+pub trait SyncStore {
+    /// 保存されている状態を読み込む（未保存の場合は空の状態）
+    fn load(&self) -> Result<SyncState>;
+
+    /// 状態を保存する
+    fn save(&mut self, state: &SyncState) -> Result<()>;
+}
+
+/// メモリ上に同期状態を保持するストア
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    state: SyncState,
+}
+
+impl SyncStore for MemoryStore {
+    fn load(&self) -> Result<SyncState> {
+        Ok(self.state.clone())
+    }
+
+    fn save(&mut self, state: &SyncState) -> Result<()> {
+        self.state = state.clone();
+        Ok(())
+    }
+}
+
+/// JSONファイルに同期状態を保存するストア
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    /// JSONファイルのストアを作成
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - JSONファイルのパス（存在しない場合は最初の保存時に作成）
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SyncStore for JsonFileStore {
+    fn load(&self) -> Result<SyncState> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SyncState::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&mut self, state: &SyncState) -> Result<()> {
+        // 書き込み途中で中断しても状態が壊れないよう、一時ファイル経由で置き換える
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(state)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+// MARK: - Diff
+
+/// 競合の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncConflictKind {
+    /// ローカルで変更し、サーバーで削除された
+    ModifiedDeleted,
+    /// ローカルで削除し、サーバーで変更された
+    DeletedModified,
+    /// 両方で異なる内容に変更された
+    BothModified,
+}
+
+/// 前回の同期以降に両方で変更された支出データ
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncConflict {
+    /// ローカルの支出データのインデックス
+    pub record: usize,
+    /// 競合の種類
+    pub kind: SyncConflictKind,
+    /// 前回の同期時点の支出データ
+    pub base: ExpenseWithId,
+    /// ローカルの支出データ（削除された場合は `None`）
+    pub local: Option<Expense>,
+    /// サーバーの支出データ（削除された場合は `None`）
+    pub remote: Option<ExpenseWithId>,
+}
+
+/// ローカルとサーバーの差分
+///
+/// ローカル側の変更は [`SyncState::records`] のインデックスで表します。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncDiff {
+    /// サーバーで追加された支出データ
+    pub remote_added: Vec<ExpenseWithId>,
+    /// サーバーで削除された支出データ（前回の同期時点の内容）
+    pub remote_deleted: Vec<ExpenseWithId>,
+    /// サーバーで変更された支出データ（変更後の内容）
+    pub remote_modified: Vec<ExpenseWithId>,
+    /// ローカルで追加された支出データ
+    pub local_added: Vec<usize>,
+    /// ローカルで削除された支出データ
+    pub local_deleted: Vec<usize>,
+    /// ローカルで変更された支出データ
+    pub local_modified: Vec<usize>,
+    /// ローカルで追加した支出データと内容が一致した、サーバーで追加された支出データ
+    pub linked: Vec<(usize, ExpenseWithId)>,
+    /// 両方で削除された支出データ
+    pub both_deleted: Vec<usize>,
+    /// 競合
+    pub conflicts: Vec<SyncConflict>,
+}
+
+impl SyncDiff {
+    /// 差分がないかどうか
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// ローカルの状態とサーバーの支出データの差分を計算
+///
+/// IDで対応付けたうえで、前回の同期時点（`checkpoint`）と内容を比較して
+/// どちらで変更されたかを判定します。ローカルで追加した支出データと
+/// 内容が一致するサーバーの新しい支出データは、同じものとして対応付けます。
+///
+/// # Arguments
+///
+/// * `state` - ローカルの状態
+/// * `server` - サーバーの支出データ一覧
+pub fn diff(state: &SyncState, server: &[ExpenseWithId]) -> SyncDiff {
+    let base: HashMap<i32, &ExpenseWithId> = state.checkpoint.iter().map(|e| (e.id, e)).collect();
+    let remote: HashMap<i32, &ExpenseWithId> = server.iter().map(|e| (e.id, e)).collect();
+    let tracked: HashSet<i32> = state.records.iter().filter_map(|r| r.server_id).collect();
+
+    let mut result = SyncDiff::default();
+    let mut claimed: HashSet<i32> = HashSet::new();
+    let mut handled: HashSet<i32> = HashSet::new();
+
+    for (index, record) in state.records.iter().enumerate() {
+        let Some(id) = record.server_id else {
+            if record.deleted {
+                continue;
+            }
+            let link = server.iter().find(|row| {
+                !base.contains_key(&row.id)
+                    && !tracked.contains(&row.id)
+                    && !claimed.contains(&row.id)
+                    && row.matches(&record.expense)
+            });
+            match link {
+                Some(row) => {
+                    claimed.insert(row.id);
+                    result.linked.push((index, row.clone()));
+                }
+                None => result.local_added.push(index),
+            }
+            continue;
+        };
+
+        let Some(base_row) = base.get(&id).or_else(|| remote.get(&id)).copied() else {
+            // 基準もサーバーの行もない場合は、サーバーで削除されたものとして扱う
+            let base_row = with_id(id, &record.expense);
+            handled.insert(id);
+            if record.deleted {
+                result.both_deleted.push(index);
+            } else {
+                result.conflicts.push(SyncConflict {
+                    record: index,
+                    kind: SyncConflictKind::ModifiedDeleted,
+                    base: base_row,
+                    local: Some(record.expense.clone()),
+                    remote: None,
+                });
+            }
+            continue;
+        };
+
+        let remote_row = remote.get(&id).copied();
+        let remote_modified = remote_row.is_some_and(|row| row != base_row);
+        let local_modified = !base_row.matches(&record.expense);
+
+        let conflict = |kind| SyncConflict {
+            record: index,
+            kind,
+            base: base_row.clone(),
+            local: (!record.deleted).then(|| record.expense.clone()),
+            remote: remote_row.cloned(),
+        };
+
+        match (record.deleted, local_modified, remote_row, remote_modified) {
+            (true, _, None, _) => result.both_deleted.push(index),
+            (true, _, Some(_), true) => result
+                .conflicts
+                .push(conflict(SyncConflictKind::DeletedModified)),
+            (true, _, Some(_), false) => result.local_deleted.push(index),
+            (false, true, None, _) => result
+                .conflicts
+                .push(conflict(SyncConflictKind::ModifiedDeleted)),
+            (false, true, Some(row), true) if !row.matches(&record.expense) => result
+                .conflicts
+                .push(conflict(SyncConflictKind::BothModified)),
+            // 両方で同じ内容に変更された場合は、サーバー側の変更として取り込む
+            (false, true, Some(_), true) => continue,
+            (false, true, Some(_), false) => result.local_modified.push(index),
+            (false, false, _, _) => continue,
+        }
+        handled.insert(id);
+    }
+
+    for row in server {
+        if !base.contains_key(&row.id) && !tracked.contains(&row.id) && !claimed.contains(&row.id) {
+            result.remote_added.push(row.clone());
+        }
+    }
+    for row in &state.checkpoint {
+        if handled.contains(&row.id) {
+            continue;
+        }
+        match remote.get(&row.id) {
+            None => result.remote_deleted.push(row.clone()),
+            Some(current) if *current != row => result.remote_modified.push((*current).clone()),
+            Some(_) => {}
+        }
+    }
+
+    result
+}
+
+fn with_id(id: i32, expense: &Expense) -> ExpenseWithId {
+    ExpenseWithId {
+        id,
+        date: expense.date.clone(),
+        genre: expense.genre.clone(),
+        amount: expense.amount,
+    }
+}
+
+// MARK: - Engine
+
+/// 同期の方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncMode {
+    /// ローカルの変更をサーバーに送信する
+    Push,
+    /// サーバーの変更をローカルに取り込む
+    Pull,
+    /// 双方向に同期する
+    #[default]
+    Bidirectional,
+}
+
+/// 競合の解決方針
+///
+/// 競合の解決は同期の方向（[`SyncMode`]）に関係なく適用されます。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// ローカルの内容を優先する
+    PreferLocal,
+    /// サーバーの内容を優先する
+    PreferRemote,
+    /// 解決せずにレポートし、次回の同期に持ち越す
+    #[default]
+    Skip,
+}
+
+/// 同期の結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    /// サーバーに追加した支出データの件数
+    pub pushed_added: usize,
+    /// サーバーから削除した支出データの件数
+    pub pushed_deleted: usize,
+    /// ローカルに取り込んだサーバーの追加の件数
+    pub pulled_added: usize,
+    /// ローカルに取り込んだサーバーの削除の件数
+    pub pulled_deleted: usize,
+    /// ローカルに取り込んだサーバーの変更の件数
+    pub pulled_modified: usize,
+    /// 内容の一致により対応付けた件数
+    pub linked: usize,
+    /// 方針に従って解決した競合の件数
+    pub resolved: usize,
+    /// 解決せずに持ち越した競合
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// サーバーへの書き込み操作
+enum Action {
+    /// ローカルの支出データを新しく追加する
+    Add(usize),
+    /// サーバーの支出データを削除し、ローカルの支出データも取り除く
+    Delete { record: usize, id: i32 },
+    /// サーバーの支出データを削除し、ローカルの支出データを新しく追加する
+    Replace { record: usize, id: i32 },
+}
+
+/// ローカルストアとサーバーの同期エンジン
+///
+/// # Examples
+///
+/// ```no_run
+/// # use shishutsukan::{ShishutsukanClient, Expense};
+/// # use shishutsukan::sync::{ConflictPolicy, JsonFileStore, SyncEngine, SyncMode, SyncStore};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = ShishutsukanClient::new("http://localhost:8000");
+/// let mut store = JsonFileStore::new("local-expenses.json");
+///
+/// // ローカルで支出データを追加
+/// let mut state = store.load()?;
+/// state.add(Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000));
+/// store.save(&state)?;
+///
+/// let mut engine = SyncEngine::new(client, store)
+///     .mode(SyncMode::Bidirectional)
+///     .policy(ConflictPolicy::PreferRemote);
+/// let report = engine.sync().await?;
+/// println!("送信 {} 件, 取り込み {} 件", report.pushed_added, report.pulled_added);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SyncEngine<S: SyncStore> {
+    client: ShishutsukanClient,
+    store: S,
+    mode: SyncMode,
+    policy: ConflictPolicy,
+}
+
+impl<S: SyncStore> SyncEngine<S> {
+    /// 同期エンジンを作成（双方向・競合は持ち越し）
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - APIクライアント
+    /// * `store` - 同期状態の保存先
+    pub fn new(client: ShishutsukanClient, store: S) -> Self {
+        Self {
+            client,
+            store,
+            mode: SyncMode::default(),
+            policy: ConflictPolicy::default(),
+        }
+    }
+
+    /// 同期の方向を設定
+    pub fn mode(mut self, mode: SyncMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// 競合の解決方針を設定
+    pub fn policy(mut self, policy: ConflictPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 同期状態の保存先
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// 同期状態の保存先（変更用）
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// 同期せずに現在の差分を計算
+    pub async fn preview(&self) -> Result<SyncDiff> {
+        let state = self.store.load()?;
+        let server = self.client.get_expenses().await?;
+        Ok(diff(&state, &server))
+    }
+
+    /// 同期を実行
+    ///
+    /// サーバーへの書き込みが途中で失敗した場合も、それまでの結果を
+    /// ローカルの状態に保存してからエラーを返します。
+    pub async fn sync(&mut self) -> Result<SyncReport> {
+        let mut state = self.store.load()?;
+        let server = self.client.get_expenses().await?;
+        let changes = diff(&state, &server);
+
+        let pull = self.mode != SyncMode::Push;
+        let push = self.mode != SyncMode::Pull;
+        let mut report = SyncReport::default();
+        let mut checkpoint: BTreeMap<i32, ExpenseWithId> =
+            state.checkpoint.iter().map(|e| (e.id, e.clone())).collect();
+        let mut removals: HashSet<usize> = HashSet::new();
+        let mut actions: Vec<Action> = Vec::new();
+
+        for (index, row) in &changes.linked {
+            state.records[*index].server_id = Some(row.id);
+            checkpoint.insert(row.id, row.clone());
+            report.linked += 1;
+        }
+        for index in &changes.both_deleted {
+            if let Some(id) = state.records[*index].server_id {
+                checkpoint.remove(&id);
+            }
+            removals.insert(*index);
+        }
+
+        if pull {
+            for row in &changes.remote_added {
+                state.records.push(LocalRecord {
+                    server_id: Some(row.id),
+                    expense: row.to_expense(),
+                    deleted: false,
+                });
+                checkpoint.insert(row.id, row.clone());
+                report.pulled_added += 1;
+            }
+            for row in &changes.remote_deleted {
+                if let Some(index) = position_of(&state, row.id) {
+                    removals.insert(index);
+                }
+                checkpoint.remove(&row.id);
+                report.pulled_deleted += 1;
+            }
+            for row in &changes.remote_modified {
+                if let Some(index) = position_of(&state, row.id) {
+                    state.records[index].expense = row.to_expense();
+                }
+                checkpoint.insert(row.id, row.clone());
+                report.pulled_modified += 1;
+            }
+        }
+
+        if push {
+            actions.extend(changes.local_added.iter().map(|&index| Action::Add(index)));
+            for &index in &changes.local_deleted {
+                if let Some(id) = state.records[index].server_id {
+                    actions.push(Action::Delete { record: index, id });
+                }
+            }
+            for &index in &changes.local_modified {
+                if let Some(id) = state.records[index].server_id {
+                    actions.push(Action::Replace { record: index, id });
+                }
+            }
+        }
+
+        for conflict in changes.conflicts {
+            let index = conflict.record;
+            let id = conflict.base.id;
+            match (self.policy, conflict.kind) {
+                (ConflictPolicy::Skip, _) => {
+                    report.conflicts.push(conflict);
+                    continue;
+                }
+                (ConflictPolicy::PreferLocal, SyncConflictKind::ModifiedDeleted) => {
+                    checkpoint.remove(&id);
+                    state.records[index].server_id = None;
+                    actions.push(Action::Add(index));
+                }
+                (ConflictPolicy::PreferLocal, SyncConflictKind::BothModified) => {
+                    actions.push(Action::Replace { record: index, id });
+                }
+                (ConflictPolicy::PreferLocal, SyncConflictKind::DeletedModified) => {
+                    actions.push(Action::Delete { record: index, id });
+                }
+                (ConflictPolicy::PreferRemote, SyncConflictKind::ModifiedDeleted) => {
+                    checkpoint.remove(&id);
+                    removals.insert(index);
+                }
+                (ConflictPolicy::PreferRemote, _) => {
+                    if let Some(remote) = conflict.remote {
+                        let record = &mut state.records[index];
+                        record.expense = remote.to_expense();
+                        record.deleted = false;
+                        checkpoint.insert(id, remote);
+                    }
+                }
+            }
+            report.resolved += 1;
+        }
+
+        let mut pending_adds: Vec<usize> = Vec::new();
+        let mut failure: Option<ShishutsukanError> = None;
+        for action in actions {
+            let result = match action {
+                Action::Add(record) => {
+                    let result = self
+                        .client
+                        .add_expense(&state.records[record].expense)
+                        .await;
+                    if result.is_ok() {
+                        pending_adds.push(record);
+                    }
+                    result
+                }
+                Action::Delete { record, id } => {
+                    let result = self.client.delete_expense(id).await;
+                    if result.is_ok() {
+                        checkpoint.remove(&id);
+                        removals.insert(record);
+                        report.pushed_deleted += 1;
+                    }
+                    result
+                }
+                Action::Replace { record, id } => {
+                    let result = self.client.delete_expense(id).await;
+                    if result.is_ok() {
+                        checkpoint.remove(&id);
+                        state.records[record].server_id = None;
+                        report.pushed_deleted += 1;
+                    }
+                    match result {
+                        Ok(_) => {
+                            let result = self
+                                .client
+                                .add_expense(&state.records[record].expense)
+                                .await;
+                            if result.is_ok() {
+                                pending_adds.push(record);
+                            }
+                            result
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+            if let Err(e) = result {
+                failure = Some(e);
+                break;
+            }
+        }
+
+        // 追加した支出データのIDは、サーバーの一覧に新しく現れた同じ内容の行から決める
+        if !pending_adds.is_empty() {
+            let known: HashSet<i32> = server.iter().map(|e| e.id).collect();
+            match self.client.get_expenses().await {
+                Ok(after) => {
+                    let mut taken = known;
+                    for index in pending_adds {
+                        let record = &mut state.records[index];
+                        let row = after
+                            .iter()
+                            .find(|row| !taken.contains(&row.id) && row.matches(&record.expense));
+                        if let Some(row) = row {
+                            taken.insert(row.id);
+                            record.server_id = Some(row.id);
+                            checkpoint.insert(row.id, row.clone());
+                        }
+                        report.pushed_added += 1;
+                    }
+                }
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+
+        let mut removals: Vec<usize> = removals.into_iter().collect();
+        removals.sort_unstable_by(|a, b| b.cmp(a));
+        for index in removals {
+            state.records.remove(index);
+        }
+        state.checkpoint = checkpoint.into_values().collect();
+        self.store.save(&state)?;
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(report),
+        }
+    }
+}
+
+fn position_of(state: &SyncState, id: i32) -> Option<usize> {
+    state
+        .records
+        .iter()
+        .position(|record| record.server_id == Some(id))
+}
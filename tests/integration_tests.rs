@@ -1,6 +1,10 @@
 /// Integration tests that verify shishutsukan-rs works with actual shishutsukan server
 /// These tests require a running shishutsukan server on localhost:8000
-use shishutsukan::{Expense, Genre, ShishutsukanClient};
+use shishutsukan::{
+    find_duplicates, DedupePlan, DuplicateCriteria, DuplicateReason, Expense, ExpenseLedger, Genre,
+    IdempotentAddOutcome, OfflineClient, ShishutsukanClient,
+};
+use std::time::Duration;
 
 // Helper function to generate unique names for tests
 fn generate_unique_name(prefix: &str) -> String {
@@ -196,6 +200,595 @@ async fn test_complete_workflow() {
     assert_eq!(genres_after_delete.len(), initial_genres.len());
 }
 
+// MARK: - Cache Tests
+
+#[tokio::test]
+async fn test_cached_genres_invalidated_by_add() {
+    let client = ShishutsukanClient::new("http://localhost:8000").with_cache(Duration::from_secs(60));
+    
+    // Second call should be served from cache
+    let first = client.get_genres().await.expect("Failed to get genres");
+    let second = client.get_genres().await.expect("Failed to get genres");
+    assert_eq!(first, second);
+    let stats = client.cache_stats().expect("Cache should be enabled");
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.misses, 1);
+    
+    // Adding a genre through a clone should invalidate the shared cache
+    let unique_name = generate_unique_name("Cached");
+    client.clone().add_genre(&Genre::new(unique_name.clone())).await.expect("Failed to add genre");
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    assert!(genres.iter().any(|g| g.name == unique_name), "Added genre should appear after invalidation");
+    
+    // Clean up
+    if let Some(added_genre) = genres.iter().find(|g| g.name == unique_name) {
+        let _ = client.delete_genre(added_genre.id).await;
+    }
+}
+
+// MARK: - Rate Limit Tests
+
+#[tokio::test]
+async fn test_rate_limit_shared_across_clones() {
+    let client = ShishutsukanClient::new("http://localhost:8000").with_rate_limit(10.0, 1);
+    let cloned = client.clone();
+    
+    // The burst of 1 is consumed by the first call; the next four must wait ~100ms each
+    let started = std::time::Instant::now();
+    for i in 0..5 {
+        let c = if i % 2 == 0 { &client } else { &cloned };
+        c.get_genres().await.expect("Failed to get genres");
+    }
+    assert!(started.elapsed() >= Duration::from_millis(350), "Requests should be throttled");
+}
+
+#[tokio::test]
+async fn test_add_expenses_bulk() {
+    let client = ShishutsukanClient::new("http://localhost:8000").with_rate_limit(20.0, 2);
+    
+    let genre_name = generate_unique_name("Bulk");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    
+    let expenses = vec![
+        Expense::new("2025-02-01".to_string(), genre_name.clone(), 100),
+        Expense::new("2025-02-02".to_string(), genre_name.clone(), 200),
+        Expense::new("2025-02-03".to_string(), genre_name.clone(), 300),
+    ];
+    let results = client.add_expenses(&expenses).await;
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|r| r.is_ok()), "All expenses should be added");
+    
+    // Clean up
+    let all = client.get_expenses().await.expect("Failed to get expenses");
+    for expense in all.iter().filter(|e| e.genre == genre_name) {
+        let _ = client.delete_expense(expense.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Idempotency Tests
+
+#[tokio::test]
+async fn test_add_expense_idempotent_with_key() {
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let mut ledger = ExpenseLedger::in_memory();
+    let window = Duration::from_secs(600);
+    
+    let key = generate_unique_name("receipt");
+    let expense = Expense::new("2025-03-01".to_string(), "食費".to_string(), 4321);
+    
+    // First add goes through
+    let first = client.add_expense_idempotent(&expense, Some(&key), &mut ledger, window).await
+        .expect("Failed to add expense");
+    assert!(matches!(first, IdempotentAddOutcome::Added(_)));
+    
+    // Retrying with the same key is skipped
+    let second = client.add_expense_idempotent(&expense, Some(&key), &mut ledger, window).await
+        .expect("Failed to add expense");
+    match second {
+        IdempotentAddOutcome::Skipped { reason, existing } => {
+            assert_eq!(reason, DuplicateReason::KeyMatched);
+            let existing = existing.expect("Existing row should be reported");
+            let _ = client.delete_expense(existing.id).await;
+        }
+        IdempotentAddOutcome::Added(_) => panic!("Duplicate should be skipped"),
+    }
+}
+
+// MARK: - Dedupe Tests
+
+#[tokio::test]
+async fn test_dedupe_expenses() {
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    
+    let genre_name = generate_unique_name("Dedupe");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    let expense = Expense::new("2025-04-01".to_string(), genre_name.clone(), 777);
+    client.add_expense(&expense).await.expect("Failed to add expense");
+    client.add_expense(&expense).await.expect("Failed to add expense");
+    
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let ours: Vec<_> = expenses.into_iter().filter(|e| e.genre == genre_name).collect();
+    let groups = find_duplicates(&ours, &DuplicateCriteria::exact());
+    assert_eq!(groups.len(), 1);
+    let plan = DedupePlan::from_groups(&groups, 1.0);
+    
+    // Dry run reports without deleting
+    let report = client.dedupe_expenses(&plan, true).await.expect("Failed to dry-run dedupe");
+    assert!(report.dry_run);
+    assert_eq!(report.deleted.len(), 1);
+    let remaining = client.get_expenses().await.expect("Failed to get expenses");
+    assert_eq!(remaining.iter().filter(|e| e.genre == genre_name).count(), 2);
+    
+    // Real run deletes the duplicate only
+    let report = client.dedupe_expenses(&plan, false).await.expect("Failed to dedupe");
+    assert_eq!(report.deleted.len(), 1);
+    let remaining = client.get_expenses().await.expect("Failed to get expenses");
+    let kept: Vec<_> = remaining.iter().filter(|e| e.genre == genre_name).collect();
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].id, groups[0].keep.id);
+    
+    // Clean up
+    let _ = client.delete_expense(kept[0].id).await;
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Offline Queue Tests
+
+#[tokio::test]
+async fn test_offline_flush_reports_conflicts() {
+    let path = std::env::temp_dir().join(format!("shishutsukan_flush_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    
+    // Queue operations while pointing at an unreachable server
+    let genre_name = generate_unique_name("Offline");
+    let mut offline = OfflineClient::open(ShishutsukanClient::new("http://127.0.0.1:9"), &path)
+        .expect("Failed to open journal");
+    let expense = Expense::new("2025-05-01".to_string(), genre_name.clone(), 1500);
+    offline.add_expense(&expense).await.expect("Failed to queue expense");
+    offline.delete_expense(999999).await.expect("Failed to queue delete");
+    offline.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to queue genre");
+    
+    // Back online: the genre is created before the expense, and the delete is a conflict
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let mut online = OfflineClient::open(client.clone(), &path).expect("Failed to reopen journal");
+    let report = online.flush().await.expect("Failed to flush");
+    assert_eq!(report.applied.len(), 2);
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.remaining, 0);
+    assert!(online.pending().is_empty());
+    
+    // Clean up
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    for e in expenses.iter().filter(|e| e.genre == genre_name) {
+        let _ = client.delete_expense(e.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+// MARK: - Sync Tests
+
+#[tokio::test]
+async fn test_bidirectional_sync() {
+    use shishutsukan::sync::{MemoryStore, SyncEngine, SyncStore};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Sync");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    
+    // Initial pull mirrors the server
+    let mut engine = SyncEngine::new(client.clone(), MemoryStore::default());
+    engine.sync().await.expect("Failed to sync");
+    let server_count = client.get_expenses().await.expect("Failed to get expenses").len();
+    assert_eq!(engine.store().load().unwrap().records.len(), server_count);
+    
+    // Push a local addition
+    let mut state = engine.store().load().unwrap();
+    state.add(Expense::new("2025-06-01".to_string(), genre_name.clone(), 2500));
+    engine.store_mut().save(&state).unwrap();
+    let report = engine.sync().await.expect("Failed to sync");
+    assert_eq!(report.pushed_added, 1);
+    
+    let state = engine.store().load().unwrap();
+    let pushed = state.records.iter().find(|r| r.expense.genre == genre_name).expect("Record should remain");
+    let id = pushed.server_id.expect("Pushed record should have a server ID");
+    
+    // Delete on the server and pull the deletion
+    client.delete_expense(id).await.expect("Failed to delete expense");
+    let report = engine.sync().await.expect("Failed to sync");
+    assert_eq!(report.pulled_deleted, 1);
+    assert!(!engine.store().load().unwrap().records.iter().any(|r| r.server_id == Some(id)));
+    
+    // Clean up
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - CSV Import Tests
+
+#[tokio::test]
+async fn test_csv_import_preview_and_commit() {
+    use shishutsukan::import::csv::{self, ColumnMapping};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Import");
+    let input = format!("日付,費目,金額\n2025年7月1日,{0},\"1,200円\"\n2025年7月2日,{0},不明\n", genre_name);
+    let mapping = ColumnMapping::new("日付", "金額").genre("費目");
+    
+    // Without auto-creation the unknown genre is reported
+    let report = csv::preview(&client, &input, &mapping, false).await.expect("Failed to preview import");
+    assert_eq!(report.valid_rows().count(), 0);
+    assert!(report.new_genres.is_empty());
+    
+    // With auto-creation only the unparsable row is left out
+    let report = csv::preview(&client, &input, &mapping, true).await.expect("Failed to preview import");
+    assert_eq!(report.new_genres, vec![genre_name.clone()]);
+    let result = csv::commit(&client, &report).await.expect("Failed to commit import");
+    assert_eq!(result.created_genres, vec![genre_name.clone()]);
+    assert_eq!(result.added, vec![2]);
+    assert_eq!(result.skipped, vec![3]);
+    
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let imported: Vec<_> = expenses.iter().filter(|e| e.genre == genre_name).collect();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].date, "2025-07-01");
+    assert_eq!(imported[0].amount, 1200);
+    
+    // Clean up
+    for expense in imported {
+        let _ = client.delete_expense(expense.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Backup Tests
+
+#[tokio::test]
+async fn test_backup_and_restore_merge() {
+    use shishutsukan::{Backup, BackupFormat, RestoreMode, ShishutsukanError};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Backup");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    client.add_expense(&Expense::new("2025-08-01".to_string(), genre_name.clone(), 100)).await.expect("Failed to add expense");
+    client.add_expense(&Expense::new("2025-08-02".to_string(), genre_name.clone(), 200)).await.expect("Failed to add expense");
+    
+    let mut output = Vec::new();
+    let backup = client.backup(&mut output, BackupFormat::Ndjson).await.expect("Failed to back up");
+    assert_eq!(Backup::read_from(output.as_slice()).expect("Failed to read backup"), backup);
+    
+    // Lose one expense, then restore on top of the existing data
+    let lost = backup.expenses.iter().find(|e| e.genre == genre_name && e.amount == 200).expect("Expense should be backed up");
+    client.delete_expense(lost.id).await.expect("Failed to delete expense");
+    
+    let result = client.restore(output.as_slice(), RestoreMode::Empty).await;
+    assert!(matches!(result, Err(ShishutsukanError::ServerNotEmpty(_))));
+    
+    let summary = client.restore(output.as_slice(), RestoreMode::Merge).await.expect("Failed to restore");
+    assert_eq!(summary.expenses_restored, 1);
+    assert_eq!(summary.expenses_existing, backup.expenses.len() - 1);
+    assert_eq!(summary.genres_existing, backup.genres.len());
+    assert!(summary.failed_expenses.is_empty());
+    
+    let new_id = summary.expense_ids[&lost.id];
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let restored = expenses.iter().find(|e| e.id == new_id).expect("Restored expense should exist");
+    assert!(restored.matches(&lost.to_expense()));
+    
+    // Clean up
+    for expense in expenses.iter().filter(|e| e.genre == genre_name) {
+        let _ = client.delete_expense(expense.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Statement Import Tests
+
+#[tokio::test]
+async fn test_ofx_import_suppresses_duplicates() {
+    use shishutsukan::import::{ofx, statement::{to_expenses, GenreRules}};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Ofx");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    
+    let fitid = generate_unique_name("FITID");
+    let input = format!(
+        "OFXHEADER:100\nDATA:OFXSGML\n\n<OFX><CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS><BANKTRANLIST>\n\
+         <STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20250901<TRNAMT>-1500.00<FITID>{0}-1<NAME>TEST SHOP</STMTTRN>\n\
+         <STMTTRN><TRNTYPE>CREDIT<DTPOSTED>20250902<TRNAMT>1500.00<FITID>{0}-2<NAME>TEST SHOP</STMTTRN>\n\
+         </BANKTRANLIST></CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1></OFX>",
+        fitid
+    );
+    let transactions = ofx::parse(&input).expect("Failed to parse OFX");
+    let import = to_expenses(&transactions, &GenreRules::new().rule("test shop", genre_name.clone()));
+    assert_eq!(import.expenses.len(), 1);
+    assert_eq!(import.skipped_credits, 1);
+    
+    // Importing the same statement twice adds the debit only once
+    let mut ledger = ExpenseLedger::in_memory();
+    for _ in 0..2 {
+        let results = client
+            .add_expenses_idempotent(&import.expenses, &mut ledger, Duration::from_secs(600))
+            .await
+            .expect("Failed to add expenses");
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+    
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let imported: Vec<_> = expenses.iter().filter(|e| e.genre == genre_name).collect();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].amount, 1500);
+    
+    // Clean up
+    for expense in imported {
+        let _ = client.delete_expense(expense.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Household App Import Tests
+
+#[tokio::test]
+async fn test_household_import_with_mapping_table() {
+    use shishutsukan::import::household::{self, HouseholdApp};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let category = generate_unique_name("Zaim");
+    let input = format!(
+        "日付,方法,カテゴリ,カテゴリの内訳,支払元,入金先,品目,メモ,お店,通貨,収入,支出,振替,残高調整,通貨変換前の金額,集計の設定\n\
+         2025-10-01,payment,{0},-,財布,,,,,JPY,0,700,0,0,0,常に集計に含める\n\
+         2025-10-02,income,給与,-,,銀行,,,,JPY,200000,0,0,0,0,常に集計に含める\n",
+        category
+    );
+    let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(&input);
+    let import = household::parse(&bytes, HouseholdApp::Zaim).expect("Failed to parse CSV");
+    assert_eq!(import.skipped_income, 1);
+    
+    // The unknown category is proposed as a new genre
+    let table = household::mapping_table(&client, &import.transactions).await.expect("Failed to build mapping table");
+    assert_eq!(table.missing_genres(), vec![category.clone()]);
+    for name in table.missing_genres() {
+        client.add_genre(&Genre::new(name)).await.expect("Failed to add genre");
+    }
+    
+    let mut ledger = ExpenseLedger::in_memory();
+    let results = client
+        .add_expenses_idempotent(&table.to_expenses(&import.transactions), &mut ledger, Duration::from_secs(600))
+        .await
+        .expect("Failed to add expenses");
+    assert!(results.iter().all(|r| r.is_ok()));
+    
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let imported: Vec<_> = expenses.iter().filter(|e| e.genre == category).collect();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].amount, 700);
+    
+    // Clean up
+    for expense in imported {
+        let _ = client.delete_expense(expense.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == category) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Recurring Tests
+
+#[tokio::test]
+async fn test_recurring_materialize_is_idempotent() {
+    use chrono::NaiveDate;
+    use shishutsukan::recurring::{RecurringStore, RecurringTemplate, Schedule};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Recurring");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    
+    // One occurrence was already entered by hand
+    client.add_expense(&Expense::new("2025-02-10".to_string(), genre_name.clone(), 5000)).await.expect("Failed to add expense");
+    
+    let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+    let mut store = RecurringStore::in_memory();
+    store.set(RecurringTemplate::new("subscription", genre_name.clone(), 5000, Schedule::MonthlyOnDay(10), date(1, 1))).unwrap();
+    
+    // Catches up January to March
+    let report = store.materialize(&client, date(3, 15)).await.expect("Failed to materialize");
+    assert_eq!(report.added.len(), 2);
+    assert_eq!(report.existing.len(), 1);
+    assert!(report.failed.is_empty());
+    assert_eq!(store.templates()[0].materialized_through, Some(date(3, 15)));
+    
+    // Running again adds nothing
+    let report = store.materialize(&client, date(3, 15)).await.expect("Failed to materialize");
+    assert!(report.added.is_empty() && report.existing.is_empty());
+    
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let created: Vec<_> = expenses.iter().filter(|e| e.genre == genre_name).collect();
+    let mut dates: Vec<&str> = created.iter().map(|e| e.date.as_str()).collect();
+    dates.sort();
+    assert_eq!(dates, vec!["2025-01-10", "2025-02-10", "2025-03-10"]);
+    
+    // Clean up
+    for expense in created {
+        let _ = client.delete_expense(expense.id).await;
+    }
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        let _ = client.delete_genre(genre.id).await;
+    }
+}
+
+// MARK: - Quick Entry Tests
+
+#[tokio::test]
+async fn test_quick_entry_adds_expense() {
+    use shishutsukan::quick_entry::QuickEntryParser;
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Quick");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    let parser = QuickEntryParser::new(&genres);
+    let today = chrono::NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+    let entry = parser.parse(&format!("昨日 {} ¥1,234", genre_name), today).expect("Failed to parse");
+    let expense = entry.to_expense().expect("Genre should be resolved");
+    client.add_expense(&expense).await.expect("Failed to add expense");
+    
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let added = expenses.iter().find(|e| e.genre == genre_name).expect("Expense should be added");
+    assert_eq!((added.date.as_str(), added.amount), ("2025-01-19", 1234));
+    
+    client.delete_expense(added.id).await.expect("Failed to delete expense");
+    if let Some(genre) = client.get_genres().await.unwrap().iter().find(|g| g.name == genre_name) {
+        client.delete_genre(genre.id).await.expect("Failed to delete genre");
+    }
+}
+
+// MARK: - Rules Tests
+
+#[cfg(feature = "rules")]
+#[tokio::test]
+async fn test_rules_categorize_statement() {
+    use shishutsukan::import::statement::StatementTransaction;
+    use shishutsukan::rules::{Item, RuleSet};
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("Rules");
+    client.add_genre(&Genre::new(genre_name.clone())).await.expect("Failed to add genre");
+    
+    let path = std::env::temp_dir().join(format!("{}.json", generate_unique_name("shishutsukan_rules")));
+    std::fs::write(
+        &path,
+        format!(r#"{{"rules": [{{"name": "書店", "genre": "{}", "merchant": "BOOK"}}, {{"genre": "存在しないジャンル"}}]}}"#, genre_name),
+    ).expect("Failed to write rules");
+    let rules = RuleSet::open(&path).expect("Failed to load rules");
+    std::fs::remove_file(&path).ok();
+    
+    let transactions = [
+        StatementTransaction {
+            id: "rules-1".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+            amount: -1540,
+            payee: "ＢＯＯＫ ＯＦＦ".to_string(),
+            memo: String::new(),
+        },
+        StatementTransaction {
+            id: "rules-2".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 1, 16).unwrap(),
+            amount: -300,
+            payee: "コインパーキング".to_string(),
+            memo: String::new(),
+        },
+    ];
+    let items: Vec<Item> = transactions.iter().map(Item::from).collect();
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    let result = rules.categorize(&items, &genres);
+    assert_eq!(result.stats.unknown_genre_rules, vec![1]);
+    assert_eq!((result.stats.matched, result.stats.unmatched), (1, 1));
+    
+    for expense in result.to_expenses() {
+        client.add_expense(&expense).await.expect("Failed to add expense");
+    }
+    let expenses = client.get_expenses().await.expect("Failed to get expenses");
+    let added = expenses.iter().find(|e| e.genre == genre_name).expect("Expense should be added");
+    assert_eq!((added.date.as_str(), added.amount), ("2025-01-15", 1540));
+    
+    client.delete_expense(added.id).await.expect("Failed to delete expense");
+    if let Some(genre) = client.get_genres().await.unwrap().iter().find(|g| g.name == genre_name) {
+        client.delete_genre(genre.id).await.expect("Failed to delete genre");
+    }
+}
+
+// MARK: - Profile Tests
+
+#[cfg(feature = "config")]
+#[tokio::test]
+async fn test_client_from_config_profile() {
+    use shishutsukan::config::Config;
+    
+    let path = std::env::temp_dir().join(format!("{}.toml", generate_unique_name("shishutsukan_config")));
+    std::fs::write(
+        &path,
+        "timeout_secs = 10\n\n[profiles.local]\nbase_url = \"http://localhost:8000\"\ntoken = \"test-token\"\n\n[profiles.offline]\nbase_url = \"http://127.0.0.1:9\"\n",
+    ).expect("Failed to write config");
+    let config = Config::open(&path).expect("Failed to load config");
+    std::fs::remove_file(&path).ok();
+    
+    let profile = config.profile(Some("local")).expect("Failed to resolve profile");
+    assert_eq!(profile.timeout_secs, Some(10));
+    let client = profile.client().expect("Failed to create client");
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    assert!(!genres.is_empty());
+    
+    let offline = config.profile(Some("offline")).unwrap().client().unwrap();
+    assert!(offline.get_genres().await.unwrap_err().is_connectivity_error());
+    assert!(config.profile(Some("missing")).is_err());
+}
+
+// MARK: - CLI Tests
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_cli_expense_add_list_delete() {
+    use std::process::Command;
+    
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    let genre_name = generate_unique_name("CLI");
+    let cli = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_shishutsukan"))
+            .args(args)
+            .env("SHISHUTSUKAN_URL", "http://localhost:8000")
+            .env("SHISHUTSUKAN_CONFIG", "/nonexistent/config.toml")
+            .output()
+            .expect("Failed to run CLI")
+    };
+    
+    assert!(cli(&["genre", "add", &genre_name]).status.success());
+    assert!(cli(&["expense", "add", &genre_name, "1234", "--date", "2025-01-15"]).status.success());
+    
+    let output = cli(&["expense", "list", "--genre", &genre_name, "--format", "json"]);
+    assert!(output.status.success());
+    let listed: Vec<shishutsukan::ExpenseWithId> = serde_json::from_slice(&output.stdout).expect("Failed to parse JSON output");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].amount, 1234);
+    
+    let output = cli(&["expense", "list", "--genre", &genre_name, "--format", "csv"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&format!("2025-01-15,{},1234", genre_name)));
+    
+    // Connection failures map to EX_UNAVAILABLE
+    let output = cli(&["genre", "list", "--url", "http://127.0.0.1:9"]);
+    assert_eq!(output.status.code(), Some(69));
+    
+    assert!(cli(&["expense", "delete", &listed[0].id.to_string()]).status.success());
+    let genres = client.get_genres().await.expect("Failed to get genres");
+    if let Some(genre) = genres.iter().find(|g| g.name == genre_name) {
+        assert!(cli(&["genre", "delete", &genre.id.to_string()]).status.success());
+    }
+}
+
 // MARK: - Error Handling Tests
 
 #[tokio::test]
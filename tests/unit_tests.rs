@@ -1,5 +1,10 @@
 /// Unit tests that verify basic functionality without requiring a server
-use shishutsukan::{Expense, Genre, ShishutsukanClient};
+use shishutsukan::{
+    find_duplicates, CacheStats, DedupePlan, DuplicateCriteria, Expense, ExpenseLedger,
+    ExpenseWithId, Genre, GenreWithId, OfflineClient, PendingOperation, ShishutsukanClient,
+    WriteOutcome,
+};
+use std::time::Duration;
 
 #[test]
 fn test_client_instantiation() {
@@ -53,3 +58,988 @@ fn test_genre_deserialization() {
     let genre: Genre = serde_json::from_str(json).expect("Failed to deserialize");
     assert_eq!(genre.name, "娯楽費");
 }
+
+#[test]
+fn test_cache_disabled_by_default() {
+    let client = ShishutsukanClient::new("http://localhost:8000");
+    assert_eq!(client.cache_stats(), None);
+}
+
+#[test]
+fn test_cache_stats_shared_across_clones() {
+    let client =
+        ShishutsukanClient::new("http://localhost:8000").with_cache(Duration::from_secs(60));
+    let cloned = client.clone();
+    assert_eq!(cloned.cache_stats(), Some(CacheStats::default()));
+}
+
+#[test]
+#[should_panic(expected = "burst must be at least 1")]
+fn test_rate_limit_rejects_zero_burst() {
+    let _ = ShishutsukanClient::new("http://localhost:8000").with_rate_limit(1.0, 0);
+}
+
+#[test]
+#[should_panic(expected = "requests_per_second must be a positive finite number")]
+fn test_rate_limit_rejects_non_positive_rate() {
+    let _ = ShishutsukanClient::new("http://localhost:8000").with_rate_limit(0.0, 1);
+}
+
+#[test]
+fn test_expense_with_id_matches() {
+    let row = ExpenseWithId {
+        id: 1,
+        date: "2025-01-15".to_string(),
+        genre: "食費".to_string(),
+        amount: 1000,
+    };
+    let expense = Expense::new("2025-01-15".to_string(), "食費".to_string(), 1000);
+    assert!(row.matches(&expense));
+    assert_eq!(row.to_expense(), expense);
+    assert!(!row.matches(&Expense::new(
+        "2025-01-15".to_string(),
+        "食費".to_string(),
+        999
+    )));
+}
+
+#[test]
+fn test_ledger_open_missing_file() {
+    let path =
+        std::env::temp_dir().join(format!("shishutsukan_ledger_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let ledger = ExpenseLedger::open(&path).expect("Failed to open ledger");
+    assert!(ledger.entries().is_empty());
+    assert!(
+        !path.exists(),
+        "Ledger file should not be created until updated"
+    );
+}
+
+fn expense_with_id(id: i32, date: &str, genre: &str, amount: i32) -> ExpenseWithId {
+    ExpenseWithId {
+        id,
+        date: date.to_string(),
+        genre: genre.to_string(),
+        amount,
+    }
+}
+
+fn genre_with_id(id: i32, name: &str) -> GenreWithId {
+    GenreWithId {
+        id,
+        name: name.to_string(),
+        created_at: "2025-01-01 00:00:00".to_string(),
+    }
+}
+
+#[test]
+fn test_find_exact_duplicates() {
+    let expenses = vec![
+        expense_with_id(3, "2025-01-15", "食費", 1000),
+        expense_with_id(1, "2025-01-15", "食費", 1000),
+        expense_with_id(2, "2025-01-15", "交通費", 1000),
+        expense_with_id(4, "2025-01-16", "食費", 1000),
+    ];
+    let groups = find_duplicates(&expenses, &DuplicateCriteria::exact());
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].keep.id, 1);
+    assert_eq!(
+        groups[0]
+            .duplicates
+            .iter()
+            .map(|e| e.id)
+            .collect::<Vec<_>>(),
+        vec![3]
+    );
+    assert!(groups[0].is_exact());
+}
+
+#[test]
+fn test_find_near_duplicates() {
+    let expenses = vec![
+        expense_with_id(1, "2025-01-31", "食費", 1000),
+        expense_with_id(2, "2025-02-01", "食費", 1010),
+        expense_with_id(3, "2025-02-10", "食費", 1000),
+    ];
+    let groups = find_duplicates(&expenses, &DuplicateCriteria::near(2, 20));
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].keep.id, 1);
+    assert_eq!(groups[0].duplicates[0].id, 2);
+    assert!(groups[0].score > 0.0 && groups[0].score < 1.0);
+
+    // Only groups above the score threshold are planned for deletion
+    assert_eq!(DedupePlan::from_groups(&groups, 0.5).deletions.len(), 1);
+    assert!(DedupePlan::from_groups(&groups, 1.0).deletions.is_empty());
+}
+
+#[test]
+fn test_near_duplicates_are_not_chained() {
+    // 2 is close to 1, and 3 is close to 2 but not to 1
+    let expenses = vec![
+        expense_with_id(1, "2025-01-01", "食費", 1000),
+        expense_with_id(2, "2025-01-03", "食費", 1000),
+        expense_with_id(3, "2025-01-05", "食費", 1000),
+        expense_with_id(4, "2025-01-06", "食費", 1010),
+    ];
+    let groups = find_duplicates(&expenses, &DuplicateCriteria::near(2, 20));
+    let ids: Vec<(i32, Vec<i32>)> = groups
+        .iter()
+        .map(|group| {
+            (
+                group.keep.id,
+                group.duplicates.iter().map(|e| e.id).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(ids, vec![(1, vec![2]), (3, vec![4])]);
+    assert!(groups.iter().all(|group| group.score > 0.0));
+}
+
+#[cfg(feature = "mirror")]
+#[test]
+fn test_mirror_detects_inserts_and_deletions() {
+    use shishutsukan::mirror::Mirror;
+
+    let mut mirror = Mirror::open_in_memory().expect("Failed to open mirror");
+    let genres = vec![genre_with_id(1, "食費")];
+    let first = vec![
+        expense_with_id(1, "2025-01-15", "食費", 1000),
+        expense_with_id(2, "2025-02-01", "食費", 500),
+    ];
+    let summary = mirror.apply(&genres, &first).expect("Failed to apply");
+    assert_eq!(summary.expenses_inserted, 2);
+    assert_eq!(summary.genres_inserted, 1);
+
+    let second = vec![
+        expense_with_id(2, "2025-02-01", "食費", 500),
+        expense_with_id(3, "2025-02-02", "食費", 300),
+    ];
+    let summary = mirror.apply(&genres, &second).expect("Failed to apply");
+    assert_eq!(summary.expenses_inserted, 1);
+    assert_eq!(summary.expenses_deleted, 1);
+    assert_eq!(summary.genres_inserted, 0);
+    assert!(mirror
+        .apply(&genres, &second)
+        .expect("Failed to apply")
+        .is_unchanged());
+
+    let from = chrono::NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+    let to = chrono::NaiveDate::from_ymd_opt(2025, 2, 28).unwrap();
+    assert_eq!(mirror.expenses_between(from, to).unwrap(), second);
+    assert_eq!(
+        mirror.totals_by_genre(from, to).unwrap(),
+        vec![("食費".to_string(), 800)]
+    );
+    assert!(mirror.last_synced_at().unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_offline_client_queues_when_unreachable() {
+    let path =
+        std::env::temp_dir().join(format!("shishutsukan_journal_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // Nothing listens on the discard port, so connections are refused immediately
+    let client = ShishutsukanClient::new("http://127.0.0.1:9");
+    let mut offline = OfflineClient::open(client.clone(), &path).expect("Failed to open journal");
+
+    let genre = Genre::new("旅行".to_string());
+    let expense = Expense::new("2025-01-15".to_string(), "旅行".to_string(), 12000);
+    assert_eq!(
+        offline
+            .add_genre(&genre)
+            .await
+            .expect("Failed to queue genre"),
+        WriteOutcome::Queued
+    );
+    assert_eq!(
+        offline
+            .add_expense(&expense)
+            .await
+            .expect("Failed to queue expense"),
+        WriteOutcome::Queued
+    );
+
+    // The journal survives a restart
+    let mut reopened = OfflineClient::open(client, &path).expect("Failed to reopen journal");
+    assert_eq!(
+        reopened.pending(),
+        &[
+            PendingOperation::AddGenre { genre },
+            PendingOperation::AddExpense { expense },
+        ]
+    );
+
+    // Flushing while still offline keeps everything queued
+    let report = reopened.flush().await.expect("Failed to flush");
+    assert!(report.applied.is_empty());
+    assert_eq!(report.remaining, 2);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_sync_diff_detects_changes_on_both_sides() {
+    use shishutsukan::sync::{diff, SyncConflictKind, SyncState};
+
+    let mut state = SyncState {
+        checkpoint: vec![
+            expense_with_id(1, "2025-01-01", "食費", 100),
+            expense_with_id(2, "2025-01-02", "食費", 200),
+            expense_with_id(3, "2025-01-03", "食費", 300),
+        ],
+        ..SyncState::default()
+    };
+    for row in &state.checkpoint.clone() {
+        state.records.push(shishutsukan::sync::LocalRecord {
+            server_id: Some(row.id),
+            expense: row.to_expense(),
+            deleted: false,
+        });
+    }
+    // Local: modify #1, delete #2, add two new rows
+    state.update(
+        0,
+        Expense::new("2025-01-01".to_string(), "食費".to_string(), 150),
+    );
+    state.delete(1);
+    state.add(Expense::new(
+        "2025-01-04".to_string(),
+        "交通費".to_string(),
+        400,
+    ));
+    state.add(Expense::new(
+        "2025-01-05".to_string(),
+        "交通費".to_string(),
+        500,
+    ));
+
+    // Server: #1 deleted, #3 untouched, the second local row was already uploaded as #5, #4 is new
+    let server = vec![
+        expense_with_id(2, "2025-01-02", "食費", 200),
+        expense_with_id(3, "2025-01-03", "食費", 300),
+        expense_with_id(4, "2025-01-06", "日用品", 600),
+        expense_with_id(5, "2025-01-05", "交通費", 500),
+    ];
+
+    let changes = diff(&state, &server);
+    assert_eq!(changes.conflicts.len(), 1);
+    assert_eq!(changes.conflicts[0].kind, SyncConflictKind::ModifiedDeleted);
+    assert_eq!(changes.local_deleted, vec![1]);
+    assert_eq!(changes.local_added, vec![3]);
+    assert_eq!(changes.linked.len(), 1);
+    assert_eq!(changes.linked[0].1.id, 5);
+    assert_eq!(
+        changes
+            .remote_added
+            .iter()
+            .map(|e| e.id)
+            .collect::<Vec<_>>(),
+        vec![4]
+    );
+    assert!(changes.remote_deleted.is_empty());
+}
+
+#[test]
+fn test_sync_state_delete_unsynced_record() {
+    use shishutsukan::sync::SyncState;
+
+    let mut state = SyncState::default();
+    state.add(Expense::new(
+        "2025-01-04".to_string(),
+        "交通費".to_string(),
+        400,
+    ));
+    assert!(state.delete(0));
+    assert!(state.records.is_empty());
+    assert!(!state.delete(0));
+}
+
+#[test]
+fn test_csv_export_escapes_and_formats() {
+    use shishutsukan::export::csv::{write_expenses, CsvOptions, ExpenseColumn, HeaderLanguage};
+
+    let expenses = vec![expense_with_id(1, "2025-01-15", "食費, 外食", 1000)];
+    let options = CsvOptions {
+        header: Some(HeaderLanguage::English),
+        date_format: Some("%Y年%m月%d日".to_string()),
+        ..CsvOptions::default()
+    };
+    let mut output = Vec::new();
+    write_expenses(
+        &mut output,
+        &expenses,
+        &[ExpenseColumn::Date, ExpenseColumn::Genre],
+        &options,
+    )
+    .expect("Failed to write CSV");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "date,genre\n2025年01月15日,\"食費, 外食\"\n"
+    );
+
+    // Invalid formats and formats asking for a time are errors, not panics, and nothing is written
+    for format in ["%Q", "%Y %H:%M", "%F %z"] {
+        let options = CsvOptions {
+            date_format: Some(format.to_string()),
+            ..CsvOptions::excel()
+        };
+        let mut output = Vec::new();
+        let result = write_expenses(&mut output, &expenses, &ExpenseColumn::ALL, &options);
+        assert!(
+            matches!(result, Err(shishutsukan::ShishutsukanError::ParseError(_))),
+            "{}",
+            format
+        );
+        assert!(output.is_empty());
+    }
+}
+
+#[test]
+fn test_csv_export_genres_for_excel() {
+    use shishutsukan::export::csv::{write_genres, CsvOptions, GenreColumn};
+
+    let genres = vec![genre_with_id(1, "食費")];
+    let mut output = Vec::new();
+    let options = CsvOptions {
+        delimiter: '\t',
+        ..CsvOptions::excel()
+    };
+    write_genres(&mut output, &genres, &GenreColumn::ALL, &options).expect("Failed to write CSV");
+    assert!(output.starts_with(b"\xEF\xBB\xBF"));
+    assert_eq!(
+        String::from_utf8(output[3..].to_vec()).unwrap(),
+        "ID\tジャンル名\t作成日時\r\n1\t食費\t2025-01-01 00:00:00\r\n"
+    );
+}
+
+#[test]
+fn test_csv_import_reports_invalid_rows() {
+    use shishutsukan::import::csv::{parse, ColumnMapping, RowIssue};
+
+    let input = "2025年1月15日\t1,234円\t\n2025/1/16\t不明\t交際費\n2025-02-30\t500\t食費\n";
+    let mapping = ColumnMapping::new(0, 1)
+        .genre(2)
+        .default_genre("雑費")
+        .delimiter('\t')
+        .without_header();
+    let rows = parse(input, &mapping).expect("Failed to parse CSV");
+
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[0].expense,
+        Some(Expense::new(
+            "2025-01-15".to_string(),
+            "雑費".to_string(),
+            1234
+        ))
+    );
+    assert_eq!(
+        rows[1].issues,
+        vec![RowIssue::InvalidAmount("不明".to_string())]
+    );
+    assert_eq!(
+        rows[2].issues,
+        vec![RowIssue::InvalidDate("2025-02-30".to_string())]
+    );
+    assert!(rows[1].expense.is_none());
+}
+
+#[test]
+fn test_csv_import_auto_creates_missing_genres() {
+    use shishutsukan::import::csv::{check_genres, parse, ColumnMapping};
+
+    let mapping: ColumnMapping =
+        serde_json::from_str(r#"{"date": "日付", "amount": "金額", "genre": "ジャンル"}"#).unwrap();
+    let input = "日付,ジャンル,金額\n2025-01-15,食費,100\n2025-01-16,新ジャンル,200\n2025-01-17,新ジャンル,300\n";
+    let rows = parse(input, &mapping).expect("Failed to parse CSV");
+    let genres = vec![genre_with_id(1, "食費")];
+
+    let report = check_genres(rows, &genres, true);
+    assert!(report.is_clean());
+    assert_eq!(report.new_genres, vec!["新ジャンル".to_string()]);
+
+    let missing_column = ColumnMapping::new("日付", "価格");
+    assert!(parse(input, &missing_column).is_err());
+}
+
+#[test]
+fn test_backup_json_round_trip_and_version_check() {
+    use shishutsukan::{Backup, BackupFormat, BACKUP_VERSION};
+
+    let genres = vec![genre_with_id(3, "食費")];
+    let backup = Backup::new(genres, vec![expense_with_id(7, "2025-01-15", "食費", 1000)]);
+    let mut output = Vec::new();
+    backup
+        .write_to(&mut output, BackupFormat::Json)
+        .expect("Failed to write backup");
+    assert_eq!(
+        Backup::read_from(output.as_slice()).expect("Failed to read backup"),
+        backup
+    );
+
+    let newer = Backup {
+        version: BACKUP_VERSION + 1,
+        ..backup
+    };
+    let mut output = Vec::new();
+    newer
+        .write_to(&mut output, BackupFormat::Json)
+        .expect("Failed to write backup");
+    assert!(Backup::read_from(output.as_slice()).is_err());
+}
+
+#[test]
+fn test_statement_to_expenses_keeps_debits_and_applies_rules() {
+    use shishutsukan::import::qif;
+    use shishutsukan::import::statement::{to_expenses, GenreRules};
+
+    let input = "!Type:Bank\nD01/10/2025\nT-1,280\nPイオン\n^\nD01/11/2025\nT250000\nP給与\n^\nD01/12/2025\nT-980\nP不明な店\nM定期券\n^\nD01/13/2025\nT-3,000\nP謎の店\n^\n";
+    let transactions = qif::parse(input).expect("Failed to parse QIF");
+    let rules: GenreRules = serde_json::from_str(
+        r#"{"rules": [{"keyword": "イオン", "genre": "食費"}, {"keyword": "定期", "genre": "交通費"}]}"#,
+    )
+    .unwrap();
+
+    let import = to_expenses(&transactions, &rules);
+    assert_eq!(import.skipped_credits, 1);
+    assert_eq!(import.unmatched.len(), 1);
+    assert_eq!(import.unmatched[0].payee, "謎の店");
+    assert_eq!(
+        import
+            .expenses
+            .iter()
+            .map(|(e, _)| (e.genre.as_str(), e.amount))
+            .collect::<Vec<_>>(),
+        vec![("食費", 1280), ("交通費", 980)]
+    );
+    assert_eq!(
+        import.expenses[0].1.as_deref(),
+        Some("qif:2025-01-10:-1280:イオン:1")
+    );
+
+    let with_default = to_expenses(&transactions, &rules.clone().default_genre("雑費"));
+    assert!(with_default.unmatched.is_empty());
+    assert_eq!(with_default.expenses.len(), 3);
+}
+
+#[test]
+fn test_beancount_export_opens_accounts() {
+    use shishutsukan::export::ledger::{write_transactions, AccountMapping, LedgerFormat};
+
+    let expenses = vec![
+        expense_with_id(2, "2025-01-20", "交通費", 500),
+        expense_with_id(1, "2025-01-15", "食費", 1000),
+    ];
+    let genres = vec![genre_with_id(9, "娯楽費")];
+    let mapping = AccountMapping::new()
+        .prefix("Expenses:Living")
+        .account("交通費", "Expenses:Transport");
+
+    let mut output = Vec::new();
+    write_transactions(
+        &mut output,
+        &expenses,
+        &genres,
+        &mapping,
+        LedgerFormat::Beancount,
+    )
+    .expect("Failed to write journal");
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "2025-01-15 open Assets:Cash JPY\n\
+         2025-01-15 open Expenses:Living:娯楽費 JPY\n\
+         2025-01-15 open Expenses:Living:食費 JPY\n\
+         2025-01-15 open Expenses:Transport JPY\n\
+         \n\
+         2025-01-15 * \"食費\"\n  id: \"1\"\n  Expenses:Living:食費  1000 JPY\n  Assets:Cash\n\
+         \n\
+         2025-01-20 * \"交通費\"\n  id: \"2\"\n  Expenses:Transport  500 JPY\n  Assets:Cash\n"
+    );
+}
+
+#[test]
+fn test_household_genre_mapping_table() {
+    use shishutsukan::import::household::{parse, GenreMappingTable, HouseholdApp};
+
+    let input = "計算対象,日付,内容,金額（円）,保有金融機関,大項目,中項目,メモ,振替,ID\n\
+                 1,2025/02/01,居酒屋,-4000,カード,食費,外食,,0,a\n\
+                 1,2025/02/02,書店,-1500,カード,教養・教育,書籍,,0,b\n\
+                 1,2025/02/03,書店,-800,カード,教養・教育,書籍,,0,c\n\
+                 1,2025/02/04,口座振替,-50000,銀行,未分類,未分類,,1,d\n\
+                 0,2025/02/05,立替,-3000,カード,食費,外食,,0,e\n";
+    let import = parse(input.as_bytes(), HouseholdApp::MoneyForward).expect("Failed to parse CSV");
+    assert_eq!(import.transactions.len(), 3);
+    assert_eq!(import.skipped_transfers, 1);
+    assert_eq!(import.skipped_excluded, 1);
+
+    let genres = vec![genre_with_id(1, "外食")];
+    let mut table = GenreMappingTable::build(&import.transactions, &genres);
+    assert_eq!(table.genre_for("食費", "外食"), Some("外食"));
+    assert_eq!(table.missing_genres(), vec!["教養・教育".to_string()]);
+    assert_eq!(table.entries[1].count, 2);
+
+    table.set("教養・教育", None, "外食");
+    assert!(table.missing_genres().is_empty());
+    let expenses = table.to_expenses(&import.transactions);
+    assert_eq!(
+        expenses[1].0,
+        Expense::new("2025-02-02".to_string(), "外食".to_string(), 1500)
+    );
+    assert_eq!(expenses[1].1.as_deref(), Some("mf:b"));
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_xlsx_export_writes_workbook() {
+    use shishutsukan::export::xlsx::write_workbook;
+
+    let expenses = vec![
+        expense_with_id(1, "2025-01-15", "食費", 1000),
+        expense_with_id(2, "2025-02-03", "交通費", 640),
+    ];
+    let mut output = Vec::new();
+    write_workbook(&mut output, &expenses, &[]).expect("Failed to write workbook");
+    // xlsx はZIPアーカイブ
+    assert!(output.starts_with(b"PK\x03\x04"));
+}
+
+#[test]
+fn test_report_aggregates_by_month_year_and_genre() {
+    use shishutsukan::report::Report;
+
+    let expenses = vec![
+        expense_with_id(1, "2024-12-31", "食費", 500),
+        expense_with_id(2, "2025-01-15", "食費", 1200),
+        expense_with_id(3, "2025-01-20", "交通費", 300),
+        expense_with_id(4, "2025-02-01", "食費", 800),
+        expense_with_id(5, "不明", "食費", 9999),
+    ];
+    let report = Report::new(&expenses);
+    assert_eq!(report.skipped, 1);
+    assert_eq!(report.overall.unwrap().total, 2800);
+    assert_eq!(
+        report
+            .years
+            .iter()
+            .map(|y| y.period.as_str())
+            .collect::<Vec<_>>(),
+        vec!["2024", "2025"]
+    );
+    assert_eq!(report.years[1].stats.count, 3);
+    assert_eq!(report.month_keys(), vec!["2024-12", "2025-01", "2025-02"]);
+
+    let january = &report.months[1].stats;
+    assert_eq!(
+        (january.count, january.total, january.min, january.max),
+        (2, 1500, 300, 1200)
+    );
+    assert_eq!(january.average, 750.0);
+
+    assert_eq!(report.genres[0].genre, "食費");
+    assert_eq!(report.genres[0].stats.total, 2500);
+    assert_eq!(report.genres[0].month_total("2025-01"), 1200);
+    assert_eq!(report.genres[1].month_total("2025-02"), 0);
+
+    let table = report.render_genres();
+    assert!(table.starts_with("ジャンル  2024-12  2025-01  2025-02    合計\n"));
+    assert!(table.contains("食費         ¥500   ¥1,200     ¥800  ¥2,500\n"));
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["months"][1]["stats"]["total"], 1500);
+}
+
+#[test]
+fn test_budget_store_round_trip_and_evaluation() {
+    use chrono::NaiveDate;
+    use shishutsukan::budget::{Budget, BudgetPeriod, BudgetStore, BudgetTarget};
+
+    let path =
+        std::env::temp_dir().join(format!("shishutsukan_budgets_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut store = BudgetStore::open(&path).expect("Failed to open budgets");
+    store.set(Budget::monthly("食費", 30000)).unwrap();
+    store.set(Budget::monthly("食費", 20000)).unwrap();
+    store
+        .set(Budget::annual(2, 100000).with_rollover())
+        .unwrap();
+    store.set(Budget::monthly(99, 1000)).unwrap();
+
+    let store = BudgetStore::open(&path).expect("Failed to reopen budgets");
+    assert_eq!(store.budgets().len(), 3);
+    assert_eq!(store.budgets()[0].limit, 20000);
+
+    let genres = vec![genre_with_id(2, "交通費")];
+    let expenses = vec![
+        expense_with_id(1, "2025-06-01", "食費", 25000),
+        expense_with_id(2, "2025-06-02", "交通費", 1000),
+        expense_with_id(3, "2025-05-31", "食費", 5000),
+    ];
+    let as_of = NaiveDate::from_ymd_opt(2025, 6, 15).unwrap();
+    let report = store.evaluate(&expenses, &genres, as_of);
+    assert_eq!(report.unresolved.len(), 1);
+
+    let food = &report.statuses[0];
+    assert_eq!((food.spent, food.remaining), (25000, -5000));
+    assert_eq!(food.percentage, Some(125.0));
+    assert!(food.overspent && !food.projected_overspend);
+    assert_eq!(report.overspent().count(), 1);
+
+    let transport = &report.statuses[1];
+    assert_eq!(transport.period, BudgetPeriod::Annual);
+    assert_eq!(transport.remaining, 99000);
+    assert_eq!(report.at_risk().count(), 0);
+
+    let mut store = store;
+    assert!(store
+        .remove(&BudgetTarget::GenreId(99), BudgetPeriod::Monthly)
+        .unwrap());
+    assert_eq!(BudgetStore::open(&path).unwrap().budgets().len(), 2);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_recurring_plan_skips_existing_and_materialized() {
+    use chrono::{NaiveDate, Weekday};
+    use shishutsukan::recurring::{plan, RecurringTemplate, Schedule};
+
+    let date = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+    let rent = RecurringTemplate::new("家賃", "住居費", 80000, Schedule::EndOfMonth, date(1, 1));
+    let mut gym = RecurringTemplate::new(
+        "ジム",
+        "健康",
+        1000,
+        Schedule::Weekly(Weekday::Mon),
+        date(1, 1),
+    )
+    .ends_on(date(1, 31));
+    gym.materialized_through = Some(date(1, 13));
+
+    let existing = vec![expense_with_id(7, "2025-01-31", "住居費", 80000)];
+    let planned = plan(&[rent, gym], &existing, date(2, 28));
+    let summary: Vec<(&str, &str, Option<i32>)> = planned
+        .iter()
+        .map(|p| (p.template.as_str(), p.expense.date.as_str(), p.existing))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("家賃", "2025-01-31", Some(7)),
+            ("家賃", "2025-02-28", None),
+            ("ジム", "2025-01-20", None),
+            ("ジム", "2025-01-27", None),
+        ]
+    );
+}
+
+#[test]
+fn test_detect_recurring_patterns() {
+    use chrono::{NaiveDate, Weekday};
+    use shishutsukan::recurring::detect::{detect, DetectionOptions};
+    use shishutsukan::recurring::Schedule;
+
+    let mut expenses = vec![
+        // 月末の家賃（金額が少し変わっても同じパターン）
+        expense_with_id(1, "2025-01-31", "住居費", 80000),
+        expense_with_id(2, "2025-02-28", "住居費", 80000),
+        expense_with_id(3, "2025-03-31", "住居費", 82000),
+        expense_with_id(4, "2025-04-30", "住居費", 82000),
+        // 不規則な食費
+        expense_with_id(5, "2025-01-03", "食費", 1200),
+        expense_with_id(6, "2025-01-04", "食費", 1100),
+        expense_with_id(7, "2025-02-20", "食費", 1150),
+        expense_with_id(8, "2025-02-21", "食費", 1180),
+    ];
+    // 毎週土曜のジム
+    for (id, day) in [(9, 4), (10, 11), (11, 18), (12, 25)] {
+        expenses.push(expense_with_id(
+            id,
+            &format!("2025-01-{:02}", day),
+            "健康",
+            500,
+        ));
+    }
+
+    let patterns = detect(&expenses, &DetectionOptions::default());
+    assert_eq!(patterns.len(), 2);
+
+    let gym = patterns.iter().find(|p| p.genre == "健康").unwrap();
+    assert_eq!(gym.schedule, Schedule::Weekly(Weekday::Sat));
+    assert_eq!(gym.interval_days, 7);
+    assert_eq!(gym.confidence, 1.0);
+    assert_eq!(
+        gym.next_expected,
+        NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()
+    );
+    assert!(gym.is_lapsed(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(), 7));
+
+    let rent = patterns.iter().find(|p| p.genre == "住居費").unwrap();
+    assert_eq!(rent.schedule, Schedule::EndOfMonth);
+    assert_eq!((rent.min_amount, rent.max_amount), (80000, 82000));
+    assert_eq!(rent.expense_ids, vec![1, 2, 3, 4]);
+    assert_eq!(
+        rent.next_expected,
+        NaiveDate::from_ymd_opt(2025, 5, 31).unwrap()
+    );
+
+    let template = rent.to_template("家賃");
+    assert_eq!(
+        template.pending_dates(NaiveDate::from_ymd_opt(2025, 5, 31).unwrap()),
+        vec![rent.next_expected]
+    );
+
+    // 金額の許容範囲を狭めると家賃は2つに分かれ、件数が足りなくなる
+    let strict = DetectionOptions::default().amount_tolerance(0.01);
+    assert!(detect(&expenses, &strict)
+        .iter()
+        .all(|p| p.genre != "住居費"));
+}
+
+#[test]
+fn test_anomaly_detection_flags_outliers_and_spikes() {
+    use shishutsukan::anomaly::{detect, AnomalyOptions};
+
+    let mut expenses = Vec::new();
+    let mut id = 0;
+    // 1〜6月は毎月 ¥20,000 前後、7月は ¥48,000
+    for (month, amounts) in [
+        (1, vec![9000, 11000]),
+        (2, vec![8500, 11500]),
+        (3, vec![10000, 10000]),
+        (4, vec![9500, 10500]),
+        (5, vec![12000, 8000]),
+        (6, vec![10000, 10000]),
+        (7, vec![10000, 38000]),
+    ] {
+        for amount in amounts {
+            id += 1;
+            expenses.push(expense_with_id(
+                id,
+                &format!("2025-{:02}-10", month),
+                "食費",
+                amount,
+            ));
+        }
+    }
+    expenses.push(expense_with_id(100, "2025-07-01", "対象外", 999999));
+
+    let genres = vec![genre_with_id(1, "食費")];
+    let report = detect(&expenses, &genres, &AnomalyOptions::default());
+
+    assert_eq!(report.expenses.len(), 1);
+    assert_eq!(report.expenses[0].expense.amount, 38000);
+    assert_eq!(report.expenses[0].median, 10000.0);
+    assert!(report.expenses[0].score > 3.5);
+
+    assert_eq!(report.months.len(), 1);
+    let spike = &report.months[0];
+    assert_eq!(
+        (spike.genre.as_str(), spike.month.as_str(), spike.total),
+        ("食費", "2025-07", 48000)
+    );
+    assert_eq!(spike.baseline_months, 6);
+    assert_eq!(
+        spike.explanation(),
+        "2025-07 の食費の合計 ¥48,000 は直近6か月の中央値 ¥20,000 の2.4倍です"
+    );
+    assert_eq!(report.explanations().len(), 2);
+
+    let relaxed = AnomalyOptions::default()
+        .spike_ratio(3.0)
+        .score_threshold(100.0);
+    assert!(detect(&expenses, &genres, &relaxed).is_empty());
+}
+
+#[test]
+fn test_forecast_combines_run_rate_seasonal_and_recurring() {
+    use chrono::NaiveDate;
+    use shishutsukan::forecast::{forecast, ForecastOptions, Horizon};
+    use shishutsukan::recurring::{RecurringTemplate, Schedule};
+
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    let expenses = vec![
+        expense_with_id(1, "2024-06-01", "食費", 1000),
+        expense_with_id(2, "2024-06-20", "食費", 6000),
+        expense_with_id(3, "2025-05-25", "住居費", 80000),
+        expense_with_id(4, "2025-06-05", "食費", 3000),
+    ];
+    let rent = RecurringTemplate::new(
+        "家賃",
+        "住居費",
+        80000,
+        Schedule::MonthlyOnDay(25),
+        date(2025, 1, 1),
+    );
+
+    let result = forecast(
+        &expenses,
+        &[rent],
+        date(2025, 6, 15),
+        &ForecastOptions::default(),
+    );
+
+    let food = result.month.iter().find(|f| f.genre == "食費").unwrap();
+    assert_eq!(
+        (food.actual, food.recurring, food.run_rate),
+        (3000, 0, 3000)
+    );
+    // 前年の6月16日〜30日は ¥6,000
+    assert_eq!(food.seasonal, Some(6000));
+    assert_eq!(food.projected, 7500);
+    assert!(food.low <= food.projected && food.projected <= food.high);
+    assert!(food.low >= food.actual);
+
+    let rent_month = &result.month[0];
+    assert_eq!(
+        (
+            rent_month.genre.as_str(),
+            rent_month.actual,
+            rent_month.projected
+        ),
+        ("住居費", 0, 80000)
+    );
+
+    // 定期的な支出は実績とテンプレートから求め、ペースには含めない
+    let rent_year = result.year.iter().find(|f| f.genre == "住居費").unwrap();
+    assert_eq!(
+        (rent_year.actual, rent_year.recurring, rent_year.run_rate),
+        (80000, 560000, 0)
+    );
+    assert_eq!(rent_year.projected, 640000);
+
+    assert_eq!(result.total(Horizon::EndOfMonth).0, 87500);
+}
+
+#[test]
+fn test_auth_debug_hides_secrets() {
+    use shishutsukan::Auth;
+
+    let client = ShishutsukanClient::new("http://localhost:8000")
+        .with_auth(Auth::Bearer("secret-token".to_string()));
+    assert!(!format!("{:?}", client).contains("secret-token"));
+
+    let basic = Auth::Basic {
+        username: "family".to_string(),
+        password: Some("hunter2".to_string()),
+    };
+    let debug = format!("{:?}", basic);
+    assert!(debug.contains("family"));
+    assert!(!debug.contains("hunter2"));
+}
+
+#[test]
+fn test_quick_entry_parses_phrases() {
+    use chrono::NaiveDate;
+    use shishutsukan::quick_entry::{QuickEntryParser, Synonyms};
+
+    let genres: Vec<GenreWithId> = ["食費", "交通費", "日用品", "娯楽費"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| genre_with_id(i as i32 + 1, name))
+        .collect();
+    let today = NaiveDate::from_ymd_opt(2025, 1, 20).unwrap();
+    let parser = QuickEntryParser::new(&genres);
+
+    let expense = parser
+        .parse("昨日 ランチ 850円", today)
+        .unwrap()
+        .to_expense()
+        .unwrap();
+    assert_eq!(
+        expense,
+        Expense::new("2025-01-19".to_string(), "食費".to_string(), 850)
+    );
+
+    let expense = parser
+        .parse("１/１５ 交通費 ￥１，２００", today)
+        .unwrap()
+        .to_expense()
+        .unwrap();
+    assert_eq!(
+        expense,
+        Expense::new("2025-01-15".to_string(), "交通費".to_string(), 1200)
+    );
+
+    // 飲み会は食費と娯楽費のどちらにもなりうる
+    let entry = parser.parse("飲み会 4000", today).unwrap();
+    assert!(entry.to_expense().is_none());
+    assert_eq!(entry.candidates.len(), 2);
+
+    // 独自の同義語の表
+    let synonyms: Synonyms = serde_json::from_str(r#"{"飲み会": ["娯楽費"]}"#).unwrap();
+    let entry = QuickEntryParser::new(&genres)
+        .synonyms(synonyms)
+        .parse("飲み会 4000", today)
+        .unwrap();
+    assert_eq!(entry.genre.as_deref(), Some("娯楽費"));
+}
+
+#[cfg(feature = "rules")]
+#[test]
+fn test_rules_first_match_and_stats() {
+    use chrono::{NaiveDate, Weekday};
+    use shishutsukan::rules::{Condition, Item, Rule, RuleSet};
+
+    let genres: Vec<GenreWithId> = ["食費", "交通費"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| genre_with_id(i as i32 + 1, name))
+        .collect();
+    let mut rules = RuleSet::new();
+    rules
+        .push(
+            Rule::new("食費")
+                .named("平日の昼食")
+                .merchant("セブン")
+                .amount_range(None, Some(1500))
+                .weekdays([
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                ]),
+        )
+        .unwrap();
+    rules
+        .push(Rule::new("交通費").description("(?i)suica|pasmo"))
+        .unwrap();
+    rules
+        .push(Rule::new("日用品").description("ドラッグ"))
+        .unwrap();
+
+    // 2025-01-15 は水曜日、2025-01-18 は土曜日
+    let wed = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+    let sat = NaiveDate::from_ymd_opt(2025, 1, 18).unwrap();
+    let items = vec![
+        Item::new(wed, 680, "弁当").merchant("ＳＥＶＥＮ セブン－イレブン 新宿店"),
+        Item::new(sat, 680, "弁当").merchant("セブン-イレブン"),
+        Item::new(wed, 3000, "Suica チャージ"),
+        Item::new(wed, 980, "ドラッグストア"),
+        Item::new(sat, 1200, "弁当").merchant("セブン-イレブン"),
+    ];
+    let result = rules.categorize(&items, &genres);
+    let assigned: Vec<Option<&str>> = result.items.iter().map(|c| c.genre.as_deref()).collect();
+    assert_eq!(
+        assigned,
+        vec![Some("食費"), None, Some("交通費"), None, None]
+    );
+    assert_eq!(result.stats.rule_hits, vec![1, 1, 0]);
+    assert_eq!(result.stats.unknown_genre_rules, vec![2]);
+    assert_eq!(
+        (result.stats.unmatched, result.stats.unmatched_amount),
+        (3, 2860)
+    );
+    assert_eq!(result.stats.top_unmatched[0].key, "セブン-イレブン");
+    assert_eq!(result.stats.top_unmatched[0].count, 2);
+    assert_eq!(result.to_expenses().len(), 2);
+
+    let explanation = rules.explain(&items[1], &genres);
+    assert_eq!(explanation.rule, None);
+    assert_eq!(explanation.checks[0].failed, vec![Condition::Weekday]);
+    assert_eq!(
+        explanation.checks[2].failed,
+        vec![Condition::Description, Condition::Genre]
+    );
+}